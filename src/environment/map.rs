@@ -1,12 +1,23 @@
+use std::collections::HashMap;
+
 use noise::{NoiseFn, Perlin};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
+use super::pheromone::{PheromoneConfig, PheromoneField};
+use super::restriction::Restrictions;
+use super::structure::{footprint_tiles, Structure, StructureType};
 use super::tile::{MapTile, Resource, ResourceType, TileType};
 
 pub const TERRAIN_SCALE: f64 = 6.0;
 pub const RESOURCE_SCALE: f64 = 2.0;
 const RESOURCE_PROBABILITY: f64 = 0.1;
 const THRESHOLD: f64 = 0.3;
+// Sous le seuil du relief, le sol reste praticable mais accidenté
+const ROUGH_THRESHOLD: f64 = 0.15;
+// Temps de traversée d'une tuile, relatif à un sol dégagé
+pub const ROUGH_COST: f32 = 2.0;
+pub const ROAD_COST: f32 = 0.5;
 
 #[derive(Debug, Clone)]
 pub struct Map {
@@ -15,6 +26,12 @@ pub struct Map {
     pub grid: Vec<MapTile>,
     pub seed: u32,
     pub base_position: (usize, usize),
+    pub structures: HashMap<(usize, usize), Structure>,
+    rough: Vec<bool>,
+    // Couche facultative : absente, les robots ne déposent ni ne suivent de traces
+    pub pheromones: Option<PheromoneField>,
+    // Zones interdites ou à éviter tracées par l'opérateur
+    pub restrictions: Restrictions,
 }
 
 impl Map {
@@ -22,9 +39,16 @@ impl Map {
         let mut map = Map {
             width,
             height,
-            grid: vec![MapTile::new(0, 0, TileType::Empty); width * height],
+            // Chaque tuile porte ses coordonnées, y compris les tuiles vides jamais réécrites
+            grid: (0..width * height)
+                .map(|index| MapTile::new(index % width, index / width, TileType::Empty))
+                .collect(),
             seed,
             base_position: (0, 0),
+            structures: HashMap::new(),
+            rough: vec![false; width * height],
+            pheromones: None,
+            restrictions: Restrictions::default(),
         };
 
         map.generate_terrain();
@@ -33,6 +57,17 @@ impl Map {
         map
     }
 
+    pub fn enable_pheromones(&mut self, enabled: bool) {
+        self.pheromones = match enabled {
+            true => Some(PheromoneField::new(
+                self.width,
+                self.height,
+                PheromoneConfig::default(),
+            )),
+            false => None,
+        };
+    }
+
     fn get_index(&self, x: usize, y: usize) -> usize {
         y * self.width + x
     }
@@ -54,6 +89,9 @@ impl Map {
                 let noise_value = perlin.get([x as f64 / TERRAIN_SCALE, y as f64 / TERRAIN_SCALE]);
                 if noise_value > THRESHOLD {
                     self.set(MapTile::new(x, y, TileType::Terrain));
+                } else if noise_value > ROUGH_THRESHOLD {
+                    let idx = self.get_index(x, y);
+                    self.rough[idx] = true;
                 }
             }
         }
@@ -61,7 +99,7 @@ impl Map {
 
     fn place_resources(&mut self) {
         let perlin = Perlin::new(self.seed);
-        let mut rng = rand::rng();
+        let mut rng = StdRng::seed_from_u64(self.seed as u64);
 
         for y in 0..self.height {
             for x in 0..self.width {
@@ -89,7 +127,7 @@ impl Map {
     }
 
     fn place_science_base(&mut self) {
-        let mut rng = rand::rng();
+        let mut rng = StdRng::seed_from_u64(self.seed as u64);
 
         loop {
             let x = rng.random_range(1..self.width - 1);
@@ -109,6 +147,112 @@ impl Map {
     }
 
     pub fn is_valid(&self, x: usize, y: usize) -> bool {
-        x < self.width && y < self.height && (self.get(x, y).tile == TileType::Empty)
+        x < self.width && y < self.height && self.get(x, y).tile.is_walkable()
+    }
+
+    pub fn is_rough(&self, x: usize, y: usize) -> bool {
+        self.rough[self.get_index(x, y)]
+    }
+
+    // Les routes accélèrent, le sol accidenté ralentit
+    pub fn travel_cost(&self, x: usize, y: usize) -> f32 {
+        match self.structures.get(&(x, y)) {
+            Some(structure) if structure.built && structure.kind == StructureType::Road => ROAD_COST,
+            _ if self.is_rough(x, y) => ROUGH_COST,
+            _ => 1.0,
+        }
+    }
+
+    // Tuile à afficher lorsqu'aucun robot ne l'occupe
+    pub fn ground_tile(&self, x: usize, y: usize) -> TileType {
+        if (x, y) == self.base_position {
+            TileType::Base
+        } else if let Some(structure) = self.structures.get(&(x, y)) {
+            TileType::Structure(*structure)
+        } else {
+            TileType::Empty
+        }
+    }
+
+    pub fn vacate(&mut self, x: usize, y: usize) {
+        let tile = self.ground_tile(x, y);
+        self.set(MapTile::new(x, y, tile));
+    }
+
+    pub fn can_build(&self, kind: StructureType, x: usize, y: usize) -> bool {
+        let (base_x, base_y) = self.base_position;
+        footprint_tiles(kind, x, y).iter().all(|&(tile_x, tile_y)| {
+            tile_x < self.width
+                && tile_y < self.height
+                && self.get(tile_x, tile_y).tile == TileType::Empty
+                && !self.structures.contains_key(&(tile_x, tile_y))
+                && tile_x.abs_diff(base_x) + tile_y.abs_diff(base_y) >= kind.min_base_distance()
+        })
+    }
+
+    // Premier emplacement valide, du plus proche au plus éloigné de la base
+    pub fn find_build_site(&self, kind: StructureType) -> Option<(usize, usize)> {
+        let (base_x, base_y) = self.base_position;
+        let mut sites: Vec<(usize, usize)> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.can_build(kind, x, y))
+            .collect();
+        sites.sort_by_key(|&(x, y)| x.abs_diff(base_x) + y.abs_diff(base_y));
+        sites.first().copied()
+    }
+
+    pub fn place_structure(&mut self, structure: Structure) {
+        for (x, y) in structure.tiles() {
+            self.structures.insert((x, y), structure);
+            if matches!(self.get(x, y).tile, TileType::Empty | TileType::Structure(_)) {
+                self.set(MapTile::new(x, y, TileType::Structure(structure)));
+            }
+        }
+    }
+
+    pub fn complete_structure(&mut self, origin: (usize, usize)) -> Option<Structure> {
+        let mut structure = *self.structures.get(&origin)?;
+        structure.built = true;
+        self.place_structure(structure);
+        Some(structure)
+    }
+
+    pub fn is_near_charger(&self, x: usize, y: usize) -> bool {
+        (y.saturating_sub(1)..=y + 1).any(|near_y| {
+            (x.saturating_sub(1)..=x + 1).any(|near_x| {
+                self.structures
+                    .get(&(near_x, near_y))
+                    .is_some_and(|structure| structure.built && structure.kind.charges_robots())
+            })
+        })
+    }
+
+    pub fn built_structures(&self) -> Vec<Structure> {
+        let mut structures: Vec<Structure> = self
+            .structures
+            .iter()
+            .filter(|(position, structure)| structure.built && **position == structure.origin)
+            .map(|(_, structure)| *structure)
+            .collect();
+        structures.sort_by_key(|structure| structure.origin);
+        structures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_built_outpost_charges_robots_next_to_it() {
+        let mut map = Map::new(25, 25, 7);
+        map.place_structure(Structure::new(StructureType::Outpost, (10, 10)));
+        // Un chantier ne recharge rien tant qu'il n'est pas terminé
+        assert!(!map.is_near_charger(12, 11));
+
+        map.complete_structure((10, 10));
+        // L'emprise couvre (10..=11, 10..=11) : le robot voisin de la tuile (11, 11) se recharge
+        assert!(map.is_near_charger(12, 11));
+        assert!(!map.is_near_charger(13, 11));
     }
 }
@@ -1,2 +1,5 @@
 pub mod map;
+pub mod pheromone;
+pub mod restriction;
+pub mod structure;
 pub mod tile;
\ No newline at end of file
@@ -0,0 +1,150 @@
+use std::fmt;
+
+// En dessous de ce seuil, une trace est considérée comme évaporée
+const TRACE_THRESHOLD: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PheromoneKind {
+    // « Déjà exploré » : repousse les explorateurs les uns des autres
+    Explored,
+    // « Chemin de ressource » : déposé par les récolteurs chargés, attire les suivants
+    ResourcePath,
+}
+
+impl PheromoneKind {
+    fn index(&self) -> usize {
+        match self {
+            PheromoneKind::Explored => 0,
+            PheromoneKind::ResourcePath => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PheromoneConfig {
+    // Part de chaque trace perdue à chaque tick
+    pub evaporation: f32,
+    // Part de chaque trace répartie entre les quatre voisines à chaque tick
+    pub diffusion: f32,
+    pub deposit: f32,
+}
+
+impl Default for PheromoneConfig {
+    fn default() -> Self {
+        PheromoneConfig {
+            evaporation: 0.02,
+            diffusion: 0.1,
+            deposit: 1.0,
+        }
+    }
+}
+
+impl PheromoneConfig {
+    pub fn adjust(&mut self, setting: PheromoneSetting, increase: bool) {
+        let value = match setting {
+            PheromoneSetting::Evaporation => &mut self.evaporation,
+            PheromoneSetting::Diffusion => &mut self.diffusion,
+        };
+        let step = if increase { 0.01 } else { -0.01 };
+        *value = (*value + step).clamp(0.0, 1.0);
+    }
+
+    pub fn value(&self, setting: PheromoneSetting) -> f32 {
+        match setting {
+            PheromoneSetting::Evaporation => self.evaporation,
+            PheromoneSetting::Diffusion => self.diffusion,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PheromoneSetting {
+    Evaporation,
+    Diffusion,
+}
+
+impl PheromoneSetting {
+    pub const ALL: [PheromoneSetting; 2] = [PheromoneSetting::Evaporation, PheromoneSetting::Diffusion];
+}
+
+impl fmt::Display for PheromoneSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PheromoneSetting::Evaporation => write!(f, "Evaporation"),
+            PheromoneSetting::Diffusion => write!(f, "Diffusion"),
+        }
+    }
+}
+
+// Couche de phéromones superposée à la carte : une intensité par tuile et par type
+#[derive(Debug, Clone)]
+pub struct PheromoneField {
+    width: usize,
+    height: usize,
+    pub config: PheromoneConfig,
+    levels: [Vec<f32>; 2],
+}
+
+impl PheromoneField {
+    pub fn new(width: usize, height: usize, config: PheromoneConfig) -> Self {
+        PheromoneField {
+            width,
+            height,
+            config,
+            levels: [vec![0.0; width * height], vec![0.0; width * height]],
+        }
+    }
+
+    pub fn deposit(&mut self, kind: PheromoneKind, x: usize, y: usize) {
+        let index = y * self.width + x;
+        self.levels[kind.index()][index] += self.config.deposit;
+    }
+
+    pub fn level(&self, kind: PheromoneKind, x: usize, y: usize) -> f32 {
+        self.levels[kind.index()][y * self.width + x]
+    }
+
+    pub fn max_level(&self, kind: PheromoneKind) -> f32 {
+        self.levels[kind.index()].iter().copied().fold(0.0, f32::max)
+    }
+
+    // Diffusion vers les voisines puis évaporation ; la part qui sortirait de la carte reste sur place
+    pub fn tick(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let PheromoneConfig {
+            evaporation,
+            diffusion,
+            ..
+        } = self.config;
+
+        for levels in self.levels.iter_mut() {
+            let mut next = levels.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let level = levels[y * width + x];
+                    if level < TRACE_THRESHOLD {
+                        continue;
+                    }
+                    let share = level * diffusion / 4.0;
+                    let neighbours = [
+                        (x > 0).then(|| (x - 1, y)),
+                        (x + 1 < width).then_some((x + 1, y)),
+                        (y > 0).then(|| (x, y - 1)),
+                        (y + 1 < height).then_some((x, y + 1)),
+                    ];
+                    for (neighbour_x, neighbour_y) in neighbours.into_iter().flatten() {
+                        next[neighbour_y * width + neighbour_x] += share;
+                        next[y * width + x] -= share;
+                    }
+                }
+            }
+            for level in next.iter_mut() {
+                *level *= 1.0 - evaporation;
+                if *level < TRACE_THRESHOLD {
+                    *level = 0.0;
+                }
+            }
+            *levels = next;
+        }
+    }
+}
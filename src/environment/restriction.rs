@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::robots::robot::RobotType;
+
+// Pas supplémentaires comptés par la recherche de chemin pour traverser une tuile à éviter
+pub const AVOID_PENALTY: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestrictionKind {
+    // Interdite : aucun robot concerné n'y entre
+    NoGo,
+    // Traversable, mais contournée dès que le détour coûte moins que la pénalité
+    Avoid,
+}
+
+impl RestrictionKind {
+    pub const ALL: [RestrictionKind; 2] = [RestrictionKind::NoGo, RestrictionKind::Avoid];
+}
+
+impl fmt::Display for RestrictionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestrictionKind::NoGo => write!(f, "No-go"),
+            RestrictionKind::Avoid => write!(f, "Avoid"),
+        }
+    }
+}
+
+// Région contrainte par l'opérateur, pour tous les robots ou un seul type
+#[derive(Debug, Clone)]
+pub struct Restriction {
+    pub id: usize,
+    pub kind: RestrictionKind,
+    pub robot_type: Option<RobotType>,
+    pub tiles: HashSet<(usize, usize)>,
+}
+
+impl Restriction {
+    fn applies_to(&self, robot_type: RobotType) -> bool {
+        self.robot_type.is_none_or(|restricted| restricted == robot_type)
+    }
+}
+
+// Tuiles interdites à un type de robot, avec le nombre de pas qui sépare chacune de la plus proche
+// tuile autorisée de la carte
+#[derive(Debug, Clone)]
+pub struct NoGoArea {
+    tiles: HashSet<(usize, usize)>,
+    escape: HashMap<(usize, usize), usize>,
+}
+
+impl NoGoArea {
+    fn new(tiles: HashSet<(usize, usize)>, width: usize, height: usize) -> Self {
+        let neighbours = |(x, y): (usize, usize)| {
+            [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)]
+                .into_iter()
+                .filter(|(x, y)| *x < width && *y < height)
+        };
+        // Parcours en largeur depuis le bord de la zone ; une zone qui couvre toute la carte n'a pas d'issue
+        let mut queue: VecDeque<((usize, usize), usize)> = tiles
+            .iter()
+            .copied()
+            .filter(|tile| neighbours(*tile).any(|next| !tiles.contains(&next)))
+            .map(|tile| (tile, 1))
+            .collect();
+        let mut escape = HashMap::new();
+        while let Some((tile, distance)) = queue.pop_front() {
+            if escape.contains_key(&tile) {
+                continue;
+            }
+            escape.insert(tile, distance);
+            queue.extend(
+                neighbours(tile)
+                    .filter(|next| tiles.contains(next) && !escape.contains_key(next))
+                    .map(|next| (next, distance + 1)),
+            );
+        }
+        NoGoArea { tiles, escape }
+    }
+
+    pub fn contains(&self, position: (usize, usize)) -> bool {
+        self.tiles.contains(&position)
+    }
+
+    // Une tuile interdite n'est permise que si elle rapproche strictement de la sortie : un robot sur
+    // lequel la zone a été tracée en sort par le plus court chemin, sans jamais la traverser
+    pub fn allows(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        match (self.escape.get(&to), self.escape.get(&from)) {
+            (None, _) => !self.contains(to),
+            (Some(to), Some(from)) => to < from,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Restrictions {
+    next_id: usize,
+    rules: Vec<Restriction>,
+}
+
+impl Restrictions {
+    pub fn add(&mut self, kind: RestrictionKind, robot_type: Option<RobotType>, tiles: HashSet<(usize, usize)>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.rules.push(Restriction {
+            id,
+            kind,
+            robot_type,
+            tiles,
+        });
+        id
+    }
+
+    pub fn clear(&mut self) -> usize {
+        std::mem::take(&mut self.rules).len()
+    }
+
+    pub fn rules(&self) -> &[Restriction] {
+        &self.rules
+    }
+
+    pub fn is_no_go(&self, robot_type: RobotType, position: (usize, usize)) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.kind == RestrictionKind::NoGo && rule.applies_to(robot_type) && rule.tiles.contains(&position)
+        })
+    }
+
+    pub fn no_go(&self, robot_type: RobotType, width: usize, height: usize) -> NoGoArea {
+        let tiles = self
+            .rules
+            .iter()
+            .filter(|rule| rule.kind == RestrictionKind::NoGo && rule.applies_to(robot_type))
+            .flat_map(|rule| rule.tiles.iter().copied())
+            .collect();
+        NoGoArea::new(tiles, width, height)
+    }
+
+    // Les pénalités de zones superposées s'additionnent
+    pub fn penalties(&self, robot_type: RobotType) -> HashMap<(usize, usize), u32> {
+        let mut penalties = HashMap::new();
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.kind == RestrictionKind::Avoid && rule.applies_to(robot_type))
+        {
+            for tile in &rule.tiles {
+                *penalties.entry(*tile).or_insert(0) += AVOID_PENALTY;
+            }
+        }
+        penalties
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StructureType {
+    RelayAntenna,
+    ChargingStation,
+    Road,
+    Outpost,
+    // Point de dépôt intermédiaire entre les gisements et la base
+    Depot,
+}
+
+impl StructureType {
+    pub const ALL: [StructureType; 5] = [
+        StructureType::RelayAntenna,
+        StructureType::ChargingStation,
+        StructureType::Road,
+        StructureType::Outpost,
+        StructureType::Depot,
+    ];
+
+    pub fn mineral_cost(&self) -> u32 {
+        match self {
+            StructureType::RelayAntenna => 6,
+            StructureType::ChargingStation => 8,
+            StructureType::Road => 2,
+            StructureType::Outpost => 15,
+            StructureType::Depot => 10,
+        }
+    }
+
+    pub fn build_ticks(&self) -> u32 {
+        match self {
+            StructureType::RelayAntenna => 8,
+            StructureType::ChargingStation => 10,
+            StructureType::Road => 3,
+            StructureType::Outpost => 20,
+            StructureType::Depot => 12,
+        }
+    }
+
+    // Emprise (largeur, hauteur) en tuiles à partir de l'origine du chantier
+    pub fn footprint(&self) -> (usize, usize) {
+        match self {
+            StructureType::RelayAntenna => (1, 1),
+            StructureType::ChargingStation => (1, 1),
+            StructureType::Road => (3, 1),
+            StructureType::Outpost => (2, 2),
+            StructureType::Depot => (1, 1),
+        }
+    }
+
+    // Distance minimale (Manhattan) entre la base et chaque tuile de l'emprise
+    pub fn min_base_distance(&self) -> usize {
+        match self {
+            StructureType::RelayAntenna => 6,
+            StructureType::ChargingStation => 3,
+            StructureType::Road => 2,
+            StructureType::Outpost => 8,
+            StructureType::Depot => 6,
+        }
+    }
+
+    // Un avant-poste relaie la radio comme une antenne et recharge les robots comme une borne
+    pub fn relays_radio(&self) -> bool {
+        matches!(self, StructureType::RelayAntenna | StructureType::Outpost)
+    }
+
+    pub fn charges_robots(&self) -> bool {
+        matches!(self, StructureType::ChargingStation | StructureType::Outpost)
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        matches!(self, StructureType::Road)
+    }
+
+    pub fn char(&self) -> char {
+        match self {
+            StructureType::RelayAntenna => '📡',
+            StructureType::ChargingStation => '🔌',
+            StructureType::Road => '🛤',
+            StructureType::Outpost => '🏕',
+            StructureType::Depot => '📦',
+        }
+    }
+}
+
+impl fmt::Display for StructureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StructureType::RelayAntenna => "Relay antenna",
+            StructureType::ChargingStation => "Charging station",
+            StructureType::Road => "Road",
+            StructureType::Outpost => "Outpost",
+            StructureType::Depot => "Depot",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Structure {
+    pub kind: StructureType,
+    pub origin: (usize, usize),
+    pub built: bool,
+}
+
+impl Structure {
+    pub fn new(kind: StructureType, origin: (usize, usize)) -> Self {
+        Structure {
+            kind,
+            origin,
+            built: false,
+        }
+    }
+
+    pub fn tiles(&self) -> Vec<(usize, usize)> {
+        footprint_tiles(self.kind, self.origin.0, self.origin.1)
+    }
+}
+
+pub fn footprint_tiles(kind: StructureType, x: usize, y: usize) -> Vec<(usize, usize)> {
+    let (width, height) = kind.footprint();
+    (0..height)
+        .flat_map(|dy| (0..width).map(move |dx| (x + dx, y + dy)))
+        .collect()
+}
@@ -1,7 +1,10 @@
 use std::char;
 
+use crate::robots::registry::registry;
 use crate::robots::robot::RobotType;
 
+use super::structure::Structure;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TileType {
     Empty,
@@ -9,6 +12,8 @@ pub enum TileType {
     Base,
     Resource(Resource),
     Robot(RobotType),
+    BrokenRobot(RobotType),
+    Structure(Structure),
 }
 
 impl TileType {
@@ -21,12 +26,22 @@ impl TileType {
                 ResourceType::Energy => '⚡',
                 ResourceType::Mineral => '💎',
             },
-            TileType::Robot(robot) => match robot {
-                RobotType::Explorer => '🚜',
-                RobotType::Harvester => '🤖',
+            TileType::Robot(robot) => registry().read().unwrap().glyph(*robot),
+            TileType::BrokenRobot(_) => '💥',
+            TileType::Structure(structure) => match structure.built {
+                true => structure.kind.char(),
+                false => '🚧',
             },
         }
     }
+
+    pub fn is_walkable(&self) -> bool {
+        match self {
+            TileType::Empty => true,
+            TileType::Structure(structure) => structure.built && structure.kind.is_walkable(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -36,12 +51,24 @@ pub struct MapTile {
     pub tile: TileType,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ResourceType {
     Energy,
     Mineral,
 }
 
+impl ResourceType {
+    pub const ALL: [ResourceType; 2] = [ResourceType::Energy, ResourceType::Mineral];
+
+    // Poids d'une unité dans la soute d'un robot
+    pub fn unit_weight(&self) -> u32 {
+        match self {
+            ResourceType::Energy => 1,
+            ResourceType::Mineral => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Resource {
     pub scale: u32,
@@ -0,0 +1,50 @@
+use std::fmt;
+
+// Réserve gardée pour absorber les détours du chemin de retour
+const RETURN_MARGIN: u32 = 5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Battery {
+    capacity: u32,
+    charge: u32,
+}
+
+impl Battery {
+    pub fn new(capacity: u32) -> Self {
+        Battery {
+            capacity,
+            charge: capacity,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: u32) {
+        self.capacity = capacity;
+        self.charge = self.charge.min(capacity);
+    }
+
+    pub fn drain(&mut self, amount: u32) {
+        self.charge = self.charge.saturating_sub(amount);
+    }
+
+    pub fn recharge(&mut self, amount: u32) {
+        self.charge = (self.charge + amount).min(self.capacity);
+    }
+
+    pub fn refill(&mut self) {
+        self.charge = self.capacity;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.charge == 0
+    }
+
+    pub fn needs_return(&self, distance_to_base: usize) -> bool {
+        self.charge <= distance_to_base as u32 + RETURN_MARGIN
+    }
+}
+
+impl fmt::Display for Battery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.charge, self.capacity)
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use super::perception::{self, Perception};
+use super::robot::{Robot, RobotState};
+
+// Les arbres sont relus à chaque envoi de robot : pas besoin de recompiler pour les modifier
+pub const BEHAVIOUR_DIR: &str = "assets/behaviours";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    Returning,
+    HasTarget,
+    ResourceKnown,
+    CargoFull,
+    TargetDepleted,
+}
+
+impl Condition {
+    const ALL: [Condition; 5] = [
+        Condition::Returning,
+        Condition::HasTarget,
+        Condition::ResourceKnown,
+        Condition::CargoFull,
+        Condition::TargetDepleted,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Condition::Returning => "returning",
+            Condition::HasTarget => "has_target",
+            Condition::ResourceKnown => "resource_known",
+            Condition::CargoFull => "cargo_full",
+            Condition::TargetDepleted => "target_depleted",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|condition| condition.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Explore,
+    ReturnToBase,
+    TargetNearestResource,
+    BroadcastDeposit,
+    Harvest,
+    FollowPheromones,
+    // Couvre la zone ou suit la ronde fixée par l'opérateur
+    Survey,
+}
+
+impl Action {
+    const ALL: [Action; 7] = [
+        Action::Explore,
+        Action::ReturnToBase,
+        Action::TargetNearestResource,
+        Action::BroadcastDeposit,
+        Action::Harvest,
+        Action::FollowPheromones,
+        Action::Survey,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Explore => "explore",
+            Action::ReturnToBase => "return_to_base",
+            Action::TargetNearestResource => "target_nearest_resource",
+            Action::BroadcastDeposit => "broadcast_deposit",
+            Action::Harvest => "harvest",
+            Action::FollowPheromones => "follow_pheromones",
+            Action::Survey => "survey",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Sequence(Vec<Node>),
+    Selector(Vec<Node>),
+    Invert(Box<Node>),
+    Succeed(Box<Node>),
+    Condition(Condition),
+    Action(Action),
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Sequence(_) => write!(f, "sequence"),
+            Node::Selector(_) => write!(f, "selector"),
+            Node::Invert(_) => write!(f, "invert"),
+            Node::Succeed(_) => write!(f, "succeed"),
+            Node::Condition(condition) => write!(f, "{}?", condition.name()),
+            Node::Action(action) => write!(f, "{}", action.name()),
+        }
+    }
+}
+
+// Ce qu'un robot doit savoir évaluer pour être piloté par un arbre.
+// Une condition ou une action qu'il ne connaît pas échoue.
+pub trait Agent {
+    fn check(&self, _condition: Condition, _perception: &Perception) -> bool {
+        false
+    }
+
+    // Une action qui bouge le robot retient son intention ; une seule par tick
+    fn act(&mut self, _action: Action, _perception: &Perception) -> Status {
+        Status::Failure
+    }
+}
+
+// Action commune : un pas vers la base, réussie une fois arrivé
+pub fn return_step<R: Robot>(robot: &mut R, perception: &Perception) -> (Status, perception::Action) {
+    robot.set_state(RobotState::ReturningToBase);
+    let intent = robot.return_to_base(perception);
+    match robot.get_state() {
+        RobotState::Reporting => (Status::Success, intent),
+        _ => (Status::Running, intent),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BehaviourTree {
+    root: Node,
+}
+
+impl BehaviourTree {
+    // Évalue l'arbre depuis la racine et renvoie le chemin du nœud resté en cours
+    pub fn tick(&self, agent: &mut dyn Agent, perception: &Perception) -> (Status, Option<String>) {
+        let mut path = Vec::new();
+        let status = tick_node(&self.root, agent, perception, &mut path);
+        let running = match status {
+            Status::Running => Some(path.join(" > ")),
+            _ => None,
+        };
+        (status, running)
+    }
+
+    // Format texte indenté : un nœud par ligne, les enfants plus indentés que leur parent.
+    // Les lignes vides et celles commençant par '#' sont ignorées.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let lines: Vec<Line> = source
+            .lines()
+            .enumerate()
+            .filter(|(_, text)| !text.trim().is_empty() && !text.trim_start().starts_with('#'))
+            .map(|(index, text)| Line {
+                number: index + 1,
+                indent: text.len() - text.trim_start().len(),
+                words: text.split_whitespace().collect(),
+            })
+            .collect();
+
+        if lines.is_empty() {
+            return Err("empty behaviour tree".to_string());
+        }
+
+        let mut position = 0;
+        let root = parse_node(&lines, &mut position)?;
+        if let Some(extra) = lines.get(position) {
+            return Err(format!("line {}: only one root node is allowed", extra.number));
+        }
+        Ok(BehaviourTree { root })
+    }
+}
+
+fn tick_node(node: &Node, agent: &mut dyn Agent, perception: &Perception, path: &mut Vec<String>) -> Status {
+    path.push(node.to_string());
+    let status = match node {
+        Node::Sequence(children) => tick_children(children, Status::Success, agent, perception, path),
+        Node::Selector(children) => tick_children(children, Status::Failure, agent, perception, path),
+        Node::Invert(child) => match tick_node(child, agent, perception, path) {
+            Status::Success => Status::Failure,
+            Status::Failure => Status::Success,
+            Status::Running => Status::Running,
+        },
+        Node::Succeed(child) => match tick_node(child, agent, perception, path) {
+            Status::Running => Status::Running,
+            _ => Status::Success,
+        },
+        Node::Condition(condition) => match agent.check(*condition, perception) {
+            true => Status::Success,
+            false => Status::Failure,
+        },
+        Node::Action(action) => agent.act(*action, perception),
+    };
+    // Le chemin n'est conservé que jusqu'au nœud en cours
+    if status != Status::Running {
+        path.pop();
+    }
+    status
+}
+
+// Une séquence continue tant que ses enfants réussissent, un sélecteur tant qu'ils échouent
+fn tick_children(
+    children: &[Node],
+    keep_going: Status,
+    agent: &mut dyn Agent,
+    perception: &Perception,
+    path: &mut Vec<String>,
+) -> Status {
+    for child in children {
+        let status = tick_node(child, agent, perception, path);
+        if status != keep_going {
+            return status;
+        }
+    }
+    keep_going
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    words: Vec<&'a str>,
+}
+
+fn parse_node(lines: &[Line], position: &mut usize) -> Result<Node, String> {
+    let line = &lines[*position];
+    *position += 1;
+
+    let mut children = Vec::new();
+    let child_indent = lines
+        .get(*position)
+        .map(|next| next.indent)
+        .filter(|indent| *indent > line.indent);
+    if let Some(child_indent) = child_indent {
+        while let Some(next) = lines.get(*position) {
+            if next.indent <= line.indent {
+                break;
+            }
+            if next.indent != child_indent {
+                return Err(format!("line {}: inconsistent indentation", next.number));
+            }
+            children.push(parse_node(lines, position)?);
+        }
+    }
+
+    let error = |message: &str| Err(format!("line {}: {}", line.number, message));
+    match line.words.as_slice() {
+        ["sequence"] | ["selector"] if children.is_empty() => error("composite node without children"),
+        ["sequence"] => Ok(Node::Sequence(children)),
+        ["selector"] => Ok(Node::Selector(children)),
+        ["invert"] | ["succeed"] if children.len() != 1 => error("decorator needs exactly one child"),
+        ["invert"] => Ok(Node::Invert(Box::new(children.remove(0)))),
+        ["succeed"] => Ok(Node::Succeed(Box::new(children.remove(0)))),
+        ["condition", _] | ["action", _] if !children.is_empty() => error("leaf node cannot have children"),
+        ["condition", name] => match Condition::from_name(name) {
+            Some(condition) => Ok(Node::Condition(condition)),
+            None => error(&format!("unknown condition '{}'", name)),
+        },
+        ["action", name] => match Action::from_name(name) {
+            Some(action) => Ok(Node::Action(action)),
+            None => error(&format!("unknown action '{}'", name)),
+        },
+        _ => error(&format!("cannot read '{}'", line.words.join(" "))),
+    }
+}
+
+// Fichier d'arbre d'un type de robot, avec une copie embarquée si le fichier est absent
+#[derive(Debug, Clone, Copy)]
+pub struct BehaviourSource {
+    pub file: &'static str,
+    pub builtin: &'static str,
+}
+
+impl BehaviourSource {
+    pub fn load(&self) -> Result<BehaviourTree, String> {
+        let source = match fs::read_to_string(Path::new(BEHAVIOUR_DIR).join(self.file)) {
+            Ok(source) => source,
+            Err(error) if error.kind() == ErrorKind::NotFound => self.builtin.to_string(),
+            Err(error) => return Err(error.to_string()),
+        };
+        BehaviourTree::parse(&source)
+    }
+
+    pub fn load_builtin(&self) -> BehaviourTree {
+        BehaviourTree::parse(self.builtin).expect("built-in behaviour tree must be valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE: &str = "
+# Récolteur minimal
+selector
+  sequence
+    condition cargo_full
+
+    action return_to_base
+  invert
+    action harvest
+";
+
+    #[test]
+    fn parses_a_valid_tree() {
+        let tree = BehaviourTree::parse(TREE).unwrap();
+        let Node::Selector(children) = &tree.root else {
+            panic!("root should be a selector, got {}", tree.root);
+        };
+        let names: Vec<String> = children.iter().map(|child| child.to_string()).collect();
+        assert_eq!(names, ["sequence", "invert"]);
+
+        let Node::Sequence(steps) = &children[0] else {
+            unreachable!();
+        };
+        assert!(matches!(steps[..], [Node::Condition(Condition::CargoFull), Node::Action(Action::ReturnToBase)]));
+        assert!(matches!(&children[1], Node::Invert(child) if matches!(**child, Node::Action(Action::Harvest))));
+    }
+
+    #[test]
+    fn rejects_an_unknown_node_name() {
+        let error = BehaviourTree::parse("selector\n  action fly\n").unwrap_err();
+        assert_eq!(error, "line 2: unknown action 'fly'");
+        let error = BehaviourTree::parse("sequence\n  condition sunny\n").unwrap_err();
+        assert_eq!(error, "line 2: unknown condition 'sunny'");
+        let error = BehaviourTree::parse("parallel\n  action explore\n").unwrap_err();
+        assert_eq!(error, "line 1: cannot read 'parallel'");
+    }
+
+    #[test]
+    fn rejects_bad_indentation() {
+        // Deux frères doivent être indentés de la même façon
+        let error = BehaviourTree::parse("selector\n    action explore\n  action harvest\n").unwrap_err();
+        assert_eq!(error, "line 3: inconsistent indentation");
+        // Un nœud revenu au niveau de la racine en serait une seconde
+        let error = BehaviourTree::parse("selector\n  action explore\naction harvest\n").unwrap_err();
+        assert_eq!(error, "line 3: only one root node is allowed");
+        // Une feuille indentée sous une autre feuille devient son enfant
+        let error = BehaviourTree::parse("sequence\n  action explore\n    action harvest\n").unwrap_err();
+        assert_eq!(error, "line 2: leaf node cannot have children");
+    }
+
+    #[test]
+    fn the_bundled_trees_are_valid() {
+        for source in [
+            include_str!("../../assets/behaviours/explorer.bt"),
+            include_str!("../../assets/behaviours/harvester.bt"),
+            include_str!("../../assets/behaviours/swarm.bt"),
+        ] {
+            assert!(BehaviourTree::parse(source).is_ok());
+        }
+    }
+}
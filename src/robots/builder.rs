@@ -0,0 +1,165 @@
+use super::registry::{RobotConfig, RobotKind, RobotRole, Spawn};
+use super::perception::{Action, Perception, Route};
+use super::robot::{Order, Robot, RobotState, RobotType};
+use super::sensor::{LocalKnowledge, SensorSpec};
+use crate::environment::structure::StructureType;
+use crate::simulation::simulation::Simulation;
+
+pub fn builder_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::BUILDER,
+        glyph: '👷',
+        role: RobotRole::Construction,
+        manual_dispatch: false,
+        config: RobotConfig {
+            energy_cost: 3,
+            mineral_cost: 5,
+            build_ticks: 5,
+            sensor: SensorSpec::default(),
+            speed: 1.0,
+            battery_capacity: None,
+            cargo: None,
+            breaks_down: true,
+            modules: &[],
+        },
+        behaviour: None,
+        constructor: |spawn| Box::new(Builder::new(spawn)),
+        on_arrival,
+        on_abandon,
+        resumes_order: false,
+    }
+}
+
+fn on_arrival(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(Order::Build(kind, x, y)) = robot.order() {
+        simulation.log_event(format!(
+            "Builder {} completed {} at ({}, {})",
+            robot.get_id(),
+            kind,
+            x,
+            y
+        ));
+    }
+    simulation.dock(robot);
+}
+
+fn on_abandon(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(Order::Build(kind, x, y)) = robot.order() {
+        simulation.log_event(format!("{} site ({}, {}) left unfinished", kind, x, y));
+    }
+}
+
+pub struct Builder {
+    id: usize,
+    x: usize,
+    y: usize,
+    state: RobotState,
+    knowledge: LocalKnowledge,
+    order: Option<(StructureType, usize, usize)>,
+    progress: u32,
+    notes: Vec<String>,
+}
+
+impl Robot for Builder {
+    fn get_type(&self) -> RobotType {
+        RobotType::BUILDER
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    fn get_state(&self) -> RobotState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: RobotState) {
+        self.state = state;
+    }
+
+    fn set_position(&mut self, x: usize, y: usize) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn update(&mut self, perception: &Perception) -> Action {
+        self.knowledge.observe(&perception.tiles);
+        match self.state {
+            RobotState::Building => self.build(perception),
+            RobotState::ReturningToBase => self.return_to_base(perception),
+            _ => Action::Wait,
+        }
+    }
+
+    fn knowledge(&self) -> &LocalKnowledge {
+        &self.knowledge
+    }
+
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge {
+        &mut self.knowledge
+    }
+
+    fn order(&self) -> Option<Order> {
+        self.order.map(|(kind, x, y)| Order::Build(kind, x, y))
+    }
+
+    fn set_order(&mut self, order: Option<Order>) {
+        self.order = match order {
+            Some(Order::Build(kind, x, y)) => Some((kind, x, y)),
+            _ => None,
+        };
+        self.progress = 0;
+    }
+
+    fn note(&mut self, reason: String) {
+        self.notes.push(reason);
+    }
+
+    fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+
+    fn deploy(&mut self) {
+        self.set_state(RobotState::Building);
+    }
+}
+
+impl Builder {
+    pub fn new(spawn: &Spawn) -> Self {
+        Builder {
+            id: spawn.id,
+            x: spawn.position.0,
+            y: spawn.position.1,
+            state: RobotState::Building,
+            knowledge: LocalKnowledge::new(),
+            order: None,
+            progress: 0,
+            notes: Vec::new(),
+        }
+    }
+
+    // Le chantier n'est achevé qu'au dernier tick de travail
+    pub fn build(&mut self, perception: &Perception) -> Action {
+        let Some((kind, site_x, site_y)) = self.order else {
+            self.set_state(RobotState::ReturningToBase);
+            return Action::Wait;
+        };
+
+        match self.calculate_next_step(site_x, site_y, perception) {
+            Route::Step(next_x, next_y) => Action::Move(next_x, next_y),
+            Route::Arrived => {
+                self.progress += 1;
+                if self.progress < kind.build_ticks() {
+                    return Action::Wait;
+                }
+                self.set_state(RobotState::ReturningToBase);
+                Action::Build(site_x, site_y)
+            }
+            Route::Unreachable => Action::Wait,
+        }
+    }
+}
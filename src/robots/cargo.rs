@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use crate::environment::tile::{Resource, ResourceType};
+
+#[derive(Debug, Clone, Copy)]
+pub struct CargoConfig {
+    pub weight_limit: u32,
+    pub max_stops: usize,
+    pub search_radius: usize,
+}
+
+impl Default for CargoConfig {
+    fn default() -> Self {
+        CargoConfig {
+            weight_limit: 10,
+            max_stops: 3,
+            search_radius: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CargoHold {
+    weight_limit: u32,
+    contents: HashMap<ResourceType, u32>,
+}
+
+impl CargoHold {
+    pub fn new(weight_limit: u32) -> Self {
+        CargoHold {
+            weight_limit,
+            contents: HashMap::new(),
+        }
+    }
+
+    pub fn set_weight_limit(&mut self, weight_limit: u32) {
+        self.weight_limit = weight_limit;
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.contents
+            .iter()
+            .map(|(resource_type, amount)| resource_type.unit_weight() * amount)
+            .sum()
+    }
+
+    pub fn free_weight(&self) -> u32 {
+        self.weight_limit.saturating_sub(self.weight())
+    }
+
+    pub fn can_hold(&self, resource_type: ResourceType) -> bool {
+        resource_type.unit_weight() <= self.free_weight()
+    }
+
+    pub fn is_full(&self) -> bool {
+        ResourceType::ALL.iter().all(|resource_type| !self.can_hold(*resource_type))
+    }
+
+    // Charge autant d'unités que possible et renvoie la quantité effectivement chargée
+    pub fn load(&mut self, resource_type: ResourceType, available: u32) -> u32 {
+        let loaded = available.min(self.free_weight() / resource_type.unit_weight());
+        if loaded > 0 {
+            *self.contents.entry(resource_type).or_insert(0) += loaded;
+        }
+        loaded
+    }
+
+    pub fn unload(&mut self) -> Vec<Resource> {
+        self.contents
+            .drain()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(resource_type, amount)| Resource::new(amount, resource_type))
+            .collect()
+    }
+}
@@ -0,0 +1,193 @@
+use super::cargo::{CargoConfig, CargoHold};
+use super::modules::Module;
+use super::perception::{Action, Perception, Route};
+use super::registry::{RobotConfig, RobotKind, RobotRole, Spawn};
+use super::robot::{Order, Robot, RobotState, RobotType};
+use super::sensor::{LocalKnowledge, SensorSpec};
+use super::upgrades::UpgradeLevels;
+use crate::environment::tile::Resource;
+use crate::simulation::simulation::Simulation;
+
+pub fn carrier_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::CARRIER,
+        glyph: '🚚',
+        role: RobotRole::Logistics,
+        manual_dispatch: false,
+        config: RobotConfig {
+            energy_cost: 4,
+            mineral_cost: 4,
+            build_ticks: 4,
+            sensor: SensorSpec::default(),
+            speed: 2.0,
+            battery_capacity: Some(200),
+            cargo: Some(CargoConfig {
+                weight_limit: 30,
+                ..CargoConfig::default()
+            }),
+            breaks_down: true,
+            modules: &[Module::CargoBay],
+        },
+        behaviour: None,
+        constructor: |spawn| Box::new(Carrier::new(spawn)),
+        on_arrival,
+        on_abandon,
+        resumes_order: false,
+    }
+}
+
+fn on_arrival(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    let cargo = robot.unload_cargo();
+    let units = cargo.iter().map(|resource| resource.scale).sum();
+    simulation.deliver_cargo(cargo);
+    if let Some(Order::Haul(depot_x, depot_y)) = robot.order() {
+        simulation.finish_haul((depot_x, depot_y), units);
+        simulation.log_event(format!(
+            "Carrier {} hauled {} units from depot ({}, {})",
+            robot.get_id(),
+            units,
+            depot_x,
+            depot_y
+        ));
+    }
+    robot.set_order(None);
+    simulation.dock(robot);
+}
+
+// Le dépôt pourra recevoir un autre transporteur
+fn on_abandon(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(Order::Haul(depot_x, depot_y)) = robot.order() {
+        simulation.logistics.lock().unwrap().release((depot_x, depot_y));
+    }
+}
+
+// Fait la navette entre un dépôt et la base
+pub struct Carrier {
+    id: usize,
+    x: usize,
+    y: usize,
+    state: RobotState,
+    knowledge: LocalKnowledge,
+    weight_limit: u32,
+    cargo: CargoHold,
+    depot: Option<(usize, usize)>,
+    notes: Vec<String>,
+}
+
+impl Robot for Carrier {
+    fn get_type(&self) -> RobotType {
+        RobotType::CARRIER
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    fn get_state(&self) -> RobotState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: RobotState) {
+        self.state = state;
+    }
+
+    fn set_position(&mut self, x: usize, y: usize) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn update(&mut self, perception: &Perception) -> Action {
+        self.knowledge.observe(&perception.tiles);
+        match self.state {
+            RobotState::Hauling => self.haul(perception),
+            RobotState::ReturningToBase => self.return_to_base(perception),
+            _ => Action::Wait,
+        }
+    }
+
+    fn knowledge(&self) -> &LocalKnowledge {
+        &self.knowledge
+    }
+
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge {
+        &mut self.knowledge
+    }
+
+    fn unload_cargo(&mut self) -> Vec<Resource> {
+        self.cargo.unload()
+    }
+
+    fn free_cargo(&self) -> u32 {
+        self.cargo.free_weight()
+    }
+
+    fn load_from(&mut self, _position: (usize, usize), deposit: Resource) -> u32 {
+        self.cargo.load(deposit.resource_type, deposit.scale)
+    }
+
+    fn apply_upgrades(&mut self, levels: &UpgradeLevels) {
+        self.cargo
+            .set_weight_limit(self.weight_limit + levels.cargo_bonus());
+    }
+
+    fn order(&self) -> Option<Order> {
+        self.depot.map(|(x, y)| Order::Haul(x, y))
+    }
+
+    fn set_order(&mut self, order: Option<Order>) {
+        self.depot = match order {
+            Some(Order::Haul(x, y)) => Some((x, y)),
+            _ => None,
+        };
+    }
+
+    fn note(&mut self, reason: String) {
+        self.notes.push(reason);
+    }
+
+    fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+
+    fn deploy(&mut self) {
+        self.set_state(RobotState::Hauling);
+    }
+}
+
+impl Carrier {
+    pub fn new(spawn: &Spawn) -> Self {
+        let weight_limit = spawn.config.cargo.unwrap_or_default().weight_limit;
+        Carrier {
+            id: spawn.id,
+            x: spawn.position.0,
+            y: spawn.position.1,
+            state: RobotState::Hauling,
+            knowledge: LocalKnowledge::new(),
+            weight_limit,
+            cargo: CargoHold::new(weight_limit),
+            depot: None,
+            notes: Vec::new(),
+        }
+    }
+
+    // Le chargement est demandé une fois à côté du dépôt, puis le transporteur rentre
+    pub fn haul(&mut self, perception: &Perception) -> Action {
+        let Some((depot_x, depot_y)) = self.depot else {
+            self.set_state(RobotState::ReturningToBase);
+            return Action::Wait;
+        };
+
+        match self.calculate_next_step(depot_x, depot_y, perception) {
+            Route::Step(next_x, next_y) => Action::Move(next_x, next_y),
+            Route::Arrived => {
+                self.set_state(RobotState::ReturningToBase);
+                Action::Pickup(depot_x, depot_y)
+            }
+            Route::Unreachable => Action::Wait,
+        }
+    }
+}
@@ -1,31 +1,146 @@
-use super::robot::{Robot, RobotState, RobotType};
-use crate::environment::{
-    map::Map,
-    tile::{MapTile, Resource, TileType},
-};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::behaviour::{self, return_step, Agent, BehaviourSource, BehaviourTree, Condition, Status};
+use super::modules::Module;
+use super::perception::{Action, Perception, Route};
+use super::registry::{no_order, RobotConfig, RobotKind, RobotRole, Spawn};
+use super::robot::{ResourceTarget, Robot, RobotState, RobotType};
+use super::sensor::{LocalKnowledge, SensorSpec};
+use crate::environment::{pheromone::PheromoneKind, tile::TileType};
+use crate::simulation::radio::MessageKind;
+use crate::simulation::simulation::Simulation;
 use rand::Rng;
 
+const BEHAVIOUR: BehaviourSource = BehaviourSource {
+    file: "explorer.bt",
+    builtin: include_str!("../../assets/behaviours/explorer.bt"),
+};
+
+const SWARM_BEHAVIOUR: BehaviourSource = BehaviourSource {
+    file: "swarm.bt",
+    builtin: include_str!("../../assets/behaviours/swarm.bt"),
+};
+
+// Lent mais voit loin, malgré une détection imparfaite en bordure de relief
+pub fn explorer_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::EXPLORER,
+        glyph: '🚜',
+        role: RobotRole::Exploration,
+        manual_dispatch: true,
+        config: RobotConfig {
+            energy_cost: 4,
+            mineral_cost: 0,
+            build_ticks: 3,
+            sensor: SensorSpec {
+                radius: 4,
+                resource_detection: 0.6,
+                terrain_detection: 1.0,
+                robot_detection: 0.9,
+                structure_detection: 1.0,
+            },
+            speed: 1.5,
+            battery_capacity: Some(100),
+            cargo: None,
+            breaks_down: true,
+            modules: &[Module::Sensor],
+        },
+        behaviour: Some(BEHAVIOUR),
+        constructor: |spawn| Box::new(Explorer::new(spawn)),
+        on_arrival,
+        on_abandon: no_order,
+        resumes_order: true,
+    }
+}
+
+// Rapide mais myope
+pub fn scout_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::SCOUT,
+        glyph: '🛸',
+        role: RobotRole::Exploration,
+        manual_dispatch: true,
+        config: RobotConfig {
+            energy_cost: 3,
+            mineral_cost: 1,
+            build_ticks: 2,
+            sensor: SensorSpec {
+                radius: 1,
+                resource_detection: 0.9,
+                terrain_detection: 1.0,
+                robot_detection: 1.0,
+                structure_detection: 1.0,
+            },
+            speed: 3.0,
+            battery_capacity: Some(60),
+            cargo: None,
+            breaks_down: true,
+            modules: &[Module::Sensor],
+        },
+        behaviour: Some(BEHAVIOUR),
+        constructor: |spawn| Box::new(Explorer::new(spawn)),
+        on_arrival,
+        on_abandon: no_order,
+        resumes_order: true,
+    }
+}
+
+// Explore par stigmergie, pour comparer avec la marche aléatoire des explorateurs
+pub fn swarm_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::SWARM,
+        glyph: '🐜',
+        role: RobotRole::Exploration,
+        manual_dispatch: true,
+        config: RobotConfig {
+            energy_cost: 3,
+            mineral_cost: 0,
+            build_ticks: 3,
+            sensor: SensorSpec {
+                radius: 2,
+                ..SensorSpec::default()
+            },
+            speed: 1.5,
+            battery_capacity: Some(100),
+            cargo: None,
+            breaks_down: true,
+            modules: &[Module::Sensor],
+        },
+        behaviour: Some(SWARM_BEHAVIOUR),
+        constructor: |spawn| Box::new(Explorer::new(spawn)),
+        on_arrival,
+        on_abandon: no_order,
+        resumes_order: true,
+    }
+}
+
+fn on_arrival(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(target) = robot.get_current_resource() {
+        simulation.report_deposit(target.x, target.y, target.resource, (robot.get_type(), robot.get_id()));
+    }
+    simulation.dock(robot);
+}
+
 pub struct Explorer {
     id: usize,
     x: usize,
     y: usize,
-    resource: Option<(usize, usize, Resource, bool)>,
+    robot_type: RobotType,
+    knowledge: LocalKnowledge,
+    resource: Option<ResourceTarget>,
+    // Gisements déjà signalés par radio pendant une mission de l'opérateur
+    reported: HashSet<(usize, usize)>,
     state: RobotState,
+    behaviour: Arc<BehaviourTree>,
+    running_node: Option<String>,
+    intent: Option<Action>,
+    notes: Vec<String>,
 }
 
 impl Robot for Explorer {
-    fn new(x: usize, y: usize, id: usize) -> Self {
-        Explorer {
-            id: id,
-            x: x,
-            y: y,
-            resource: None,
-            state: RobotState::Exploring,
-        }
-    }
-
     fn get_type(&self) -> RobotType {
-        RobotType::Explorer
+        self.robot_type
     }
 
     fn get_id(&self) -> usize {
@@ -37,7 +152,7 @@ impl Robot for Explorer {
     }
 
     fn get_state(&self) -> RobotState {
-        self.state.clone()
+        self.state
     }
 
     fn set_state(&mut self, state: RobotState) {
@@ -49,28 +164,154 @@ impl Robot for Explorer {
         self.y = y;
     }
 
-    fn update(&mut self, map: &mut Map) {
-        match self.state {
-            RobotState::Exploring => {
-                self.explore(map);
-            }
-            RobotState::ReturningToBase => {
-                self.return_to_base(map);
-            }
-            _ => {}
+    fn update(&mut self, perception: &Perception) -> Action {
+        self.knowledge.observe(&perception.tiles);
+        if !matches!(self.state, RobotState::Exploring | RobotState::ReturningToBase) {
+            return Action::Wait;
         }
+        let behaviour = Arc::clone(&self.behaviour);
+        let (_, running_node) = behaviour.tick(self, perception);
+        self.running_node = running_node;
+        self.intent.take().unwrap_or(Action::Wait)
+    }
+
+    fn knowledge(&self) -> &LocalKnowledge {
+        &self.knowledge
+    }
+
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge {
+        &mut self.knowledge
+    }
+
+    fn running_node(&self) -> Option<String> {
+        self.running_node.clone()
     }
 
-    fn get_current_resource(&self) -> Option<(usize, usize, Resource, bool)> {
+    fn note(&mut self, reason: String) {
+        self.notes.push(reason);
+    }
+
+    fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+
+    fn get_current_resource(&self) -> Option<ResourceTarget> {
         self.resource
     }
 
-    fn set_target_resource(&mut self, _: Option<(usize, usize, Resource, bool)>) {}
+    fn pheromone(&self) -> Option<PheromoneKind> {
+        match self.state {
+            RobotState::Exploring => Some(PheromoneKind::Explored),
+            _ => None,
+        }
+    }
+
+    // Le gisement déjà signalé ne doit pas renvoyer aussitôt le robot à la base
+    fn deploy(&mut self) {
+        self.knowledge = LocalKnowledge::new();
+        self.resource = None;
+        self.reported.clear();
+        self.set_state(RobotState::Exploring);
+    }
+}
+
+impl Agent for Explorer {
+    fn check(&self, condition: Condition, _perception: &Perception) -> bool {
+        match condition {
+            Condition::Returning => self.state == RobotState::ReturningToBase,
+            Condition::HasTarget => self.resource.is_some(),
+            Condition::ResourceKnown => !self.knowledge.resources().is_empty(),
+            _ => false,
+        }
+    }
+
+    fn act(&mut self, action: behaviour::Action, perception: &Perception) -> Status {
+        if self.intent.is_some() {
+            return Status::Running;
+        }
+        match action {
+            behaviour::Action::Explore => {
+                self.set_state(RobotState::Exploring);
+                self.explore(perception);
+                match self.state {
+                    RobotState::ReturningToBase => Status::Success,
+                    _ => Status::Running,
+                }
+            }
+            behaviour::Action::FollowPheromones => {
+                if !self.follow_pheromones(perception) {
+                    return Status::Failure;
+                }
+                match self.state {
+                    RobotState::ReturningToBase => Status::Success,
+                    _ => Status::Running,
+                }
+            }
+            behaviour::Action::ReturnToBase => {
+                let (status, intent) = return_step(self, perception);
+                self.intent = Some(intent);
+                status
+            }
+            behaviour::Action::TargetNearestResource => {
+                match self.knowledge.nearest_resource((self.x, self.y)) {
+                    Some((x, y, resource)) => {
+                        self.note(format!("nearest known deposit at ({}, {})", x, y));
+                        self.resource = Some(ResourceTarget::new(x, y, resource));
+                        Status::Success
+                    }
+                    None => Status::Failure,
+                }
+            }
+            // Les récolteurs à portée n'ont pas à attendre le retour à la base ;
+            // l'émission occupe le tick, le retour commence au suivant
+            behaviour::Action::BroadcastDeposit => match self.resource {
+                Some(target) => {
+                    self.intent = Some(Action::Transmit(MessageKind::Deposit(
+                        target.x,
+                        target.y,
+                        target.resource,
+                    )));
+                    self.set_state(RobotState::ReturningToBase);
+                    Status::Success
+                }
+                None => Status::Failure,
+            },
+            behaviour::Action::Survey => match perception.waypoint {
+                Some((x, y)) => {
+                    self.set_state(RobotState::Exploring);
+                    self.survey(x, y, perception);
+                    Status::Running
+                }
+                None => Status::Failure,
+            },
+            _ => Status::Failure,
+        }
+    }
 }
 
 impl Explorer {
-    pub fn explore(&mut self, map: &mut Map) {
-        let mut rng = rand::rng();
+    pub fn new(spawn: &Spawn) -> Self {
+        Explorer {
+            id: spawn.id,
+            x: spawn.position.0,
+            y: spawn.position.1,
+            robot_type: spawn.robot_type,
+            knowledge: LocalKnowledge::new(),
+            resource: None,
+            reported: HashSet::new(),
+            state: RobotState::Exploring,
+            behaviour: spawn
+                .behaviour
+                .clone()
+                .unwrap_or_else(|| Arc::new(BEHAVIOUR.load_builtin())),
+            running_node: None,
+            intent: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn explore(&mut self, perception: &Perception) {
+        let mut rng = perception.rng();
 
         let move_horizontal = rng.random_bool(0.5);
 
@@ -83,16 +324,78 @@ impl Explorer {
         let new_x = (self.x as isize + direction_x).max(0) as usize;
         let new_y = (self.y as isize + direction_y).max(0) as usize;
 
-        if new_x <= map.width && new_y <= map.height {
-            match map.get(new_x, new_y).tile {
-                TileType::Resource(resource) => {
-                    self.resource = Some((new_x, new_y, resource, true));
-                    self.set_state(RobotState::ReturningToBase);
-                }
-                _ => {
-                    self.move_to(new_x, new_y, map);
-                }
+        if !perception.may_enter(new_x, new_y) {
+            self.note(format!("({}, {}) is a no-go area", new_x, new_y));
+            return;
+        }
+        if new_x < perception.width && new_y < perception.height {
+            self.note(format!("random step towards ({}, {})", new_x, new_y));
+            self.visit(new_x, new_y);
+        }
+    }
+
+    // Le robot reste dans sa zone : chaque nouveau gisement est émis par radio plutôt que rapporté à la base
+    pub fn survey(&mut self, x: usize, y: usize, perception: &Perception) {
+        let unreported = self
+            .knowledge
+            .resources()
+            .into_iter()
+            .find(|(x, y, _)| !self.reported.contains(&(*x, *y)));
+        if let Some((deposit_x, deposit_y, resource)) = unreported {
+            self.reported.insert((deposit_x, deposit_y));
+            self.intent = Some(Action::Transmit(MessageKind::Deposit(deposit_x, deposit_y, resource)));
+            return;
+        }
+        match self.calculate_next_step(x, y, perception) {
+            Route::Step(next_x, next_y) => {
+                self.note(format!("heading to waypoint ({}, {})", x, y));
+                self.intent = Some(Action::Move(next_x, next_y));
             }
+            Route::Arrived => self.note(format!("waiting at waypoint ({}, {})", x, y)),
+            // La zone abandonne d'elle-même une tuile visée trop longtemps
+            Route::Unreachable => {}
         }
     }
-}
+
+    // Va vers la voisine la moins marquée par les autres explorateurs ; faux sans couche de phéromones
+    pub fn follow_pheromones(&mut self, perception: &Perception) -> bool {
+        if !perception.has_pheromones() {
+            self.note("no pheromone layer, falling back".to_string());
+            return false;
+        }
+        self.set_state(RobotState::Exploring);
+
+        let mut rng = perception.rng();
+        let next = perception
+            .neighbours((self.x, self.y))
+            .into_iter()
+            .filter(|(x, y)| {
+                perception.passable(&self.knowledge, *x, *y)
+                    || matches!(self.knowledge.tile(*x, *y), Some(TileType::Resource(_)))
+            })
+            // Un léger bruit départage les voisines également marquées
+            .map(|(x, y)| ((x, y), perception.pheromone(PheromoneKind::Explored, (x, y)) + rng.random_range(0.0..0.01)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        match next {
+            Some(((x, y), level)) => {
+                self.note(format!("least-marked neighbour ({}, {}), level {:.2}", x, y, level));
+                self.visit(x, y);
+            }
+            None => self.note("surrounded, no free neighbour".to_string()),
+        }
+        true
+    }
+
+    // Un gisement connu sur la tuile visée est signalé au lieu d'être foulé
+    fn visit(&mut self, x: usize, y: usize) {
+        match self.knowledge.tile(x, y) {
+            Some(TileType::Resource(resource)) => {
+                self.note(format!("deposit spotted at ({}, {})", x, y));
+                self.resource = Some(ResourceTarget::new(x, y, resource));
+                self.set_state(RobotState::ReturningToBase);
+            }
+            _ => self.intent = Some(Action::Move(x, y)),
+        }
+    }
+}
\ No newline at end of file
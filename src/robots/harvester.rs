@@ -1,34 +1,97 @@
-use super::robot::{Robot, RobotState, RobotType};
+use std::sync::Arc;
+
+use super::behaviour::{self, return_step, Agent, BehaviourSource, BehaviourTree, Condition, Status};
+use super::cargo::{CargoConfig, CargoHold};
+use super::modules::Module;
+use super::perception::{Action, Perception, Route};
+use super::registry::{RobotConfig, RobotKind, RobotRole, Spawn};
+use super::robot::{Order, ResourceTarget, Robot, RobotState, RobotType};
+use super::sensor::{LocalKnowledge, SensorSpec};
+use super::upgrades::UpgradeLevels;
 use crate::environment::{
-    map::Map,
+    pheromone::PheromoneKind,
     tile::{MapTile, Resource, TileType},
 };
+use crate::simulation::radio::MessageKind;
+use crate::simulation::simulation::Simulation;
+
+const BEHAVIOUR: BehaviourSource = BehaviourSource {
+    file: "harvester.bt",
+    builtin: include_str!("../../assets/behaviours/harvester.bt"),
+};
+
+pub fn harvester_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::HARVESTER,
+        glyph: '🤖',
+        role: RobotRole::Harvesting,
+        manual_dispatch: false,
+        config: RobotConfig {
+            energy_cost: 5,
+            mineral_cost: 2,
+            build_ticks: 5,
+            sensor: SensorSpec {
+                radius: 2,
+                resource_detection: 0.8,
+                terrain_detection: 1.0,
+                robot_detection: 1.0,
+                structure_detection: 1.0,
+            },
+            speed: 1.0,
+            battery_capacity: Some(150),
+            cargo: Some(CargoConfig::default()),
+            breaks_down: true,
+            modules: &[Module::Sensor, Module::CargoBay, Module::Drill],
+        },
+        behaviour: Some(BEHAVIOUR),
+        constructor: |spawn| Box::new(Harvester::new(spawn)),
+        on_arrival,
+        on_abandon,
+        resumes_order: true,
+    }
+}
+
+fn on_arrival(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    simulation.deliver_cargo(robot.unload_cargo());
+    match robot.get_current_resource() {
+        Some(target) if target.remaining => robot.set_state(RobotState::Harvesting),
+        _ => simulation.dock(robot),
+    }
+}
+
+fn on_abandon(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(Order::Harvest(target)) = robot.order() {
+        simulation.abandon_deposit((robot.get_type(), robot.get_id()), (target.x, target.y));
+    }
+}
 
 pub struct Harvester {
     id: usize,
     x: usize,
     y: usize,
-    cargo_capacity: u32,
+    config: CargoConfig,
+    cargo: CargoHold,
+    knowledge: LocalKnowledge,
     state: RobotState,
-    target_resource: Option<(usize, usize, Resource, bool)>,
+    target_resource: Option<ResourceTarget>,
+    current_stop: Option<(usize, usize)>,
+    stops: usize,
+    // Gisements attribués à d'autres récolteurs lors de la dernière perception, jamais pris comme arrêt
+    reserved: Vec<(usize, usize)>,
+    // Arrêt dont la récolte a été demandée et pas encore confirmée par la simulation
+    harvesting: Option<(usize, usize)>,
+    behaviour: Arc<BehaviourTree>,
+    running_node: Option<String>,
+    intent: Option<Action>,
+    notes: Vec<String>,
 }
 
 impl Robot for Harvester {
-    fn new(x: usize, y: usize, id: usize) -> Self {
-        Harvester {
-            id: id,
-            x: x,
-            y: y,
-            cargo_capacity: 5,
-            state: RobotState::Harvesting,
-            target_resource: None,
-        }
-    }
     fn get_id(&self) -> usize {
         self.id
     }
     fn get_type(&self) -> RobotType {
-        RobotType::Harvester
+        RobotType::HARVESTER
     }
 
     fn get_position(&self) -> (usize, usize) {
@@ -36,7 +99,7 @@ impl Robot for Harvester {
     }
 
     fn get_state(&self) -> RobotState {
-        self.state.clone()
+        self.state
     }
 
     fn set_state(&mut self, state: RobotState) {
@@ -48,73 +111,370 @@ impl Robot for Harvester {
         self.y = y;
     }
 
-    fn get_current_resource(&self) -> Option<(usize, usize, Resource, bool)> {
-        if let Some((x, y, resource, remind)) = self.target_resource {
-            Some((x, y, resource, remind))
-        } else {
-            None
-        }
+    fn get_current_resource(&self) -> Option<ResourceTarget> {
+        self.target_resource
     }
 
-    fn set_target_resource(&mut self, target: Option<(usize, usize, Resource, bool)>) {
+    fn order(&self) -> Option<Order> {
+        self.target_resource.map(Order::Harvest)
+    }
+
+    fn set_order(&mut self, order: Option<Order>) {
+        let target = match order {
+            Some(Order::Harvest(target)) => Some(target),
+            _ => None,
+        };
+        match target {
+            Some(target) => {
+                self.note(format!("assigned deposit ({}, {})", target.x, target.y));
+                // Gisement signalé par un autre robot : un capteur qui le manque à l'arrivée ne doit pas le faire croire disparu
+                self.knowledge
+                    .observe(&[MapTile::new(target.x, target.y, TileType::Resource(target.resource))]);
+            }
+            None => self.note("target cleared".to_string()),
+        }
         self.target_resource = target;
     }
 
-    fn update(&mut self, map: &mut Map) {
-        match self.state {
-            RobotState::Harvesting => {
-                self.harvest(map);
+    fn unload_cargo(&mut self) -> Vec<Resource> {
+        self.current_stop = None;
+        self.stops = 0;
+        self.cargo.unload()
+    }
+
+    // Le dépôt prend le chargement : la tournée reprend si le gisement n'est pas épuisé
+    fn drop_cargo(&mut self) -> Vec<Resource> {
+        let cargo = self.unload_cargo();
+        if self.target_resource.is_some_and(|target| target.remaining) {
+            self.note("cargo dropped at depot, back to deposit".to_string());
+            self.set_state(RobotState::Harvesting);
+        }
+        cargo
+    }
+
+    // Un récolteur chargé marque le chemin du gisement à la base
+    fn pheromone(&self) -> Option<PheromoneKind> {
+        match self.state == RobotState::ReturningToBase && self.cargo.weight() > 0 {
+            true => Some(PheromoneKind::ResourcePath),
+            false => None,
+        }
+    }
+
+    fn free_cargo(&self) -> u32 {
+        self.cargo.free_weight()
+    }
+
+    fn load_from(&mut self, position: (usize, usize), deposit: Resource) -> u32 {
+        let loaded = self.collect(position, deposit);
+        if self.harvesting.take() == Some(position) {
+            self.finish_stop();
+        }
+        loaded
+    }
+
+    fn apply_upgrades(&mut self, levels: &UpgradeLevels) {
+        self.cargo
+            .set_weight_limit(self.config.weight_limit + levels.cargo_bonus());
+    }
+
+    fn update(&mut self, perception: &Perception) -> Action {
+        self.knowledge.observe(&perception.tiles);
+        self.reserved.clone_from(&perception.reserved);
+        for message in &perception.messages {
+            if let MessageKind::Deposit(x, y, resource) = message.kind {
+                self.knowledge
+                    .observe(&[MapTile::new(x, y, TileType::Resource(resource))]);
             }
-            RobotState::ReturningToBase => {
-                self.return_to_base(map);
+        }
+        if !matches!(self.state, RobotState::Harvesting | RobotState::ReturningToBase) {
+            return Action::Wait;
+        }
+        let behaviour = Arc::clone(&self.behaviour);
+        let (_, running_node) = behaviour.tick(self, perception);
+        self.running_node = running_node;
+        self.intent.take().unwrap_or(Action::Wait)
+    }
+
+    fn knowledge(&self) -> &LocalKnowledge {
+        &self.knowledge
+    }
+
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge {
+        &mut self.knowledge
+    }
+
+    fn running_node(&self) -> Option<String> {
+        self.running_node.clone()
+    }
+
+    fn note(&mut self, reason: String) {
+        self.notes.push(reason);
+    }
+
+    fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+}
+
+impl Agent for Harvester {
+    fn check(&self, condition: Condition, _perception: &Perception) -> bool {
+        match condition {
+            Condition::Returning => self.state == RobotState::ReturningToBase,
+            Condition::HasTarget => self.target_resource.is_some(),
+            Condition::ResourceKnown => !self.knowledge.resources().is_empty(),
+            Condition::CargoFull => self.cargo.is_full(),
+            Condition::TargetDepleted => self
+                .target_resource
+                .is_some_and(|target| !target.remaining),
+        }
+    }
+
+    fn act(&mut self, action: behaviour::Action, perception: &Perception) -> Status {
+        if self.intent.is_some() {
+            return Status::Running;
+        }
+        match action {
+            behaviour::Action::Harvest => {
+                self.set_state(RobotState::Harvesting);
+                self.harvest(perception);
+                match self.state {
+                    RobotState::ReturningToBase => Status::Success,
+                    _ => Status::Running,
+                }
+            }
+            behaviour::Action::ReturnToBase => {
+                if let Some(intent) = self.head_to_depot(perception) {
+                    self.intent = Some(intent);
+                    return Status::Running;
+                }
+                let (status, intent) = return_step(self, perception);
+                self.intent = Some(intent);
+                status
             }
-            _ => {}
+            _ => Status::Failure,
         }
     }
 }
 
 impl Harvester {
-    pub fn harvest(&mut self, map: &mut Map) {
-        if let Some((x, y, _, _)) = self.target_resource {
-            let step = self.calculate_next_step(x, y, map);
-            match step {
-                Some((next_x, next_y)) => {
-                    self.move_to(next_x, next_y, map);
+    pub fn new(spawn: &Spawn) -> Self {
+        let config = spawn.config.cargo.unwrap_or_default();
+        Harvester {
+            id: spawn.id,
+            x: spawn.position.0,
+            y: spawn.position.1,
+            config,
+            cargo: CargoHold::new(config.weight_limit),
+            knowledge: LocalKnowledge::new(),
+            state: RobotState::Waiting,
+            target_resource: None,
+            current_stop: None,
+            stops: 0,
+            reserved: Vec::new(),
+            harvesting: None,
+            behaviour: spawn
+                .behaviour
+                .clone()
+                .unwrap_or_else(|| Arc::new(BEHAVIOUR.load_builtin())),
+            running_node: None,
+            intent: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn harvest(&mut self, perception: &Perception) {
+        self.harvesting = None;
+        // Un arrêt attribué entre-temps à un autre récolteur lui est laissé
+        if let Some((x, y)) = self.current_stop.filter(|stop| self.reserved.contains(stop)) {
+            self.note(format!("deposit ({}, {}) assigned to another harvester", x, y));
+            self.finish_stop();
+            if self.state == RobotState::ReturningToBase {
+                return;
+            }
+        }
+        let stop = match (self.current_stop, self.target_resource) {
+            (Some(stop), _) => stop,
+            (None, Some(target)) => (target.x, target.y),
+            (None, None) => return,
+        };
+
+        match self.calculate_next_step(stop.0, stop.1, perception) {
+            Route::Step(next_x, next_y) => {
+                let (next_x, next_y) = self.follow_trail((next_x, next_y), stop, perception);
+                self.intent = Some(Action::Move(next_x, next_y));
+            }
+            // Sans intention le robot attend sur place ; le chien de garde décide de la suite
+            Route::Unreachable => {}
+            Route::Arrived => match self.knowledge.tile(stop.0, stop.1) {
+                Some(TileType::Resource(res)) if self.cargo.can_hold(res.resource_type) => {
+                    self.harvesting = Some(stop);
+                    self.intent = Some(Action::Harvest(stop.0, stop.1));
                 }
-                None => {
-                    let tile = map.get(x, y);
-                    match tile.tile {
-                        TileType::Resource(res) => {
-                            if res.scale > self.cargo_capacity {
-                                self.set_target_resource(Some((
-                                    x,
-                                    y,
-                                    Resource::new(self.cargo_capacity, res.resource_type),
-                                    true,
-                                )));
-                                map.set(MapTile::new(
-                                    x,
-                                    y,
-                                    TileType::Resource(Resource::new(
-                                        res.scale - self.cargo_capacity,
-                                        res.resource_type,
-                                    )),
-                                ));
-                            } else {
-                                self.set_target_resource(Some((
-                                    x,
-                                    y,
-                                    Resource::new(res.scale, res.resource_type),
-                                    false,
-                                )));
-                                map.set(MapTile::new(x, y, TileType::Empty));
-                            }
+                // La soute ne peut plus rien prendre ici : l'arrêt est passé
+                Some(TileType::Resource(_)) => {
+                    self.note(format!("no room left for deposit ({}, {})", stop.0, stop.1));
+                    self.finish_stop();
+                }
+                // Le gisement a disparu avant notre arrivée : l'arrêt est passé
+                _ => {
+                    self.note(format!("deposit ({}, {}) is gone", stop.0, stop.1));
+                    if let Some(target) = self.target_resource.as_mut() {
+                        if (target.x, target.y) == stop {
+                            target.remaining = false;
                         }
-                        _ => {}
                     }
-                    self.set_state(RobotState::ReturningToBase);
+                    self.finish_stop();
                 }
+            },
+        }
+    }
+
+    // Chargé, le récolteur vide sa soute au dépôt le plus proche plutôt qu'à la base
+    fn head_to_depot(&mut self, perception: &Perception) -> Option<Action> {
+        if self.cargo.weight() == 0 {
+            return None;
+        }
+        let (depot_x, depot_y) = perception.nearest_depot()?;
+        self.set_state(RobotState::ReturningToBase);
+        match self.calculate_next_step(depot_x, depot_y, perception) {
+            Route::Step(next_x, next_y) => Some(Action::Move(next_x, next_y)),
+            Route::Arrived => Some(Action::Unload(depot_x, depot_y)),
+            // Dépôt hors d'atteinte : la base prend le relais
+            Route::Unreachable => None,
+        }
+    }
+
+    // Charge la soute et met à jour ce que le robot sait du gisement ; renvoie la quantité chargée
+    fn collect(&mut self, (x, y): (usize, usize), deposit: Resource) -> u32 {
+        let loaded = self.cargo.load(deposit.resource_type, deposit.scale);
+        let remaining = deposit.scale - loaded;
+        let tile = match remaining {
+            0 => TileType::Empty,
+            _ => TileType::Resource(Resource::new(remaining, deposit.resource_type)),
+        };
+        self.knowledge.observe(&[MapTile::new(x, y, tile)]);
+
+        // Le gisement d'origine est à revisiter tant qu'il n'est pas épuisé
+        if let Some(target) = self.target_resource.as_mut() {
+            if target.x == x && target.y == y {
+                target.remaining = remaining > 0;
             }
         }
+        loaded
+    }
+
+    fn finish_stop(&mut self) {
+        self.stops += 1;
+        self.current_stop = self.find_next_stop();
+        match self.current_stop {
+            Some((x, y)) => self.note(format!("next stop ({}, {})", x, y)),
+            None => {
+                let reason = match self.cargo.is_full() {
+                    true => "cargo full",
+                    false => "no other deposit within reach",
+                };
+                self.note(format!("{}, heading home", reason));
+                self.set_state(RobotState::ReturningToBase);
+            }
+        }
+    }
+
+    // Parmi les pas qui rapprochent autant de l'arrêt, préfère celui où la trace de ressource est la plus forte
+    fn follow_trail(&self, next: (usize, usize), stop: (usize, usize), perception: &Perception) -> (usize, usize) {
+        if !perception.has_pheromones() {
+            return next;
+        }
+        let distance = |(x, y): (usize, usize)| x.abs_diff(stop.0) + y.abs_diff(stop.1);
+        // Un détour imposé par le relief reste celui de la recherche de chemin
+        if distance(next) >= distance((self.x, self.y)) {
+            return next;
+        }
+        let level = |position| perception.pheromone(PheromoneKind::ResourcePath, position);
+
+        perception
+            .neighbours((self.x, self.y))
+            .into_iter()
+            .filter(|position| distance(*position) == distance(next) && perception.passable(&self.knowledge, position.0, position.1))
+            .map(|position| (position, level(position)))
+            .filter(|(_, trail)| *trail > level(next))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(next, |(position, _)| position)
+    }
+
+    // Cherche, parmi les gisements perçus, le plus proche qui peut encore entrer dans la soute
+    fn find_next_stop(&self) -> Option<(usize, usize)> {
+        if self.stops >= self.config.max_stops || self.cargo.is_full() {
+            return None;
+        }
+
+        self.knowledge
+            .resources()
+            .into_iter()
+            .filter(|(x, y, res)| self.cargo.can_hold(res.resource_type) && !self.reserved.contains(&(*x, *y)))
+            .map(|(x, y, _)| ((x, y), self.x.abs_diff(x) + self.y.abs_diff(y)))
+            .filter(|(_, distance)| *distance <= self.config.search_radius)
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(position, _)| position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::map::Map;
+    use crate::environment::tile::ResourceType;
+
+    #[test]
+    fn assigned_deposit_missed_by_the_sensor_is_still_harvested() {
+        let mut map = Map::new(25, 25, 7);
+        for y in 0..5 {
+            for x in 0..5 {
+                map.set(MapTile::new(x, y, TileType::Empty));
+            }
+        }
+        let deposit = Resource::new(4, ResourceType::Energy);
+        map.set(MapTile::new(2, 2, TileType::Resource(deposit)));
+        let kind = harvester_kind();
+        let mut harvester = Harvester::new(&Spawn {
+            robot_type: kind.robot_type,
+            id: 0,
+            position: (2, 3),
+            config: kind.config,
+            behaviour: None,
+        });
+        harvester.set_order(Some(Order::Harvest(ResourceTarget::new(2, 2, deposit))));
+        harvester.set_state(RobotState::Harvesting);
+
+        // Le capteur n'a rien rapporté à ce pas, pas même le gisement voisin
+        let perception = Perception::new(&map, RobotType::HARVESTER, (2, 3), Vec::new(), Vec::new());
+        let action = harvester.update(&perception);
+
+        assert!(matches!(action, Action::Harvest(2, 2)), "got {}", action);
+        assert!(harvester.get_current_resource().is_some_and(|target| target.remaining));
+    }
+
+    #[test]
+    fn deposit_assigned_to_another_harvester_is_not_a_stop() {
+        let map = Map::new(25, 25, 7);
+        let kind = harvester_kind();
+        let mut harvester = Harvester::new(&Spawn {
+            robot_type: kind.robot_type,
+            id: 0,
+            position: (2, 3),
+            config: kind.config,
+            behaviour: None,
+        });
+        let deposit = TileType::Resource(Resource::new(4, ResourceType::Energy));
+        let tiles = vec![MapTile::new(3, 3, deposit), MapTile::new(0, 3, deposit)];
+
+        // Le gisement le plus proche revient à un autre récolteur : le robot passe au suivant
+        let mut perception = Perception::new(&map, RobotType::HARVESTER, (2, 3), tiles, Vec::new());
+        perception.reserved = vec![(3, 3)];
+        harvester.update(&perception);
+        assert_eq!(harvester.find_next_stop(), Some((0, 3)));
+
+        perception.reserved.clear();
+        harvester.update(&perception);
+        assert_eq!(harvester.find_next_stop(), Some((3, 3)));
     }
 }
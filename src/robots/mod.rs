@@ -1,3 +1,33 @@
 pub mod robot;
+pub mod battery;
+pub mod behaviour;
+pub mod builder;
+pub mod cargo;
+pub mod carrier;
 pub mod explorer;
-pub mod harvester;
\ No newline at end of file
+pub mod harvester;
+pub mod modules;
+pub mod perception;
+pub mod registry;
+pub mod reliability;
+pub mod repair;
+pub mod script;
+pub mod scripted;
+pub mod sensor;
+pub mod trace;
+pub mod upgrades;
+
+use registry::RobotKind;
+
+// Types fournis par les modules ci-dessus, enregistrés dans cet ordre au démarrage :
+// un nouveau type n'a qu'à ajouter sa fonction ici
+pub const BUILTIN_KINDS: [fn() -> RobotKind; 8] = [
+    explorer::explorer_kind,
+    explorer::scout_kind,
+    explorer::swarm_kind,
+    harvester::harvester_kind,
+    builder::builder_kind,
+    repair::repair_kind,
+    carrier::carrier_kind,
+    scripted::script_kind,
+];
\ No newline at end of file
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::environment::tile::{Resource, ResourceType};
+
+// Éléments interchangeables montés sur un châssis commun ; ils déterminent le rôle du robot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Module {
+    Sensor,
+    CargoBay,
+    Drill,
+}
+
+impl Module {
+    pub fn cost(&self) -> Vec<Resource> {
+        let (energy, minerals) = match self {
+            Module::Sensor => (2, 1),
+            Module::CargoBay => (1, 3),
+            Module::Drill => (2, 3),
+        };
+        vec![
+            Resource::new(energy, ResourceType::Energy),
+            Resource::new(minerals, ResourceType::Mineral),
+        ]
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Module::Sensor => "Sensor",
+            Module::CargoBay => "Cargo bay",
+            Module::Drill => "Drill",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Modules à monter pour passer d'un équipement à l'autre ; ceux déjà montés sont conservés,
+// ceux qui ne servent plus sont démontés sans remboursement
+pub fn missing_modules(current: &[Module], target: &[Module]) -> Vec<Module> {
+    target
+        .iter()
+        .filter(|module| !current.contains(module))
+        .copied()
+        .collect()
+}
+
+// Coût total d'un rééquipement, regroupé par ressource
+pub fn refit_cost(current: &[Module], target: &[Module]) -> Vec<Resource> {
+    ResourceType::ALL
+        .iter()
+        .map(|resource_type| {
+            let amount = missing_modules(current, target)
+                .iter()
+                .flat_map(|module| module.cost())
+                .filter(|resource| resource.resource_type == *resource_type)
+                .map(|resource| resource.scale)
+                .sum();
+            Resource::new(amount, *resource_type)
+        })
+        .filter(|resource| resource.scale > 0)
+        .collect()
+}
@@ -0,0 +1,398 @@
+use std::cell::{Cell, RefCell, RefMut};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::robot::RobotType;
+use super::sensor::LocalKnowledge;
+use crate::environment::{
+    map::Map,
+    pheromone::PheromoneKind,
+    restriction::NoGoArea,
+    structure::StructureType,
+    tile::{MapTile, TileType},
+};
+use crate::simulation::radio::{MessageKind, RadioMessage};
+
+// Début de la raison notée quand seule une zone interdite mène à la cible
+pub const UNREACHABLE: &str = "unreachable under constraints";
+
+// Tuile précédente sur le meilleur chemin vers chaque tuile atteinte par la recherche
+type CameFrom = HashMap<(usize, usize), Option<(usize, usize)>>;
+
+// Résultat d'une recherche de chemin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Step(usize, usize),
+    // Sur la cible ou à côté
+    Arrived,
+    // Aucun chemin connu : le robot ne doit pas errer en attendant
+    Unreachable,
+}
+
+// Ce qu'un robot reçoit à chaque pas : il ne voit de la carte que ce que ses capteurs lui rapportent
+#[derive(Debug, Clone)]
+pub struct Perception {
+    pub position: (usize, usize),
+    pub base: (usize, usize),
+    // Dépôts construits, connus de toute la flotte comme la base
+    pub depots: Vec<(usize, usize)>,
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<MapTile>,
+    pub messages: Vec<RadioMessage>,
+    // Prochaine tuile de la zone ou de la ronde fixée par l'opérateur
+    pub waypoint: Option<(usize, usize)>,
+    // Gisements attribués par la base à d'autres récolteurs
+    pub reserved: Vec<(usize, usize)>,
+    // Zones interdites et pénalités des zones à éviter qui s'appliquent à ce type de robot
+    no_go: NoGoArea,
+    penalties: HashMap<(usize, usize), u32>,
+    // Dernière cible cherchée pendant le pas, relue par le chien de garde de la simulation
+    last_route: Cell<Option<((usize, usize), Route)>>,
+    // Hasard du pas, tiré par la simulation de la graine de la carte, du tick et du robot
+    rng: RefCell<StdRng>,
+    // None sans couche de phéromones
+    pheromones: Option<Vec<PheromoneReading>>,
+}
+
+// Intensité lue sur la tuile du robot ou l'une de ses voisines
+#[derive(Debug, Clone, Copy)]
+struct PheromoneReading {
+    kind: PheromoneKind,
+    position: (usize, usize),
+    level: f32,
+}
+
+// Intention renvoyée par le robot ; la simulation la vérifie avant de l'appliquer
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    Move(usize, usize),
+    Harvest(usize, usize),
+    Build(usize, usize),
+    // Vide la soute dans le dépôt voisin
+    Unload(usize, usize),
+    // Charge le contenu du dépôt voisin
+    Pickup(usize, usize),
+    Scan,
+    Transmit(MessageKind),
+    Wait,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Move(x, y) => write!(f, "move to ({}, {})", x, y),
+            Action::Harvest(x, y) => write!(f, "harvest ({}, {})", x, y),
+            Action::Build(x, y) => write!(f, "build ({}, {})", x, y),
+            Action::Unload(x, y) => write!(f, "unload at ({}, {})", x, y),
+            Action::Pickup(x, y) => write!(f, "pick up at ({}, {})", x, y),
+            Action::Scan => write!(f, "scan"),
+            Action::Transmit(kind) => write!(f, "transmit {}", kind),
+            Action::Wait => write!(f, "wait"),
+        }
+    }
+}
+
+impl Perception {
+    pub fn new(
+        map: &Map,
+        robot_type: RobotType,
+        position: (usize, usize),
+        tiles: Vec<MapTile>,
+        messages: Vec<RadioMessage>,
+    ) -> Self {
+        let mut perception = Perception {
+            position,
+            base: map.base_position,
+            depots: map
+                .built_structures()
+                .into_iter()
+                .filter(|structure| structure.kind == StructureType::Depot)
+                .map(|structure| structure.origin)
+                .collect(),
+            width: map.width,
+            height: map.height,
+            tiles,
+            messages,
+            waypoint: None,
+            reserved: Vec::new(),
+            no_go: map.restrictions.no_go(robot_type, map.width, map.height),
+            penalties: map.restrictions.penalties(robot_type),
+            last_route: Cell::new(None),
+            rng: RefCell::new(StdRng::seed_from_u64(map.seed as u64)),
+            pheromones: None,
+        };
+        perception.pheromones = map.pheromones.as_ref().map(|field| {
+            let mut around = perception.neighbours(position);
+            around.push(position);
+            around
+                .into_iter()
+                .flat_map(|(x, y)| {
+                    [PheromoneKind::Explored, PheromoneKind::ResourcePath]
+                        .map(|kind| PheromoneReading {
+                            kind,
+                            position: (x, y),
+                            level: field.level(kind, x, y),
+                        })
+                })
+                .collect()
+        });
+        perception
+    }
+
+    pub fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = RefCell::new(rng);
+        self
+    }
+
+    // Seule source de hasard des robots, pour qu'une partie puisse être rejouée
+    pub fn rng(&self) -> RefMut<'_, StdRng> {
+        self.rng.borrow_mut()
+    }
+
+    pub fn has_pheromones(&self) -> bool {
+        self.pheromones.is_some()
+    }
+
+    pub fn pheromone(&self, kind: PheromoneKind, position: (usize, usize)) -> f32 {
+        self.pheromones
+            .iter()
+            .flatten()
+            .find(|reading| reading.kind == kind && reading.position == position)
+            .map_or(0.0, |reading| reading.level)
+    }
+
+    // Voisines en ligne droite qui restent sur la carte
+    pub fn neighbours(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        [(0, 1), (1, 0), (0, -1), (-1, 0)]
+            .into_iter()
+            .map(|(dx, dy)| (x as isize + dx, y as isize + dy))
+            .filter(|(x, y)| *x >= 0 && *y >= 0 && (*x as usize) < self.width && (*y as usize) < self.height)
+            .map(|(x, y)| (x as usize, y as usize))
+            .collect()
+    }
+
+    pub fn sensed(&self, x: usize, y: usize) -> Option<TileType> {
+        self.tiles
+            .iter()
+            .find(|tile| (tile.x, tile.y) == (x, y))
+            .map(|tile| tile.tile)
+    }
+
+    // Hors des zones interdites à ce robot, ou sur le plus court chemin pour en sortir
+    pub fn may_enter(&self, x: usize, y: usize) -> bool {
+        self.no_go.allows(self.position, (x, y))
+    }
+
+    // Praticable et permise depuis la position du robot
+    pub fn passable(&self, knowledge: &LocalKnowledge, x: usize, y: usize) -> bool {
+        self.may_enter(x, y) && self.walkable(knowledge, x, y)
+    }
+
+    // Ce que le robot voit prime sur ce dont il se souvient ; une tuile jamais vue est supposée libre.
+    // Les robots mémorisés ont pu bouger depuis, seuls ceux perçus maintenant bloquent le passage.
+    fn walkable(&self, knowledge: &LocalKnowledge, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        match self.sensed(x, y) {
+            Some(tile) => tile.is_walkable(),
+            None => knowledge
+                .tile(x, y)
+                .is_none_or(|tile| tile.is_walkable() || matches!(tile, TileType::Robot(_))),
+        }
+    }
+
+    // Gisement perçu sous le robot ou sur une tuile voisine
+    pub fn adjacent_deposit(&self) -> Option<(usize, usize)> {
+        let mut around = vec![self.position];
+        around.extend(self.neighbours(self.position));
+        around
+            .into_iter()
+            .find(|(x, y)| matches!(self.sensed(*x, *y), Some(TileType::Resource(_))))
+    }
+
+    // Dépôt le plus proche du robot, s'il l'est davantage que la base
+    pub fn nearest_depot(&self) -> Option<(usize, usize)> {
+        let distance = |(x, y): (usize, usize)| x.abs_diff(self.position.0) + y.abs_diff(self.position.1);
+        self.depots
+            .iter()
+            .copied()
+            .filter(|depot| distance(*depot) < distance(self.base))
+            .min_by_key(|depot| (distance(*depot), *depot))
+    }
+
+    pub fn last_route(&self) -> Option<((usize, usize), Route)> {
+        self.last_route.get()
+    }
+
+    // Plus court chemin sur la carte telle que le robot la connaît, les zones à éviter comptant pour
+    // plusieurs pas. Renvoie l'issue de la recherche et la raison du choix.
+    pub fn next_step(&self, knowledge: &LocalKnowledge, target: (usize, usize)) -> (Route, String) {
+        let (route, reason) = self.route(knowledge, target);
+        self.last_route.set(Some((target, route)));
+        (route, reason)
+    }
+
+    fn route(&self, knowledge: &LocalKnowledge, target: (usize, usize)) -> (Route, String) {
+        let (target_x, target_y) = target;
+        if self.position == target {
+            return (Route::Arrived, format!("already at ({}, {})", target_x, target_y));
+        }
+
+        let (came_from, _) = self.search(knowledge, &[target], true);
+        if !came_from.contains_key(&target) {
+            // Une cible que seule une zone interdite dessert est signalée comme telle
+            let reason = match self.search(knowledge, &[target], false).0.contains_key(&target) {
+                true => format!(
+                    "{}: ({}, {}) only reachable through a no-go area",
+                    UNREACHABLE, target_x, target_y
+                ),
+                false => format!("no path to ({}, {})", target_x, target_y),
+            };
+            return (Route::Unreachable, reason);
+        }
+
+        let mut path = Vec::new();
+        let mut current = Some(target);
+        while let Some(position) = current {
+            path.push(position);
+            current = came_from.get(&position).cloned().flatten();
+        }
+        path.reverse();
+
+        let reason = format!("path to ({}, {}): {} steps", target_x, target_y, path.len() - 1);
+        match path.len() > 2 {
+            true => (Route::Step(path[1].0, path[1].1), reason),
+            false => (Route::Arrived, reason),
+        }
+    }
+
+    // Coût du plus court chemin vers chaque cible sur la carte telle que le robot la connaît,
+    // zones à éviter comprises ; None pour une cible qu'il ne sait pas atteindre
+    pub fn path_costs(&self, knowledge: &LocalKnowledge, targets: &[(usize, usize)]) -> Vec<Option<u32>> {
+        let (_, costs) = self.search(knowledge, targets, true);
+        targets.iter().map(|target| costs.get(target).copied()).collect()
+    }
+
+    // Dijkstra depuis la position du robot, arrêté une fois toutes les cibles atteintes ;
+    // `constrained` écarte les zones interdites
+    fn search(
+        &self,
+        knowledge: &LocalKnowledge,
+        targets: &[(usize, usize)],
+        constrained: bool,
+    ) -> (CameFrom, HashMap<(usize, usize), u32>) {
+        let mut queue = BinaryHeap::new();
+        let mut costs = HashMap::new();
+        let mut came_from = HashMap::new();
+        queue.push(Reverse((0, self.position)));
+        costs.insert(self.position, 0);
+        came_from.insert(self.position, None);
+        let mut reached = 0;
+
+        while let Some(Reverse((cost, current))) = queue.pop() {
+            if costs.get(&current).is_some_and(|best| cost > *best) {
+                continue;
+            }
+            // Une cible n'est pas traversée : ce peut être une tuile non praticable
+            if targets.contains(&current) {
+                reached += 1;
+                if reached == targets.len() {
+                    break;
+                }
+                continue;
+            }
+            for next in self.neighbours(current) {
+                // La cible peut être une tuile non praticable (base, gisement, chantier)
+                let reachable = targets.contains(&next)
+                    || match constrained {
+                        true => self.no_go.allows(current, next) && self.walkable(knowledge, next.0, next.1),
+                        false => self.walkable(knowledge, next.0, next.1),
+                    };
+                let total = cost + 1 + self.penalties.get(&next).copied().unwrap_or(0);
+                if reachable && costs.get(&next).is_none_or(|best| total < *best) {
+                    costs.insert(next, total);
+                    came_from.insert(next, Some(current));
+                    queue.push(Reverse((total, next)));
+                }
+            }
+        }
+        (came_from, costs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::restriction::RestrictionKind;
+
+    const SEED: u32 = 42;
+
+    #[test]
+    fn robot_caught_in_a_new_zone_leaves_by_the_shortest_way() {
+        let mut map = Map::new(25, 25, SEED);
+        for y in 0..10 {
+            for x in 0..10 {
+                map.set(MapTile::new(x, y, TileType::Empty));
+            }
+        }
+        // Zone tracée sur le robot : la sortie la plus proche est à l'ouest, la cible à l'est
+        let zone = (2..=6).flat_map(|x| (2..=6).map(move |y| (x, y))).collect();
+        map.restrictions.add(RestrictionKind::NoGo, None, zone);
+        let no_go = map.restrictions.no_go(RobotType::HARVESTER, map.width, map.height);
+        let knowledge = LocalKnowledge::new();
+        let target = (8, 4);
+
+        let mut position = (3, 4);
+        let mut path = Vec::new();
+        while path.len() < 30 {
+            let perception = Perception::new(&map, RobotType::HARVESTER, position, Vec::new(), Vec::new());
+            let Route::Step(x, y) = perception.next_step(&knowledge, target).0 else {
+                break;
+            };
+            // La simulation accepte chacun de ces pas
+            assert!(no_go.allows(position, (x, y)));
+            position = (x, y);
+            path.push(position);
+        }
+
+        assert_eq!(path[..2], [(2, 4), (1, 4)]);
+        assert!(path[1..].iter().all(|tile| !no_go.contains(*tile)));
+        assert_eq!(target.0.abs_diff(position.0) + target.1.abs_diff(position.1), 1);
+    }
+
+    #[test]
+    fn robot_outside_a_zone_never_enters_it() {
+        let mut map = Map::new(25, 25, SEED);
+        map.restrictions.add(RestrictionKind::NoGo, None, [(3, 3)].into_iter().collect());
+        let no_go = map.restrictions.no_go(RobotType::HARVESTER, map.width, map.height);
+
+        assert!(!no_go.allows((2, 3), (3, 3)));
+        assert!(no_go.allows((3, 3), (2, 3)));
+    }
+
+    #[test]
+    fn path_costs_follow_what_the_robot_knows() {
+        let map = Map::new(25, 25, SEED);
+        // Mur connu du robot entre lui et le premier gisement, qui l'oblige à le contourner
+        let mut knowledge = LocalKnowledge::new();
+        let wall: Vec<MapTile> = (0..=6).map(|y| MapTile::new(4, y, TileType::Terrain)).collect();
+        knowledge.observe(&wall);
+        // Le second gisement est enclos de relief
+        let enclosure: Vec<MapTile> = [(19, 20), (21, 20), (20, 19), (20, 21)]
+            .into_iter()
+            .map(|(x, y)| MapTile::new(x, y, TileType::Terrain))
+            .collect();
+        knowledge.observe(&enclosure);
+
+        let perception = Perception::new(&map, RobotType::HARVESTER, (2, 2), Vec::new(), Vec::new());
+        let costs = perception.path_costs(&knowledge, &[(6, 2), (20, 20), (2, 5)]);
+        // Détour par le bas du mur : 5 pas vers le sud, 4 vers l'est et 5 vers le nord au lieu de 4
+        assert_eq!(costs, vec![Some(14), None, Some(3)]);
+    }
+}
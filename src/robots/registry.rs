@@ -0,0 +1,138 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::behaviour::{BehaviourSource, BehaviourTree};
+use super::cargo::CargoConfig;
+use super::modules::Module;
+use super::robot::{Robot, RobotType};
+use super::sensor::SensorSpec;
+use super::BUILTIN_KINDS;
+use crate::environment::tile::{Resource, ResourceType};
+use crate::simulation::simulation::Simulation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RobotRole {
+    Exploration,
+    Harvesting,
+    Construction,
+    Maintenance,
+    Logistics,
+    Scripted,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RobotConfig {
+    pub energy_cost: u32,
+    pub mineral_cost: u32,
+    pub build_ticks: u32,
+    pub sensor: SensorSpec,
+    // Tuiles par seconde de simulation, sur sol dégagé
+    pub speed: f32,
+    pub battery_capacity: Option<u32>,
+    pub cargo: Option<CargoConfig>,
+    pub breaks_down: bool,
+    // Vide pour les châssis spécialisés, qui ne peuvent pas être rééquipés
+    pub modules: &'static [Module],
+}
+
+impl RobotConfig {
+    pub fn cost(&self) -> Vec<Resource> {
+        vec![
+            Resource::new(self.energy_cost, ResourceType::Energy),
+            Resource::new(self.mineral_cost, ResourceType::Mineral),
+        ]
+    }
+}
+
+// Tout ce dont un constructeur a besoin pour créer un robot à la base
+pub struct Spawn {
+    pub robot_type: RobotType,
+    pub id: usize,
+    pub position: (usize, usize),
+    pub config: RobotConfig,
+    pub behaviour: Option<Arc<BehaviourTree>>,
+}
+
+pub type Constructor = fn(&Spawn) -> Box<dyn Robot + Send>;
+pub type ArrivalHandler = fn(&mut Simulation, &mut Box<dyn Robot + Send>);
+pub type AbandonHandler = fn(&mut Simulation, &mut Box<dyn Robot + Send>);
+
+// Pour les types qui ne reçoivent pas de mission de la base
+pub fn no_order(_simulation: &mut Simulation, _robot: &mut Box<dyn Robot + Send>) {}
+
+#[derive(Clone)]
+pub struct RobotKind {
+    pub robot_type: RobotType,
+    pub glyph: char,
+    pub role: RobotRole,
+    // Peut être envoyé depuis l'interface sans ordre particulier
+    pub manual_dispatch: bool,
+    pub config: RobotConfig,
+    // Arbre de comportement relu depuis son fichier à chaque envoi
+    pub behaviour: Option<BehaviourSource>,
+    pub constructor: Constructor,
+    pub on_arrival: ArrivalHandler,
+    // Rend la mission en cours quand le robot y renonce
+    pub on_abandon: AbandonHandler,
+    // La mission reprend après un passage à la base ; sinon rentrer revient à y renoncer
+    pub resumes_order: bool,
+}
+
+pub struct RobotRegistry {
+    kinds: Vec<RobotKind>,
+}
+
+impl RobotRegistry {
+    pub fn new() -> Self {
+        RobotRegistry { kinds: Vec::new() }
+    }
+
+    pub fn with_builtin_kinds() -> Self {
+        let mut registry = RobotRegistry::new();
+        for kind in BUILTIN_KINDS {
+            registry.register(kind());
+        }
+        registry
+    }
+
+    // Un type déjà enregistré est remplacé
+    pub fn register(&mut self, kind: RobotKind) {
+        match self
+            .kinds
+            .iter_mut()
+            .find(|existing| existing.robot_type == kind.robot_type)
+        {
+            Some(existing) => *existing = kind,
+            None => self.kinds.push(kind),
+        }
+    }
+
+    pub fn get(&self, robot_type: RobotType) -> Option<&RobotKind> {
+        self.kinds.iter().find(|kind| kind.robot_type == robot_type)
+    }
+
+    pub fn kinds(&self) -> &[RobotKind] {
+        &self.kinds
+    }
+
+    // Type que la base produit ou envoie pour un rôle : le premier enregistré
+    pub fn for_role(&self, role: RobotRole) -> Option<RobotType> {
+        self.kinds
+            .iter()
+            .find(|kind| kind.role == role)
+            .map(|kind| kind.robot_type)
+    }
+
+    pub fn role_of(&self, robot_type: RobotType) -> Option<RobotRole> {
+        self.get(robot_type).map(|kind| kind.role)
+    }
+
+    pub fn glyph(&self, robot_type: RobotType) -> char {
+        self.get(robot_type).map(|kind| kind.glyph).unwrap_or('?')
+    }
+}
+
+static REGISTRY: OnceLock<RwLock<RobotRegistry>> = OnceLock::new();
+
+pub fn registry() -> &'static RwLock<RobotRegistry> {
+    REGISTRY.get_or_init(|| RwLock::new(RobotRegistry::with_builtin_kinds()))
+}
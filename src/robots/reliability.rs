@@ -0,0 +1,96 @@
+use std::sync::{Arc, Mutex};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::robot::{RobotState, RobotType};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Wear {
+    pub distance: u32,
+    pub age: u32,
+}
+
+impl Wear {
+    pub fn record(&mut self, moved: u32) {
+        self.distance += moved;
+        self.age += 1;
+    }
+
+    pub fn level(&self) -> u32 {
+        self.distance + self.age / 10
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReliabilityModel {
+    pub base_rate: f64,
+    pub distance_rate: f64,
+    pub age_rate: f64,
+    pub max_rate: f64,
+    // Au-delà de ce niveau d'usure, le robot est remorqué à la base au lieu d'être réparé sur place
+    pub tow_threshold: u32,
+}
+
+impl Default for ReliabilityModel {
+    fn default() -> Self {
+        ReliabilityModel {
+            base_rate: 0.0005,
+            distance_rate: 0.00005,
+            age_rate: 0.00001,
+            max_rate: 0.05,
+            tow_threshold: 150,
+        }
+    }
+}
+
+impl ReliabilityModel {
+    pub fn breakdown_probability(&self, wear: &Wear) -> f64 {
+        let rate = self.base_rate
+            + self.distance_rate * wear.distance as f64
+            + self.age_rate * wear.age as f64;
+        rate.clamp(0.0, self.max_rate)
+    }
+
+    // Chaque robot tire ses pannes d'un générateur dérivé de la seed de la carte
+    pub fn rng_for(seed: u32, robot_type: RobotType, id: usize) -> StdRng {
+        let type_hash = robot_type
+            .name()
+            .bytes()
+            .fold(0u64, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as u64));
+        StdRng::seed_from_u64(((seed as u64) << 32) ^ (type_hash << 24) ^ id as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepairStatus {
+    Waiting,
+    Repaired,
+    InTow,
+    Delivered,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepairOrder {
+    pub patient: (RobotType, usize),
+    pub position: (usize, usize),
+    // Décidé à la panne selon l'usure du robot
+    pub tow: bool,
+    pub status: Arc<Mutex<RepairStatus>>,
+}
+
+impl RepairOrder {
+    pub fn status(&self) -> RepairStatus {
+        *self.status.lock().unwrap()
+    }
+
+    pub fn set_status(&self, status: RepairStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Breakdown {
+    pub resume_state: RobotState,
+    pub order: RepairOrder,
+    pub towed: bool,
+}
@@ -0,0 +1,176 @@
+use super::registry::{RobotConfig, RobotKind, RobotRole, Spawn};
+use super::reliability::{RepairOrder, RepairStatus};
+use super::perception::{Action, Perception, Route};
+use super::robot::{Order, Robot, RobotState, RobotType};
+use super::sensor::{LocalKnowledge, SensorSpec};
+use crate::simulation::simulation::Simulation;
+
+const REPAIR_TICKS: u32 = 4;
+
+pub fn repair_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::REPAIR,
+        glyph: '🚑',
+        role: RobotRole::Maintenance,
+        manual_dispatch: false,
+        config: RobotConfig {
+            energy_cost: 4,
+            mineral_cost: 3,
+            build_ticks: 4,
+            sensor: SensorSpec::default(),
+            speed: 1.5,
+            battery_capacity: None,
+            cargo: None,
+            // Personne ne viendrait réparer le réparateur
+            breaks_down: false,
+            modules: &[],
+        },
+        behaviour: None,
+        constructor: |spawn| Box::new(Repair::new(spawn)),
+        on_arrival,
+        on_abandon,
+        resumes_order: false,
+    }
+}
+
+fn on_arrival(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(Order::Repair(order)) = robot.order() {
+        if order.status() == RepairStatus::InTow {
+            order.set_status(RepairStatus::Delivered);
+        }
+        let (patient_type, patient_id) = order.patient;
+        simulation.log_event(format!(
+            "Repair {} back from {} {}",
+            robot.get_id(),
+            patient_type,
+            patient_id
+        ));
+    }
+    simulation.dock(robot);
+}
+
+fn on_abandon(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    if let Some(Order::Repair(order)) = robot.order() {
+        let (patient_type, patient_id) = order.patient;
+        simulation.log_event(format!("{} {} left waiting for repair", patient_type, patient_id));
+    }
+}
+
+pub struct Repair {
+    id: usize,
+    x: usize,
+    y: usize,
+    state: RobotState,
+    knowledge: LocalKnowledge,
+    order: Option<RepairOrder>,
+    progress: u32,
+    notes: Vec<String>,
+}
+
+impl Robot for Repair {
+    fn get_type(&self) -> RobotType {
+        RobotType::REPAIR
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    fn get_state(&self) -> RobotState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: RobotState) {
+        self.state = state;
+    }
+
+    fn set_position(&mut self, x: usize, y: usize) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn update(&mut self, perception: &Perception) -> Action {
+        self.knowledge.observe(&perception.tiles);
+        match self.state {
+            RobotState::Repairing => self.repair(perception),
+            RobotState::ReturningToBase => self.return_to_base(perception),
+            _ => Action::Wait,
+        }
+    }
+
+    fn knowledge(&self) -> &LocalKnowledge {
+        &self.knowledge
+    }
+
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge {
+        &mut self.knowledge
+    }
+
+    fn order(&self) -> Option<Order> {
+        self.order.clone().map(Order::Repair)
+    }
+
+    fn set_order(&mut self, order: Option<Order>) {
+        self.order = match order {
+            Some(Order::Repair(order)) => Some(order),
+            _ => None,
+        };
+        self.progress = 0;
+    }
+
+    fn note(&mut self, reason: String) {
+        self.notes.push(reason);
+    }
+
+    fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+
+    fn deploy(&mut self) {
+        self.set_state(RobotState::Repairing);
+    }
+}
+
+impl Repair {
+    pub fn new(spawn: &Spawn) -> Self {
+        Repair {
+            id: spawn.id,
+            x: spawn.position.0,
+            y: spawn.position.1,
+            state: RobotState::Repairing,
+            knowledge: LocalKnowledge::new(),
+            order: None,
+            progress: 0,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn repair(&mut self, perception: &Perception) -> Action {
+        let Some(order) = self.order.clone() else {
+            self.set_state(RobotState::ReturningToBase);
+            return Action::Wait;
+        };
+
+        let (patient_x, patient_y) = order.position;
+        match self.calculate_next_step(patient_x, patient_y, perception) {
+            Route::Step(next_x, next_y) => Action::Move(next_x, next_y),
+            Route::Unreachable => Action::Wait,
+            Route::Arrived => {
+                self.progress += 1;
+                if self.progress >= REPAIR_TICKS {
+                    if order.tow {
+                        order.set_status(RepairStatus::InTow);
+                    } else {
+                        order.set_status(RepairStatus::Repaired);
+                    }
+                    self.set_state(RobotState::ReturningToBase);
+                }
+                Action::Wait
+            }
+        }
+    }
+}
@@ -1,17 +1,35 @@
-use std::{
-    any::Any,
-    collections::{HashMap, VecDeque},
-};
+use std::{any::Any, fmt};
 
-use crate::environment::{
-    map::Map,
-    tile::{MapTile, Resource, TileType},
-};
+use crate::environment::{pheromone::PheromoneKind, structure::StructureType, tile::Resource};
 
-#[derive(Debug, Clone, PartialEq, Copy)]
-pub enum RobotType {
-    Explorer,
-    Harvester,
+use super::perception::{Action, Perception, Route};
+use super::reliability::RepairOrder;
+use super::sensor::LocalKnowledge;
+use super::upgrades::UpgradeLevels;
+
+// Un type de robot est identifié par son nom dans le registre
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+pub struct RobotType(pub &'static str);
+
+impl RobotType {
+    pub const EXPLORER: RobotType = RobotType("Explorer");
+    pub const SCOUT: RobotType = RobotType("Scout");
+    pub const HARVESTER: RobotType = RobotType("Harvester");
+    pub const BUILDER: RobotType = RobotType("Builder");
+    pub const REPAIR: RobotType = RobotType("Repair");
+    pub const SCRIPT: RobotType = RobotType("Script");
+    pub const SWARM: RobotType = RobotType("Swarm");
+    pub const CARRIER: RobotType = RobotType("Carrier");
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for RobotType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
@@ -20,131 +38,144 @@ pub enum RobotState {
     Harvesting,
     ReturningToBase,
     Reporting,
+    Building,
+    Repairing,
+    Hauling,
+    Broken,
+    // À quai dans la base, en attente d'une tâche
+    Waiting,
     Idle,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceTarget {
+    pub x: usize,
+    pub y: usize,
+    pub resource: Resource,
+    // Le gisement contient encore des unités après le dernier passage
+    pub remaining: bool,
+}
+
+impl ResourceTarget {
+    pub fn new(x: usize, y: usize, resource: Resource) -> Self {
+        ResourceTarget {
+            x,
+            y,
+            resource,
+            remaining: true,
+        }
+    }
+}
+
+// Mission confiée par la base ; un robot ignore celles qui ne relèvent pas de son rôle
+#[derive(Debug, Clone)]
+pub enum Order {
+    Harvest(ResourceTarget),
+    Build(StructureType, usize, usize),
+    // Dépôt à vider
+    Haul(usize, usize),
+    Repair(RepairOrder),
+}
+
 pub trait Robot: Any {
-    fn new(x: usize, y: usize, id: usize) -> Self
-    where
-        Self: Sized;
     fn get_id(&self) -> usize;
     fn get_position(&self) -> (usize, usize);
     fn get_state(&self) -> RobotState;
     fn set_state(&mut self, state: RobotState);
     fn get_type(&self) -> RobotType;
-    fn update(&mut self, map: &mut Map);
-    fn get_current_resource(&self) -> Option<(usize, usize, Resource, bool)>;
-    fn set_target_resource(&mut self, target_resource: Option<(usize, usize, Resource, bool)>);
-
-    fn move_to(&mut self, x: usize, y: usize, map: &mut Map) {
-        if map.is_valid(x, y) {
-            let (old_x, old_y) = self.get_position();
-            let (base_x, base_y) = map.base_position;
-
-            if old_x == base_x && old_y == base_y {
-                map.set(MapTile::new(old_x, old_y, TileType::Base));
-            } else {
-                map.set(MapTile::new(old_x, old_y, TileType::Empty));
-            }
+    // Le robot ne voit que sa perception et renvoie ce qu'il veut faire ; la simulation décide
+    fn update(&mut self, perception: &Perception) -> Action;
 
-            map.set(MapTile::new(x, y, TileType::Robot(self.get_type())));
+    // Tuiles mémorisées, sur lesquelles il calcule ses chemins
+    fn knowledge(&self) -> &LocalKnowledge;
 
-            self.set_position(x, y);
-        } else {
-            eprintln!("Invalid move to position ({}, {})", x, y);
-        }
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge;
+
+    fn get_current_resource(&self) -> Option<ResourceTarget> {
+        None
+    }
+
+    fn unload_cargo(&mut self) -> Vec<Resource> {
+        Vec::new()
+    }
+
+    // Chargement laissé dans un dépôt en chemin vers la base
+    fn drop_cargo(&mut self) -> Vec<Resource> {
+        self.unload_cargo()
+    }
+
+    // Trace déposée sur la tuile du robot après chacun de ses pas
+    fn pheromone(&self) -> Option<PheromoneKind> {
+        None
+    }
+
+    // Poids encore libre dans la soute, annoncé avec chaque demande de récolte
+    fn free_cargo(&self) -> u32 {
+        0
+    }
+
+    // Récolte acceptée par la simulation : renvoie les unités effectivement chargées
+    fn load_from(&mut self, _position: (usize, usize), _deposit: Resource) -> u32 {
+        0
+    }
+
+    fn apply_upgrades(&mut self, _levels: &UpgradeLevels) {}
+
+    // Messages destinés au journal de l'interface, vidés à chaque tick
+    fn take_messages(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Chemin du nœud de l'arbre de comportement en cours d'exécution
+    fn running_node(&self) -> Option<String> {
+        None
+    }
+
+    fn order(&self) -> Option<Order> {
+        None
+    }
+
+    fn set_order(&mut self, _order: Option<Order>) {}
+
+    // Offre du robot pour chaque gisement annoncé : le coût de son propre chemin jusque-là,
+    // rien pour un gisement qu'il ne sait pas atteindre
+    fn bid(&self, perception: &Perception, deposits: &[(usize, usize)]) -> Vec<Option<u32>> {
+        perception.path_costs(self.knowledge(), deposits)
+    }
+
+    // Repart de la base après avoir reçu une nouvelle mission à quai
+    fn deploy(&mut self) {}
+
+    // Raison d'un choix, reprise dans la trace de décisions du robot
+    fn note(&mut self, _reason: String) {}
+
+    fn take_notes(&mut self) -> Vec<String> {
+        Vec::new()
     }
 
     fn calculate_next_step(
-        &self,
+        &mut self,
         target_x: usize,
         target_y: usize,
-        map: &Map,
-    ) -> Option<(usize, usize)> {
-        let (start_x, start_y) = self.get_position();
-        if start_x == target_x && start_y == target_y {
-            return None;
-        }
-    
-        let mut queue = VecDeque::new();
-        let mut came_from = HashMap::new();
-    
-        queue.push_back((start_x, start_y));
-        came_from.insert((start_x, start_y), None);
-    
-        while let Some((x, y)) = queue.pop_front() {
-            if x == target_x && y == target_y {
-                break;
-            }
-    
-            // Se déplacer uniquement en ligne droite (haut, bas, gauche, droite)
-            for &(dx, dy) in &[(0, 1), (1, 0), (0, -1), (-1, 0)] {
-                let new_x = x as isize + dx;
-                let new_y = y as isize + dy;
-    
-                if new_x < 0 || new_y < 0 {
-                    continue;
-                }
-    
-                let new_x = new_x as usize;
-                let new_y = new_y as usize;
-    
-                if map.is_valid(new_x, new_y) && !came_from.contains_key(&(new_x, new_y)) {
-                    queue.push_back((new_x, new_y));
-                    came_from.insert((new_x, new_y), Some((x, y)));
-                }
-            }
-        }
-    
-        if !came_from.contains_key(&(target_x, target_y)) {
-            // Essayer toutes les directions possibles jusqu'à en trouver une valide
-            let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-            for &(dx, dy) in &directions {
-                let new_x = start_x as isize + dx;
-                let new_y = start_y as isize + dy;
-    
-                if new_x >= 0 && new_y >= 0 {
-                    let new_x = new_x as usize;
-                    let new_y = new_y as usize;
-                    if map.is_valid(new_x, new_y) {
-                        return Some((new_x, new_y));
-                    }
-                }
-            }
-            return None;
-        }
-    
-        let mut path = Vec::new();
-        let mut current = Some((target_x, target_y));
-    
-        while let Some(pos) = current {
-            path.push(pos);
-            current = came_from.get(&pos).cloned().flatten();
-        }
-    
-        path.reverse();
-        if path.len() > 2 {
-            Some(path[1])
-        } else {
-            None
-        }
+        perception: &Perception,
+    ) -> Route {
+        let (route, reason) = perception.next_step(self.knowledge(), (target_x, target_y));
+        self.note(reason);
+        route
     }
-    
-    
-    
 
     fn set_position(&mut self, x: usize, y: usize);
 
-    fn return_to_base(&mut self, map: &mut Map) {
-        let (base_x, base_y) = map.base_position;
+    fn return_to_base(&mut self, perception: &Perception) -> Action {
+        let (base_x, base_y) = perception.base;
 
-        match self.calculate_next_step(base_x, base_y, map) {
-            Some((x, y)) => {
-                self.move_to(x, y, map);
-            }
-            None => {
+        match self.calculate_next_step(base_x, base_y, perception) {
+            Route::Step(x, y) => Action::Move(x, y),
+            Route::Arrived => {
                 self.set_state(RobotState::Reporting);
+                Action::Wait
             }
+            Route::Unreachable => Action::Wait,
         }
     }
-}
+}
\ No newline at end of file
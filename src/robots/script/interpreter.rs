@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use super::parser::{Command, Direction, Expr, Instruction, Operator, Program, Value};
+use crate::robots::perception::Perception;
+
+// Nombre maximal d'instructions exécutées par tick : une boucle sans action ne bloque pas le thread
+pub const STEP_BUDGET: usize = 200;
+
+// Commande dont les arguments ont été évalués
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Move(Direction),
+    Goto(i64, i64),
+    Harvest,
+    Report,
+    Home,
+    Wait,
+}
+
+// Ce que le robot fournit au script : perception, actions et journal
+pub trait ScriptHost {
+    fn query(&mut self, name: &str, arguments: &[i64], perception: &Perception) -> Value;
+    // Renvoie vrai lorsque l'ordre est terminé ; sinon il reprend au tick suivant
+    fn execute(&mut self, order: Order, perception: &Perception) -> bool;
+    fn log(&mut self, message: String);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Flow {
+    Yield,
+    Finished,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Machine {
+    pc: usize,
+    variables: HashMap<String, Value>,
+}
+
+impl Machine {
+    pub fn current_line(&self, program: &Program) -> Option<usize> {
+        program.lines.get(self.pc).copied()
+    }
+
+    // Exécute le script jusqu'à la première action, la fin du script ou l'épuisement du budget
+    pub fn run(&mut self, program: &Program, host: &mut dyn ScriptHost, perception: &Perception) -> Result<Flow, String> {
+        for _ in 0..STEP_BUDGET {
+            let Some(instruction) = program.instructions.get(self.pc) else {
+                return Ok(Flow::Finished);
+            };
+            let line = program.lines[self.pc];
+            let at_line = |error: String| format!("line {}: {}", line, error);
+
+            match instruction {
+                Instruction::Assign(name, expression) => {
+                    let value = self.eval(expression, host, perception).map_err(at_line)?;
+                    self.variables.insert(name.clone(), value);
+                    self.pc += 1;
+                }
+                Instruction::JumpUnless(condition, target) => {
+                    match self.eval(condition, host, perception).map_err(at_line)? {
+                        Value::Bool(true) => self.pc += 1,
+                        Value::Bool(false) => self.pc = *target,
+                        other => return Err(at_line(format!("condition is not true/false: {}", other))),
+                    }
+                }
+                Instruction::Jump(target) => self.pc = *target,
+                Instruction::Log(message) => {
+                    let message = self.eval(message, host, perception).map_err(at_line)?;
+                    host.log(message.to_string());
+                    self.pc += 1;
+                }
+                Instruction::Command(command) => {
+                    let order = self.order(command, host, perception).map_err(at_line)?;
+                    if host.execute(order, perception) {
+                        self.pc += 1;
+                    }
+                    return Ok(Flow::Yield);
+                }
+            }
+        }
+        Ok(Flow::Yield)
+    }
+
+    fn order(&self, command: &Command, host: &mut dyn ScriptHost, perception: &Perception) -> Result<Order, String> {
+        Ok(match command {
+            Command::Move(direction) => Order::Move(*direction),
+            Command::Goto(x, y) => Order::Goto(
+                self.number(x, host, perception)?,
+                self.number(y, host, perception)?,
+            ),
+            Command::Harvest => Order::Harvest,
+            Command::Report => Order::Report,
+            Command::Home => Order::Home,
+            Command::Wait => Order::Wait,
+        })
+    }
+
+    fn number(&self, expression: &Expr, host: &mut dyn ScriptHost, perception: &Perception) -> Result<i64, String> {
+        match self.eval(expression, host, perception)? {
+            Value::Number(number) => Ok(number),
+            other => Err(format!("expected a number, got {}", other)),
+        }
+    }
+
+    fn eval(&self, expression: &Expr, host: &mut dyn ScriptHost, perception: &Perception) -> Result<Value, String> {
+        match expression {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Variable(name) => self
+                .variables
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("unknown variable '{}'", name)),
+            Expr::Query(name, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.number(argument, host, perception))
+                    .collect::<Result<Vec<i64>, String>>()?;
+                Ok(host.query(name, &arguments, perception))
+            }
+            Expr::Unary(operator, operand) => unary(*operator, self.eval(operand, host, perception)?),
+            // 'and' et 'or' n'évaluent leur droite que si nécessaire
+            Expr::Binary(Operator::And, left, right) => match self.eval(left, host, perception)? {
+                Value::Bool(false) => Ok(Value::Bool(false)),
+                Value::Bool(true) => self.eval(right, host, perception),
+                other => Err(format!("expected true/false, got {}", other)),
+            },
+            Expr::Binary(Operator::Or, left, right) => match self.eval(left, host, perception)? {
+                Value::Bool(true) => Ok(Value::Bool(true)),
+                Value::Bool(false) => self.eval(right, host, perception),
+                other => Err(format!("expected true/false, got {}", other)),
+            },
+            Expr::Binary(operator, left, right) => {
+                let left = self.eval(left, host, perception)?;
+                let right = self.eval(right, host, perception)?;
+                binary(*operator, left, right)
+            }
+        }
+    }
+}
+
+fn unary(operator: Operator, value: Value) -> Result<Value, String> {
+    match (operator, value) {
+        (Operator::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+        (Operator::Neg, Value::Number(value)) => value
+            .checked_neg()
+            .map(Value::Number)
+            .ok_or_else(|| format!("cannot negate {}", value)),
+        (operator, value) => Err(format!("cannot apply '{}' to {}", operator, value)),
+    }
+}
+
+fn binary(operator: Operator, left: Value, right: Value) -> Result<Value, String> {
+    use Value::{Bool, Number, Text};
+
+    match (operator, left, right) {
+        (Operator::Add, Text(left), right) => Ok(Text(format!("{}{}", left, right))),
+        (Operator::Add, left, Text(right)) => Ok(Text(format!("{}{}", left, right))),
+        (Operator::Eq, left, right) => Ok(Bool(left == right)),
+        (Operator::Ne, left, right) => Ok(Bool(left != right)),
+        (Operator::Div | Operator::Rem, Number(_), Number(0)) => Err("division by zero".to_string()),
+        (operator, Number(left), Number(right)) => match operator {
+            // Un débordement est une erreur du script, y compris i64::MIN / -1 et i64::MIN % -1
+            Operator::Add => checked(operator, left, right, left.checked_add(right)),
+            Operator::Sub => checked(operator, left, right, left.checked_sub(right)),
+            Operator::Mul => checked(operator, left, right, left.checked_mul(right)),
+            Operator::Div => checked(operator, left, right, left.checked_div(right)),
+            Operator::Rem => checked(operator, left, right, left.checked_rem(right)),
+            Operator::Lt => Ok(Bool(left < right)),
+            Operator::Le => Ok(Bool(left <= right)),
+            Operator::Gt => Ok(Bool(left > right)),
+            Operator::Ge => Ok(Bool(left >= right)),
+            _ => Err(format!("cannot apply '{}' to numbers", operator)),
+        },
+        (operator, left, right) => Err(format!("cannot apply '{}' to {} and {}", operator, left, right)),
+    }
+}
+
+fn checked(operator: Operator, left: i64, right: i64, result: Option<i64>) -> Result<Value, String> {
+    result
+        .map(Value::Number)
+        .ok_or_else(|| format!("{} {} {} overflows", left, operator, right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negating_the_smallest_number_is_an_error() {
+        assert!(unary(Operator::Neg, Value::Number(i64::MIN)).is_err());
+        assert_eq!(unary(Operator::Neg, Value::Number(5)), Ok(Value::Number(-5)));
+    }
+
+    #[test]
+    fn dividing_the_smallest_number_by_minus_one_is_an_error() {
+        assert!(binary(Operator::Div, Value::Number(i64::MIN), Value::Number(-1)).is_err());
+        assert_eq!(binary(Operator::Div, Value::Number(7), Value::Number(-2)), Ok(Value::Number(-3)));
+    }
+
+    #[test]
+    fn remainder_of_the_smallest_number_by_minus_one_is_an_error() {
+        assert!(binary(Operator::Rem, Value::Number(i64::MIN), Value::Number(-1)).is_err());
+        assert_eq!(binary(Operator::Rem, Value::Number(7), Value::Number(-2)), Ok(Value::Number(1)));
+    }
+
+    #[test]
+    fn adding_past_the_largest_number_is_an_error() {
+        assert_eq!(
+            binary(Operator::Add, Value::Number(i64::MAX), Value::Number(1)),
+            Err(format!("{} + 1 overflows", i64::MAX))
+        );
+        assert!(binary(Operator::Add, Value::Number(i64::MIN), Value::Number(-1)).is_err());
+        assert_eq!(binary(Operator::Add, Value::Number(i64::MAX), Value::Number(-1)), Ok(Value::Number(i64::MAX - 1)));
+    }
+
+    #[test]
+    fn subtracting_past_the_smallest_number_is_an_error() {
+        assert_eq!(
+            binary(Operator::Sub, Value::Number(i64::MIN), Value::Number(1)),
+            Err(format!("{} - 1 overflows", i64::MIN))
+        );
+        assert!(binary(Operator::Sub, Value::Number(i64::MAX), Value::Number(-1)).is_err());
+        assert_eq!(binary(Operator::Sub, Value::Number(i64::MIN), Value::Number(-1)), Ok(Value::Number(i64::MIN + 1)));
+    }
+
+    #[test]
+    fn multiplying_past_either_bound_is_an_error() {
+        assert_eq!(
+            binary(Operator::Mul, Value::Number(i64::MAX), Value::Number(2)),
+            Err(format!("{} * 2 overflows", i64::MAX))
+        );
+        assert!(binary(Operator::Mul, Value::Number(i64::MIN), Value::Number(-1)).is_err());
+        assert_eq!(binary(Operator::Mul, Value::Number(i64::MIN), Value::Number(1)), Ok(Value::Number(i64::MIN)));
+    }
+}
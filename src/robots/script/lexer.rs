@@ -0,0 +1,102 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Number(i64),
+    Word(String),
+    Text(String),
+    Symbol(&'static str),
+}
+
+// Les symboles de deux caractères doivent être essayés en premier
+const SYMBOLS: [&str; 15] = [
+    "==", "!=", "<=", ">=", "<", ">", "=", "+", "-", "*", "/", "%", "(", ")", ",",
+];
+
+// Découpe une ligne de script ; tout ce qui suit un '#' hors d'une chaîne est un commentaire
+pub fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+
+    loop {
+        rest = rest.trim_start();
+        let Some(first) = rest.chars().next() else {
+            break;
+        };
+
+        if first == '#' {
+            break;
+        } else if first == '"' {
+            let Some(end) = rest[1..].find('"') else {
+                return Err("unterminated text".to_string());
+            };
+            tokens.push(Token::Text(rest[1..=end].to_string()));
+            rest = &rest[end + 2..];
+        } else if first.is_ascii_digit() {
+            let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("number too large: {}", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if first.is_alphabetic() || first == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Word(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) else {
+                return Err(format!("unexpected character '{}'", first));
+            };
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_a_line_into_tokens() {
+        let tokens = tokenize("let total = cargo() + 12 # plein ?").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Word("let".to_string()),
+                Token::Word("total".to_string()),
+                Token::Symbol("="),
+                Token::Word("cargo".to_string()),
+                Token::Symbol("("),
+                Token::Symbol(")"),
+                Token::Symbol("+"),
+                Token::Number(12),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefers_two_character_symbols() {
+        let tokens = tokenize("a<=b==c").unwrap();
+        assert_eq!(tokens[1], Token::Symbol("<="));
+        assert_eq!(tokens[3], Token::Symbol("=="));
+    }
+
+    #[test]
+    fn keeps_a_hash_inside_text() {
+        // Le '#' d'une chaîne n'ouvre pas de commentaire
+        let tokens = tokenize("log \"load #2\" # fin").unwrap();
+        assert_eq!(tokens, vec![Token::Word("log".to_string()), Token::Text("load #2".to_string())]);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(tokenize("log \"open"), Err("unterminated text".to_string()));
+        assert_eq!(tokenize("x = 1 ; y"), Err("unexpected character ';'".to_string()));
+        assert_eq!(
+            tokenize("x = 99999999999999999999"),
+            Err("number too large: 99999999999999999999".to_string())
+        );
+    }
+}
@@ -0,0 +1,582 @@
+use std::fmt;
+
+use super::lexer::{tokenize, Token};
+
+// Requêtes de perception disponibles dans les scripts, avec leur nombre d'arguments
+pub const QUERIES: [(&str, usize); 12] = [
+    ("x", 0),
+    ("y", 0),
+    ("base_x", 0),
+    ("base_y", 0),
+    ("at_base", 0),
+    ("resource_seen", 0),
+    ("resource_x", 0),
+    ("resource_y", 0),
+    ("cargo", 0),
+    ("cargo_full", 0),
+    ("blocked", 0),
+    ("random", 1),
+];
+
+const KEYWORDS: [&str; 18] = [
+    "let", "if", "else", "end", "while", "repeat", "and", "or", "not", "true", "false", "move",
+    "goto", "harvest", "report", "home", "wait", "log",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(i64),
+    Bool(bool),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Bool(boolean) => write!(f, "{}", boolean),
+            Value::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Not,
+    Neg,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Sub | Operator::Neg => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Rem => "%",
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::And => "and",
+            Operator::Or => "or",
+            Operator::Not => "not",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Variable(String),
+    Query(String, Vec<Expr>),
+    Unary(Operator, Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    Random,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Move(Direction),
+    Goto(Expr, Expr),
+    Harvest,
+    Report,
+    Home,
+    Wait,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Assign(String, Expr),
+    JumpUnless(Expr, usize),
+    Jump(usize),
+    Command(Command),
+    Log(Expr),
+}
+
+// Script compilé en instructions à plat, pour pouvoir reprendre l'exécution au tick suivant
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    pub lines: Vec<usize>,
+}
+
+enum Block {
+    If {
+        line: usize,
+        jump: usize,
+        else_jump: Option<usize>,
+    },
+    Loop {
+        line: usize,
+        start: usize,
+        jump: usize,
+    },
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut program = Program::default();
+        let mut blocks: Vec<Block> = Vec::new();
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let tokens = tokenize(text).map_err(|error| format!("line {}: {}", line, error))?;
+            program
+                .statement(line, &tokens, &mut blocks)
+                .map_err(|error| format!("line {}: {}", line, error))?;
+        }
+
+        match blocks.last() {
+            Some(Block::If { line, .. }) | Some(Block::Loop { line, .. }) => {
+                Err(format!("line {}: block is never closed with 'end'", line))
+            }
+            None => Ok(program),
+        }
+    }
+
+    fn emit(&mut self, line: usize, instruction: Instruction) -> usize {
+        self.instructions.push(instruction);
+        self.lines.push(line);
+        self.instructions.len() - 1
+    }
+
+    fn patch(&mut self, at: usize, target: usize) {
+        match &mut self.instructions[at] {
+            Instruction::JumpUnless(_, jump) | Instruction::Jump(jump) => *jump = target,
+            _ => unreachable!("only jumps are patched"),
+        }
+    }
+
+    fn statement(&mut self, line: usize, tokens: &[Token], blocks: &mut Vec<Block>) -> Result<(), String> {
+        let Some(Token::Word(first)) = tokens.first() else {
+            return match tokens.first() {
+                Some(token) => Err(format!("unexpected '{}'", token)),
+                None => Ok(()),
+            };
+        };
+        let rest = &tokens[1..];
+
+        match first.as_str() {
+            "let" => match rest {
+                [Token::Word(name), Token::Symbol("="), value @ ..] => {
+                    self.assign(line, name, value)
+                }
+                _ => Err("expected 'let <name> = <value>'".to_string()),
+            },
+            "if" => {
+                let condition = parse_expression(rest)?;
+                let jump = self.emit(line, Instruction::JumpUnless(condition, 0));
+                blocks.push(Block::If {
+                    line,
+                    jump,
+                    else_jump: None,
+                });
+                Ok(())
+            }
+            "else" => {
+                expect_end(rest)?;
+                let Some(Block::If {
+                    jump,
+                    else_jump: else_jump @ None,
+                    ..
+                }) = blocks.last_mut()
+                else {
+                    return Err("'else' without 'if'".to_string());
+                };
+                let jump = *jump;
+                let skip = self.instructions.len();
+                *else_jump = Some(skip);
+                self.emit(line, Instruction::Jump(0));
+                self.patch(jump, skip + 1);
+                Ok(())
+            }
+            "while" => {
+                let condition = parse_expression(rest)?;
+                let start = self.instructions.len();
+                let jump = self.emit(line, Instruction::JumpUnless(condition, 0));
+                blocks.push(Block::Loop { line, start, jump });
+                Ok(())
+            }
+            "repeat" => {
+                // Le compteur porte un nom qu'aucun script ne peut écrire
+                let counter = format!("#repeat{}", line);
+                let count = parse_expression(rest)?;
+                self.emit(line, Instruction::Assign(counter.clone(), count));
+                let start = self.instructions.len();
+                let remaining = Expr::Binary(
+                    Operator::Gt,
+                    Box::new(Expr::Variable(counter.clone())),
+                    Box::new(Expr::Literal(Value::Number(0))),
+                );
+                let jump = self.emit(line, Instruction::JumpUnless(remaining, 0));
+                let decrement = Expr::Binary(
+                    Operator::Sub,
+                    Box::new(Expr::Variable(counter.clone())),
+                    Box::new(Expr::Literal(Value::Number(1))),
+                );
+                self.emit(line, Instruction::Assign(counter, decrement));
+                blocks.push(Block::Loop { line, start, jump });
+                Ok(())
+            }
+            "end" => {
+                expect_end(rest)?;
+                match blocks.pop() {
+                    Some(Block::If { jump, else_jump, .. }) => {
+                        self.patch(else_jump.unwrap_or(jump), self.instructions.len());
+                    }
+                    Some(Block::Loop { start, jump, .. }) => {
+                        self.emit(line, Instruction::Jump(start));
+                        self.patch(jump, self.instructions.len());
+                    }
+                    None => return Err("'end' without an open block".to_string()),
+                }
+                Ok(())
+            }
+            "move" => {
+                let direction = match rest {
+                    [Token::Word(direction)] => match direction.as_str() {
+                        "north" => Direction::North,
+                        "south" => Direction::South,
+                        "east" => Direction::East,
+                        "west" => Direction::West,
+                        "random" => Direction::Random,
+                        other => return Err(format!("unknown direction '{}'", other)),
+                    },
+                    _ => return Err("expected 'move north|south|east|west|random'".to_string()),
+                };
+                self.emit(line, Instruction::Command(Command::Move(direction)));
+                Ok(())
+            }
+            "goto" => {
+                let mut parser = ExpressionParser::new(rest);
+                let x = parser.or()?;
+                parser.expect(&Token::Symbol(","))?;
+                let y = parser.or()?;
+                parser.finish()?;
+                self.emit(line, Instruction::Command(Command::Goto(x, y)));
+                Ok(())
+            }
+            "log" => {
+                let message = parse_expression(rest)?;
+                self.emit(line, Instruction::Log(message));
+                Ok(())
+            }
+            "harvest" | "report" | "home" | "wait" => {
+                expect_end(rest)?;
+                let command = match first.as_str() {
+                    "harvest" => Command::Harvest,
+                    "report" => Command::Report,
+                    "home" => Command::Home,
+                    _ => Command::Wait,
+                };
+                self.emit(line, Instruction::Command(command));
+                Ok(())
+            }
+            name => match rest {
+                [Token::Symbol("="), value @ ..] => self.assign(line, name, value),
+                _ => Err(format!("unknown instruction '{}'", name)),
+            },
+        }
+    }
+
+    fn assign(&mut self, line: usize, name: &str, value: &[Token]) -> Result<(), String> {
+        if KEYWORDS.contains(&name) {
+            return Err(format!("'{}' is a reserved word", name));
+        }
+        let value = parse_expression(value)?;
+        self.emit(line, Instruction::Assign(name.to_string(), value));
+        Ok(())
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+fn expect_end(tokens: &[Token]) -> Result<(), String> {
+    match tokens.first() {
+        Some(token) => Err(format!("unexpected '{}'", token)),
+        None => Ok(()),
+    }
+}
+
+fn parse_expression(tokens: &[Token]) -> Result<Expr, String> {
+    let mut parser = ExpressionParser::new(tokens);
+    let expression = parser.or()?;
+    parser.finish()?;
+    Ok(expression)
+}
+
+// Descente récursive, de la priorité la plus faible (or) à la plus forte (valeurs)
+struct ExpressionParser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        ExpressionParser {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), String> {
+        match self.accept(token) {
+            true => Ok(()),
+            false => Err(format!("expected '{}'", token)),
+        }
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        expect_end(&self.tokens[self.position.min(self.tokens.len())..])
+    }
+
+    fn accept_operator(&mut self, operators: &[(Token, Operator)]) -> Option<Operator> {
+        let found = operators
+            .iter()
+            .find(|(token, _)| self.peek() == Some(token))
+            .map(|(_, operator)| *operator);
+        if found.is_some() {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn binary_level(
+        &mut self,
+        operators: &[(Token, Operator)],
+        operand: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut left = operand(self)?;
+        while let Some(operator) = self.accept_operator(operators) {
+            let right = operand(self)?;
+            left = Expr::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[(Token::Word("or".to_string()), Operator::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[(Token::Word("and".to_string()), Operator::And)], Self::not)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.accept(&Token::Word("not".to_string())) {
+            return Ok(Expr::Unary(Operator::Not, Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.sum()?;
+        let operators = [
+            (Token::Symbol("=="), Operator::Eq),
+            (Token::Symbol("!="), Operator::Ne),
+            (Token::Symbol("<="), Operator::Le),
+            (Token::Symbol(">="), Operator::Ge),
+            (Token::Symbol("<"), Operator::Lt),
+            (Token::Symbol(">"), Operator::Gt),
+        ];
+        match self.accept_operator(&operators) {
+            Some(operator) => Ok(Expr::Binary(operator, Box::new(left), Box::new(self.sum()?))),
+            None => Ok(left),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        self.binary_level(
+            &[
+                (Token::Symbol("+"), Operator::Add),
+                (Token::Symbol("-"), Operator::Sub),
+            ],
+            Self::product,
+        )
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        self.binary_level(
+            &[
+                (Token::Symbol("*"), Operator::Mul),
+                (Token::Symbol("/"), Operator::Div),
+                (Token::Symbol("%"), Operator::Rem),
+            ],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.accept(&Token::Symbol("-")) {
+            return Ok(Expr::Unary(Operator::Neg, Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Literal(Value::Number(*number))),
+            Some(Token::Text(text)) => Ok(Expr::Literal(Value::Text(text.clone()))),
+            Some(Token::Symbol("(")) => {
+                let expression = self.or()?;
+                self.expect(&Token::Symbol(")"))?;
+                Ok(expression)
+            }
+            Some(Token::Word(word)) if word == "true" || word == "false" => {
+                Ok(Expr::Literal(Value::Bool(word == "true")))
+            }
+            Some(Token::Word(word)) if self.accept(&Token::Symbol("(")) => self.query(word),
+            Some(Token::Word(word)) if KEYWORDS.contains(&word.as_str()) => {
+                Err(format!("unexpected '{}'", word))
+            }
+            Some(Token::Word(word)) => Ok(Expr::Variable(word.clone())),
+            Some(token) => Err(format!("unexpected '{}'", token)),
+            None => Err("expression is incomplete".to_string()),
+        }
+    }
+
+    fn query(&mut self, name: &str) -> Result<Expr, String> {
+        let Some((_, arity)) = QUERIES.iter().find(|(query, _)| *query == name) else {
+            return Err(format!("unknown query '{}'", name));
+        };
+
+        let mut arguments = Vec::new();
+        if !self.accept(&Token::Symbol(")")) {
+            loop {
+                arguments.push(self.or()?);
+                if self.accept(&Token::Symbol(")")) {
+                    break;
+                }
+                self.expect(&Token::Symbol(","))?;
+            }
+        }
+
+        if arguments.len() != *arity {
+            return Err(format!(
+                "'{}' expects {} argument(s), got {}",
+                name,
+                arity,
+                arguments.len()
+            ));
+        }
+        Ok(Expr::Query(name.to_string(), arguments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: i64) -> Box<Expr> {
+        Box::new(Expr::Literal(Value::Number(value)))
+    }
+
+    #[test]
+    fn products_bind_tighter_than_sums() {
+        let program = Program::parse("x = 1 + 2 * 3").unwrap();
+        let expected = Expr::Binary(
+            Operator::Add,
+            number(1),
+            Box::new(Expr::Binary(Operator::Mul, number(2), number(3))),
+        );
+        assert_eq!(program.instructions, vec![Instruction::Assign("x".to_string(), expected)]);
+    }
+
+    #[test]
+    fn if_else_jumps_over_the_other_branch() {
+        let program = Program::parse("if at_base()\nreport\nelse\nhome\nend").unwrap();
+        let condition = Expr::Query("at_base".to_string(), Vec::new());
+        assert_eq!(
+            program.instructions,
+            vec![
+                Instruction::JumpUnless(condition, 3),
+                Instruction::Command(Command::Report),
+                Instruction::Jump(4),
+                Instruction::Command(Command::Home),
+            ]
+        );
+        assert_eq!(program.lines, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn while_loops_back_to_its_condition() {
+        let program = Program::parse("while not cargo_full()\nharvest\nend").unwrap();
+        assert_eq!(program.instructions.len(), 3);
+        assert!(matches!(program.instructions[0], Instruction::JumpUnless(_, 3)));
+        assert_eq!(program.instructions[2], Instruction::Jump(0));
+    }
+
+    #[test]
+    fn reports_the_line_of_each_error() {
+        assert_eq!(
+            Program::parse("wait\nif true\nwait").unwrap_err(),
+            "line 2: block is never closed with 'end'"
+        );
+        assert_eq!(Program::parse("wait\nend").unwrap_err(), "line 2: 'end' without an open block");
+        assert_eq!(Program::parse("move up").unwrap_err(), "line 1: unknown direction 'up'");
+        assert_eq!(Program::parse("let while = 1").unwrap_err(), "line 1: 'while' is a reserved word");
+    }
+
+    #[test]
+    fn checks_queries_and_their_arguments() {
+        assert_eq!(Program::parse("x = fuel()").unwrap_err(), "line 1: unknown query 'fuel'");
+        assert_eq!(
+            Program::parse("x = random()").unwrap_err(),
+            "line 1: 'random' expects 1 argument(s), got 0"
+        );
+        assert!(Program::parse("goto resource_x(), resource_y()").is_ok());
+        assert_eq!(Program::parse("x = (1 + 2").unwrap_err(), "line 1: expected ')'");
+    }
+}
@@ -0,0 +1,354 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::cargo::{CargoConfig, CargoHold};
+use super::perception::{Action, Perception, Route};
+use super::registry::{no_order, RobotConfig, RobotKind, RobotRole, Spawn};
+use super::robot::{ResourceTarget, Robot, RobotState, RobotType};
+use super::script::interpreter::{Flow, Machine, Order, ScriptHost};
+use super::script::parser::{Direction, Program, Value};
+use super::sensor::{LocalKnowledge, SensorSpec};
+use super::upgrades::UpgradeLevels;
+use crate::environment::tile::{MapTile, Resource, TileType};
+use crate::simulation::simulation::Simulation;
+
+// Comme les arbres de comportement, le script est relu à chaque envoi
+const SCRIPT_FILE: &str = "assets/scripts/robot.bot";
+const BUILTIN_SCRIPT: &str = include_str!("../../assets/scripts/robot.bot");
+
+pub fn script_kind() -> RobotKind {
+    RobotKind {
+        robot_type: RobotType::SCRIPT,
+        glyph: '🦾',
+        role: RobotRole::Scripted,
+        manual_dispatch: true,
+        config: RobotConfig {
+            energy_cost: 4,
+            mineral_cost: 1,
+            build_ticks: 3,
+            sensor: SensorSpec {
+                radius: 2,
+                ..SensorSpec::default()
+            },
+            speed: 1.0,
+            battery_capacity: Some(100),
+            cargo: Some(CargoConfig::default()),
+            breaks_down: true,
+            modules: &[],
+        },
+        behaviour: None,
+        constructor: |spawn| Box::new(ScriptRobot::new(spawn)),
+        on_arrival,
+        on_abandon: no_order,
+        resumes_order: true,
+    }
+}
+
+fn on_arrival(simulation: &mut Simulation, robot: &mut Box<dyn Robot + Send>) {
+    simulation.deliver_cargo(robot.unload_cargo());
+    if let Some(target) = robot.get_current_resource() {
+        simulation.report_deposit(target.x, target.y, target.resource, (robot.get_type(), robot.get_id()));
+    }
+    simulation.dock(robot);
+}
+
+fn load_script() -> Result<Program, String> {
+    let source = match fs::read_to_string(Path::new(SCRIPT_FILE)) {
+        Ok(source) => source,
+        Err(error) if error.kind() == ErrorKind::NotFound => BUILTIN_SCRIPT.to_string(),
+        Err(error) => return Err(error.to_string()),
+    };
+    Program::parse(&source)
+}
+
+pub struct ScriptRobot {
+    id: usize,
+    x: usize,
+    y: usize,
+    state: RobotState,
+    config: CargoConfig,
+    cargo: CargoHold,
+    knowledge: LocalKnowledge,
+    target: Option<ResourceTarget>,
+    program: Arc<Program>,
+    machine: Machine,
+    blocked: bool,
+    intent: Option<Action>,
+    messages: Vec<String>,
+    notes: Vec<String>,
+}
+
+impl Robot for ScriptRobot {
+    fn get_type(&self) -> RobotType {
+        RobotType::SCRIPT
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    fn get_state(&self) -> RobotState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: RobotState) {
+        self.state = state;
+    }
+
+    fn set_position(&mut self, x: usize, y: usize) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn update(&mut self, perception: &Perception) -> Action {
+        self.knowledge.observe(&perception.tiles);
+        match self.state {
+            RobotState::Exploring => self.run_script(perception),
+            RobotState::ReturningToBase => self.return_to_base(perception),
+            _ => Action::Wait,
+        }
+    }
+
+    fn knowledge(&self) -> &LocalKnowledge {
+        &self.knowledge
+    }
+
+    fn knowledge_mut(&mut self) -> &mut LocalKnowledge {
+        &mut self.knowledge
+    }
+
+    fn get_current_resource(&self) -> Option<ResourceTarget> {
+        self.target
+    }
+
+    fn unload_cargo(&mut self) -> Vec<Resource> {
+        self.cargo.unload()
+    }
+
+    fn free_cargo(&self) -> u32 {
+        self.cargo.free_weight()
+    }
+
+    fn load_from(&mut self, (x, y): (usize, usize), deposit: Resource) -> u32 {
+        let loaded = self.cargo.load(deposit.resource_type, deposit.scale);
+        let tile = match deposit.scale - loaded {
+            0 => TileType::Empty,
+            remaining => TileType::Resource(Resource::new(remaining, deposit.resource_type)),
+        };
+        self.knowledge.observe(&[MapTile::new(x, y, tile)]);
+        loaded
+    }
+
+    fn apply_upgrades(&mut self, levels: &UpgradeLevels) {
+        self.cargo
+            .set_weight_limit(self.config.weight_limit + levels.cargo_bonus());
+    }
+
+    fn take_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+
+    fn note(&mut self, reason: String) {
+        self.notes.push(reason);
+    }
+
+    fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+
+    fn running_node(&self) -> Option<String> {
+        match self.state {
+            RobotState::Exploring => self
+                .machine
+                .current_line(&self.program)
+                .map(|line| format!("line {}", line)),
+            _ => None,
+        }
+    }
+
+    // Le script est relu et repris depuis le début à chaque mission
+    fn deploy(&mut self) {
+        self.target = None;
+        self.knowledge = LocalKnowledge::new();
+        self.start();
+    }
+}
+
+impl ScriptRobot {
+    pub fn new(spawn: &Spawn) -> Self {
+        let config = spawn.config.cargo.unwrap_or_default();
+        let mut robot = ScriptRobot {
+            id: spawn.id,
+            x: spawn.position.0,
+            y: spawn.position.1,
+            state: RobotState::Exploring,
+            config,
+            cargo: CargoHold::new(config.weight_limit),
+            knowledge: LocalKnowledge::new(),
+            target: None,
+            program: Arc::new(Program::default()),
+            machine: Machine::default(),
+            blocked: false,
+            intent: None,
+            messages: Vec::new(),
+            notes: Vec::new(),
+        };
+        robot.start();
+        robot
+    }
+
+    // Un script invalide est signalé et le robot ne quitte pas la base
+    fn start(&mut self) {
+        self.machine = Machine::default();
+        self.blocked = false;
+        match load_script() {
+            Ok(program) => {
+                self.program = Arc::new(program);
+                self.state = RobotState::Exploring;
+            }
+            Err(error) => {
+                self.log(format!("script error: {}", error));
+                self.state = RobotState::ReturningToBase;
+            }
+        }
+    }
+
+    fn run_script(&mut self, perception: &Perception) -> Action {
+        let program = Arc::clone(&self.program);
+        let mut machine = std::mem::take(&mut self.machine);
+        let flow = machine.run(&program, self, perception);
+        self.machine = machine;
+
+        match flow {
+            Ok(Flow::Yield) => {}
+            Ok(Flow::Finished) => self.set_state(RobotState::ReturningToBase),
+            Err(error) => {
+                self.log(format!("script error: {}", error));
+                self.set_state(RobotState::ReturningToBase);
+            }
+        }
+        self.intent.take().unwrap_or(Action::Wait)
+    }
+
+    // Bloqué d'après ce que le robot perçoit ; la simulation peut encore refuser le pas
+    fn step(&mut self, x: usize, y: usize, perception: &Perception) {
+        self.blocked = !perception.passable(&self.knowledge, x, y);
+        if !self.blocked {
+            self.intent = Some(Action::Move(x, y));
+        }
+    }
+
+    // Gisement connu sur la tuile du robot ou sur une tuile voisine
+    fn adjacent_resource(&self) -> Option<(usize, usize)> {
+        self.knowledge
+            .resources()
+            .into_iter()
+            .map(|(x, y, _)| (x, y))
+            .find(|(x, y)| self.x.abs_diff(*x) + self.y.abs_diff(*y) <= 1)
+    }
+}
+
+impl ScriptHost for ScriptRobot {
+    fn query(&mut self, name: &str, arguments: &[i64], perception: &Perception) -> Value {
+        let nearest = self.knowledge.nearest_resource((self.x, self.y));
+        let (base_x, base_y) = perception.base;
+        match name {
+            "x" => Value::Number(self.x as i64),
+            "y" => Value::Number(self.y as i64),
+            "base_x" => Value::Number(base_x as i64),
+            "base_y" => Value::Number(base_y as i64),
+            "at_base" => Value::Bool(self.x.abs_diff(base_x) + self.y.abs_diff(base_y) <= 1),
+            "resource_seen" => Value::Bool(nearest.is_some()),
+            "resource_x" => Value::Number(nearest.map_or(-1, |(x, _, _)| x as i64)),
+            "resource_y" => Value::Number(nearest.map_or(-1, |(_, y, _)| y as i64)),
+            "cargo" => Value::Number(self.cargo.weight() as i64),
+            "cargo_full" => Value::Bool(self.cargo.is_full()),
+            "blocked" => Value::Bool(self.blocked),
+            "random" => match arguments.first() {
+                Some(limit) if *limit > 0 => Value::Number(perception.rng().random_range(0..*limit)),
+                _ => Value::Number(0),
+            },
+            // Les noms sont vérifiés à la lecture du script
+            _ => unreachable!("unknown query '{}'", name),
+        }
+    }
+
+    fn execute(&mut self, order: Order, perception: &Perception) -> bool {
+        match order {
+            Order::Move(direction) => {
+                let direction = match direction {
+                    Direction::Random => [
+                        Direction::North,
+                        Direction::South,
+                        Direction::East,
+                        Direction::West,
+                    ][perception.rng().random_range(0..4)],
+                    direction => direction,
+                };
+                let (x, y) = (self.x as isize, self.y as isize);
+                let (x, y) = match direction {
+                    Direction::North => (x, y - 1),
+                    Direction::South => (x, y + 1),
+                    Direction::East => (x + 1, y),
+                    _ => (x - 1, y),
+                };
+                match x >= 0 && y >= 0 {
+                    true => self.step(x as usize, y as usize, perception),
+                    false => self.blocked = true,
+                }
+                true
+            }
+            Order::Goto(x, y) => {
+                let x = x.clamp(0, perception.width as i64 - 1) as usize;
+                let y = y.clamp(0, perception.height as i64 - 1) as usize;
+                match self.calculate_next_step(x, y, perception) {
+                    Route::Step(next_x, next_y) => {
+                        self.step(next_x, next_y, perception);
+                        self.blocked || (next_x, next_y) == (x, y)
+                    }
+                    Route::Arrived => true,
+                    // Le script lit `blocked` pour décider d'un autre ordre
+                    Route::Unreachable => {
+                        self.blocked = true;
+                        true
+                    }
+                }
+            }
+            Order::Harvest => {
+                self.blocked = match self.adjacent_resource() {
+                    Some((x, y)) => {
+                        self.intent = Some(Action::Harvest(x, y));
+                        false
+                    }
+                    None => true,
+                };
+                true
+            }
+            Order::Report => {
+                if let Some((x, y, resource)) = self.knowledge.nearest_resource((self.x, self.y)) {
+                    self.target = Some(ResourceTarget::new(x, y, resource));
+                }
+                self.set_state(RobotState::ReturningToBase);
+                true
+            }
+            Order::Home => {
+                self.set_state(RobotState::ReturningToBase);
+                true
+            }
+            Order::Wait => true,
+        }
+    }
+
+    fn log(&mut self, message: String) {
+        self.messages
+            .push(format!("{} {}: {}", RobotType::SCRIPT, self.id, message));
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::environment::{
+    map::Map,
+    tile::{MapTile, Resource, TileType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorSpec {
+    pub radius: usize,
+    pub resource_detection: f64,
+    pub terrain_detection: f64,
+    pub robot_detection: f64,
+    pub structure_detection: f64,
+}
+
+impl Default for SensorSpec {
+    fn default() -> Self {
+        SensorSpec {
+            radius: 1,
+            resource_detection: 1.0,
+            terrain_detection: 1.0,
+            robot_detection: 1.0,
+            structure_detection: 1.0,
+        }
+    }
+}
+
+impl SensorSpec {
+    pub fn detection_probability(&self, tile: &TileType) -> f64 {
+        match tile {
+            TileType::Empty | TileType::Base => 1.0,
+            TileType::Terrain => self.terrain_detection,
+            TileType::Resource(_) => self.resource_detection,
+            TileType::Robot(_) | TileType::BrokenRobot(_) => self.robot_detection,
+            TileType::Structure(_) => self.structure_detection,
+        }
+    }
+
+    // Tuiles dans le rayon du capteur et visibles depuis la position (le relief bloque la vue)
+    pub fn footprint(&self, from: (usize, usize), map: &Map) -> Vec<(usize, usize)> {
+        let (from_x, from_y) = from;
+        let radius = self.radius;
+        let min_x = from_x.saturating_sub(radius);
+        let min_y = from_y.saturating_sub(radius);
+        let max_x = (from_x + radius).min(map.width - 1);
+        let max_y = (from_y + radius).min(map.height - 1);
+
+        let mut tiles = Vec::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let dx = from_x.abs_diff(x);
+                let dy = from_y.abs_diff(y);
+                if dx * dx + dy * dy <= radius * radius && line_of_sight(from, (x, y), map) {
+                    tiles.push((x, y));
+                }
+            }
+        }
+        tiles
+    }
+
+    pub fn sense<R: Rng>(&self, from: (usize, usize), map: &Map, rng: &mut R) -> Vec<MapTile> {
+        self.footprint(from, map)
+            .into_iter()
+            .map(|(x, y)| map.get(x, y))
+            .filter(|tile| {
+                (tile.x, tile.y) == from
+                    || rng.random_bool(self.detection_probability(&tile.tile).clamp(0.0, 1.0))
+            })
+            .collect()
+    }
+}
+
+// Tracé de Bresenham : seules les tuiles intermédiaires peuvent masquer la cible
+fn line_of_sight(from: (usize, usize), to: (usize, usize), map: &Map) -> bool {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (to_x, to_y) = (to.0 as isize, to.1 as isize);
+    let dx = (to_x - x).abs();
+    let dy = -(to_y - y).abs();
+    let step_x = if x < to_x { 1 } else { -1 };
+    let step_y = if y < to_y { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        if x == to_x && y == to_y {
+            return true;
+        }
+        if (x, y) != (from.0 as isize, from.1 as isize)
+            && map.get(x as usize, y as usize).tile == TileType::Terrain
+        {
+            return false;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LocalKnowledge {
+    tiles: HashMap<(usize, usize), TileType>,
+}
+
+impl LocalKnowledge {
+    pub fn new() -> Self {
+        LocalKnowledge {
+            tiles: HashMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, tiles: &[MapTile]) {
+        for tile in tiles {
+            self.tiles.insert((tile.x, tile.y), tile.tile);
+        }
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> Option<TileType> {
+        self.tiles.get(&(x, y)).copied()
+    }
+
+    // Oublie le terrain et les robots mémorisés, qui ont pu changer ; seuls les gisements restent
+    pub fn forget_obstacles(&mut self) {
+        self.tiles.retain(|_, tile| matches!(tile, TileType::Resource(_)));
+    }
+
+    pub fn resources(&self) -> Vec<(usize, usize, Resource)> {
+        let mut resources: Vec<(usize, usize, Resource)> = self
+            .tiles
+            .iter()
+            .filter_map(|(&(x, y), tile)| match tile {
+                TileType::Resource(resource) => Some((x, y, *resource)),
+                _ => None,
+            })
+            .collect();
+        resources.sort_by_key(|&(x, y, _)| (y, x));
+        resources
+    }
+
+    pub fn nearest_resource(&self, from: (usize, usize)) -> Option<(usize, usize, Resource)> {
+        self.resources()
+            .into_iter()
+            .min_by_key(|&(x, y, _)| from.0.abs_diff(x) + from.1.abs_diff(y))
+    }
+}
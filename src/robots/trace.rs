@@ -0,0 +1,53 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::robot::RobotState;
+
+// Nombre de décisions conservées par robot ; les plus anciennes sont oubliées
+pub const TRACE_CAPACITY: usize = 40;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub tick: u64,
+    pub state: RobotState,
+    pub position: (usize, usize),
+    // Ce que le robot percevait au moment de décider
+    pub inputs: String,
+    pub action: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {:?} at ({}, {}) | {} -> {}: {}",
+            self.tick,
+            self.state,
+            self.position.0,
+            self.position.1,
+            self.inputs,
+            self.action,
+            self.reason
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DecisionTrace {
+    decisions: VecDeque<Decision>,
+}
+
+impl DecisionTrace {
+    pub fn record(&mut self, decision: Decision) {
+        if self.decisions.len() == TRACE_CAPACITY {
+            self.decisions.pop_front();
+        }
+        self.decisions.push_back(decision);
+    }
+
+    // Du plus récent au plus ancien
+    pub fn latest(&self, count: usize) -> Vec<Decision> {
+        self.decisions.iter().rev().take(count).cloned().collect()
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+use crate::environment::tile::ResourceType;
+
+pub const MAX_UPGRADE_LEVEL: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpgradeKind {
+    Speed,
+    Cargo,
+    Sensor,
+    Battery,
+}
+
+impl UpgradeKind {
+    pub const ALL: [UpgradeKind; 4] = [
+        UpgradeKind::Speed,
+        UpgradeKind::Cargo,
+        UpgradeKind::Sensor,
+        UpgradeKind::Battery,
+    ];
+
+    pub fn currency(&self) -> ResourceType {
+        match self {
+            UpgradeKind::Speed => ResourceType::Energy,
+            UpgradeKind::Cargo => ResourceType::Mineral,
+            UpgradeKind::Sensor => ResourceType::Mineral,
+            UpgradeKind::Battery => ResourceType::Energy,
+        }
+    }
+
+    // Prix du passage au niveau `level`
+    pub fn price(&self, level: u32) -> u32 {
+        let base = match self {
+            UpgradeKind::Speed => 10,
+            UpgradeKind::Cargo => 8,
+            UpgradeKind::Sensor => 6,
+            UpgradeKind::Battery => 12,
+        };
+        base * level
+    }
+}
+
+impl fmt::Display for UpgradeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UpgradeKind::Speed => "Speed",
+            UpgradeKind::Cargo => "Cargo",
+            UpgradeKind::Sensor => "Sensor",
+            UpgradeKind::Battery => "Battery",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UpgradeLevels {
+    pub speed: u32,
+    pub cargo: u32,
+    pub sensor: u32,
+    pub battery: u32,
+}
+
+impl UpgradeLevels {
+    pub fn get(&self, kind: UpgradeKind) -> u32 {
+        match kind {
+            UpgradeKind::Speed => self.speed,
+            UpgradeKind::Cargo => self.cargo,
+            UpgradeKind::Sensor => self.sensor,
+            UpgradeKind::Battery => self.battery,
+        }
+    }
+
+    pub fn next_level(&self, kind: UpgradeKind) -> Option<u32> {
+        let level = self.get(kind);
+        (level < MAX_UPGRADE_LEVEL).then_some(level + 1)
+    }
+
+    pub fn raise(&mut self, kind: UpgradeKind) {
+        let level = match kind {
+            UpgradeKind::Speed => &mut self.speed,
+            UpgradeKind::Cargo => &mut self.cargo,
+            UpgradeKind::Sensor => &mut self.sensor,
+            UpgradeKind::Battery => &mut self.battery,
+        };
+        *level = (*level + 1).min(MAX_UPGRADE_LEVEL);
+    }
+
+    // Multiplicateur de la vitesse de base du robot
+    pub fn speed_factor(&self) -> f32 {
+        1.0 + 0.5 * self.speed as f32
+    }
+
+    pub fn cargo_bonus(&self) -> u32 {
+        5 * self.cargo
+    }
+
+    pub fn sensor_bonus(&self) -> usize {
+        self.sensor as usize
+    }
+
+    pub fn battery_bonus(&self) -> u32 {
+        60 * self.battery
+    }
+}
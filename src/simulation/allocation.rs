@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::environment::tile::Resource;
+
+use super::radio::RobotKey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Open,
+    Awarded(RobotKey),
+    Done,
+    // Abandonnée par le chien de garde : aucun récolteur n'a pu l'atteindre
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HarvestTask {
+    pub position: (usize, usize),
+    pub resource: Resource,
+    pub status: TaskStatus,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bid {
+    pub bidder: RobotKey,
+    pub task: usize,
+    pub cost: u32,
+}
+
+// Nombre de récolteurs qui doivent renoncer à un gisement avant qu'il ne soit déclaré inaccessible
+pub const MAX_ATTEMPTS: usize = 3;
+
+// Contract-net : la base annonce les gisements, les récolteurs libres enchérissent
+// selon le coût de leur propre chemin et la tâche revient au moins disant
+#[derive(Debug, Clone, Default)]
+pub struct TaskAllocator {
+    tasks: Vec<HarvestTask>,
+    // Offres déposées par les récolteurs libres depuis la dernière enchère
+    bids: HashMap<RobotKey, Vec<Bid>>,
+    awards: HashMap<RobotKey, HarvestTask>,
+    // Robots ayant renoncé à chaque tâche, qui n'y enchérissent plus
+    failures: HashMap<usize, Vec<RobotKey>>,
+}
+
+impl TaskAllocator {
+    pub fn new() -> Self {
+        TaskAllocator::default()
+    }
+
+    // Un gisement déjà connu, même épuisé, ne redevient pas une tâche
+    pub fn add_task(&mut self, position: (usize, usize), resource: Resource) -> bool {
+        if self.tasks.iter().any(|task| task.position == position) {
+            return false;
+        }
+        self.tasks.push(HarvestTask {
+            position,
+            resource,
+            status: TaskStatus::Open,
+        });
+        true
+    }
+
+    // Tâches ouvertes aux enchères, avec la position de leur gisement
+    pub fn announced(&self) -> Vec<(usize, (usize, usize))> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.status == TaskStatus::Open)
+            .map(|(index, task)| (index, task.position))
+            .collect()
+    }
+
+    // Remplace les offres du robot par celles-ci, sous forme de (tâche, coût)
+    pub fn bid(&mut self, robot: RobotKey, offers: Vec<(usize, u32)>) {
+        let bids = offers
+            .into_iter()
+            .map(|(task, cost)| Bid {
+                bidder: robot,
+                task,
+                cost,
+            })
+            .collect();
+        self.bids.insert(robot, bids);
+    }
+
+    // Gisements attribués à d'autres robots, que celui-ci ne doit pas récolter en passant
+    pub fn awarded_to_others(&self, robot: RobotKey) -> Vec<(usize, usize)> {
+        self.tasks
+            .iter()
+            .filter(|task| matches!(task.status, TaskStatus::Awarded(winner) if winner != robot))
+            .map(|task| task.position)
+            .collect()
+    }
+
+    pub fn take_award(&mut self, robot: RobotKey) -> Option<HarvestTask> {
+        self.awards.remove(&robot)
+    }
+
+    // Remet en jeu la tâche d'un robot défaillant ; renvoie sa position s'il en avait une
+    pub fn release(&mut self, robot: RobotKey) -> Option<(usize, usize)> {
+        self.bids.remove(&robot);
+        self.awards.remove(&robot);
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|task| task.status == TaskStatus::Awarded(robot))?;
+        task.status = TaskStatus::Open;
+        Some(task.position)
+    }
+
+    pub fn complete(&mut self, position: (usize, usize)) {
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.position == position) {
+            task.status = TaskStatus::Done;
+        }
+    }
+
+    // Le robot renonce au gisement : la tâche est annoncée de nouveau aux autres récolteurs,
+    // jusqu'à ce que MAX_ATTEMPTS robots y aient renoncé. Renvoie vrai si elle est alors close.
+    pub fn abandon(&mut self, robot: RobotKey, position: (usize, usize)) -> bool {
+        self.release(robot);
+        let Some(index) = self.tasks.iter().position(|task| task.position == position) else {
+            return false;
+        };
+        let failures = self.failures.entry(index).or_default();
+        if !failures.contains(&robot) {
+            failures.push(robot);
+        }
+        if failures.len() < MAX_ATTEMPTS {
+            return false;
+        }
+        self.tasks[index].status = TaskStatus::Unreachable;
+        true
+    }
+
+    pub fn count(&self, matches: fn(&TaskStatus) -> bool) -> usize {
+        self.tasks.iter().filter(|task| matches(&task.status)).count()
+    }
+
+    // Attribue chaque tâche ouverte au robot le moins disant, un robot ne gagnant qu'une tâche ;
+    // les offres non retenues sont écartées, les perdants enchérissent de nouveau
+    pub fn auction(&mut self) -> Vec<Bid> {
+        let mut bids: Vec<Bid> = self
+            .bids
+            .drain()
+            .flat_map(|(_, bids)| bids)
+            .filter(|bid| {
+                self.tasks.get(bid.task).is_some_and(|task| task.status == TaskStatus::Open)
+                    && !self
+                        .failures
+                        .get(&bid.task)
+                        .is_some_and(|failures| failures.contains(&bid.bidder))
+            })
+            .collect();
+        // Départage stable pour que deux enchères identiques donnent le même résultat
+        bids.sort_by_key(|bid| (bid.cost, bid.task, bid.bidder.0.name(), bid.bidder.1));
+
+        let mut awarded: Vec<Bid> = Vec::new();
+        for bid in bids {
+            if self.tasks[bid.task].status != TaskStatus::Open
+                || awarded.iter().any(|winner| winner.bidder == bid.bidder)
+            {
+                continue;
+            }
+            self.tasks[bid.task].status = TaskStatus::Awarded(bid.bidder);
+            self.awards.insert(bid.bidder, self.tasks[bid.task]);
+            awarded.push(bid);
+        }
+        awarded
+    }
+
+    pub fn task(&self, index: usize) -> HarvestTask {
+        self.tasks[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::tile::ResourceType;
+    use crate::robots::robot::RobotType;
+
+    const NEAR: RobotKey = (RobotType::HARVESTER, 1);
+    const FAR: RobotKey = (RobotType::HARVESTER, 2);
+
+    fn allocator(deposits: &[(usize, usize)]) -> TaskAllocator {
+        let mut allocator = TaskAllocator::new();
+        for position in deposits {
+            allocator.add_task(*position, Resource::new(10, ResourceType::Mineral));
+        }
+        allocator
+    }
+
+    fn status(allocator: &TaskAllocator, index: usize) -> TaskStatus {
+        allocator.task(index).status
+    }
+
+    #[test]
+    fn each_task_goes_to_the_lowest_bid() {
+        let mut allocator = allocator(&[(5, 5), (9, 9)]);
+        allocator.bid(FAR, vec![(0, 12), (1, 3)]);
+        allocator.bid(NEAR, vec![(0, 4), (1, 2)]);
+
+        // NEAR gagne la tâche 1, la moins chère ; FAR se rabat sur la tâche 0
+        let awarded = allocator.auction();
+        assert_eq!(awarded.len(), 2);
+        assert_eq!(status(&allocator, 1), TaskStatus::Awarded(NEAR));
+        assert_eq!(status(&allocator, 0), TaskStatus::Awarded(FAR));
+        assert_eq!(allocator.take_award(NEAR).map(|task| task.position), Some((9, 9)));
+        assert!(allocator.announced().is_empty());
+    }
+
+    #[test]
+    fn a_robot_without_a_bid_wins_nothing() {
+        // Sans chemin connu vers le gisement, le robot n'enchérit pas
+        let mut allocator = allocator(&[(5, 5)]);
+        allocator.bid(NEAR, Vec::new());
+        assert!(allocator.auction().is_empty());
+        assert_eq!(allocator.announced(), vec![(0, (5, 5))]);
+
+        // Les offres sont consommées par l'enchère : il faut enchérir de nouveau
+        allocator.bid(NEAR, vec![(0, 7)]);
+        assert_eq!(allocator.auction().len(), 1);
+        allocator.release(NEAR);
+        assert!(allocator.auction().is_empty());
+    }
+
+    #[test]
+    fn a_completed_task_is_no_longer_announced() {
+        let mut allocator = allocator(&[(5, 5)]);
+        allocator.bid(NEAR, vec![(0, 4)]);
+        allocator.auction();
+        allocator.complete((5, 5));
+
+        assert_eq!(status(&allocator, 0), TaskStatus::Done);
+        allocator.bid(FAR, vec![(0, 1)]);
+        assert!(allocator.auction().is_empty());
+        // Un gisement déjà suivi ne redevient pas une tâche
+        assert!(!allocator.add_task((5, 5), Resource::new(10, ResourceType::Mineral)));
+    }
+
+    #[test]
+    fn a_released_task_is_announced_again() {
+        let mut allocator = allocator(&[(5, 5)]);
+        allocator.bid(NEAR, vec![(0, 4)]);
+        allocator.auction();
+
+        assert_eq!(allocator.release(NEAR), Some((5, 5)));
+        assert_eq!(status(&allocator, 0), TaskStatus::Open);
+        assert_eq!(allocator.take_award(NEAR).map(|task| task.position), None);
+        assert_eq!(allocator.release(NEAR), None);
+
+        allocator.bid(FAR, vec![(0, 9)]);
+        assert_eq!(allocator.auction()[0].bidder, FAR);
+    }
+
+    #[test]
+    fn an_abandoned_task_goes_to_another_robot_until_too_many_give_up() {
+        let mut allocator = allocator(&[(5, 5)]);
+        allocator.bid(NEAR, vec![(0, 4)]);
+        allocator.auction();
+
+        // Le robot qui a renoncé n'est plus retenu, même moins disant
+        assert!(!allocator.abandon(NEAR, (5, 5)));
+        assert_eq!(status(&allocator, 0), TaskStatus::Open);
+        allocator.bid(NEAR, vec![(0, 1)]);
+        allocator.bid(FAR, vec![(0, 9)]);
+        assert_eq!(allocator.auction()[0].bidder, FAR);
+
+        // Un même robot ne compte qu'une fois parmi ceux qui ont renoncé
+        assert!(!allocator.abandon(FAR, (5, 5)));
+        assert!(!allocator.abandon(FAR, (5, 5)));
+        assert_eq!(status(&allocator, 0), TaskStatus::Open);
+
+        assert!(allocator.abandon((RobotType::HARVESTER, 3), (5, 5)));
+        assert_eq!(status(&allocator, 0), TaskStatus::Unreachable);
+        assert!(allocator.announced().is_empty());
+    }
+}
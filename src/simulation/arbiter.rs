@@ -0,0 +1,554 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::environment::{
+    map::Map,
+    structure::StructureType,
+    tile::{MapTile, Resource, TileType},
+};
+use crate::robots::perception::Action;
+
+use super::radio::RobotKey;
+
+// Tours de résolution par tick, chacun couvrant une tranche égale du tick : un pas est daté au tour près.
+// Assez fin pour le robot le plus rapide (éclaireur amélioré au maximum, sur une route).
+pub const ROUNDS_PER_TICK: usize = 16;
+// Demandes comptées pour un robot qui a fini son tick
+const DONE: usize = usize::MAX;
+
+// Priorités des demandes, de la plus faible à la plus forte
+pub const PRIORITY_DEFAULT: u8 = 0;
+pub const PRIORITY_RETURNING: u8 = 1;
+pub const PRIORITY_OPERATOR: u8 = 2;
+
+// Demande d'un robot qui modifie la carte : déplacement, récolte ou construction
+#[derive(Debug, Clone, Copy)]
+pub struct Claim {
+    pub robot: RobotKey,
+    pub priority: u8,
+    pub position: (usize, usize),
+    pub action: Action,
+    // Poids encore libre dans la soute, pour partager les dernières unités d'un gisement
+    pub free_weight: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Moved(usize, usize),
+    // Gisement tel qu'il était au tour du robot
+    Harvested((usize, usize), Resource),
+    Built(StructureType, usize, usize),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Moved(x, y) => write!(f, "moved to ({}, {})", x, y),
+            Outcome::Harvested((x, y), _) => write!(f, "harvested ({}, {})", x, y),
+            Outcome::Built(kind, x, y) => write!(f, "completed {} at ({}, {})", kind, x, y),
+        }
+    }
+}
+
+pub type Verdict = Result<Outcome, String>;
+
+// Hasard d'un robot pendant un tick : deux parties lancées avec la même graine tirent les mêmes nombres
+pub fn robot_rng(seed: u32, tick: u64, robot: RobotKey) -> StdRng {
+    let type_hash = robot
+        .0
+        .name()
+        .bytes()
+        .fold(0u64, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as u64));
+    let mixed = [tick, type_hash, robot.1 as u64]
+        .into_iter()
+        .fold(seed as u64, |hash, value| hash.wrapping_mul(0x9E37_79B9_7F4A_7C15).wrapping_add(value));
+    StdRng::seed_from_u64(mixed)
+}
+
+// Règles de résolution d'un tour, dans l'ordre :
+// 1. le robot piloté par l'opérateur passe en premier, puis ceux qui rentrent à la base, puis les autres ;
+// 2. à priorité égale, l'ordre est tiré au sort à partir de la graine de la carte et du numéro de tour,
+//    sans dépendre de l'ordre d'arrivée des demandes : personne n'est toujours servi le premier ;
+// 3. les demandes sont appliquées une à une dans cet ordre, si bien qu'une case occupée ou libérée,
+//    ou un gisement entamé, l'est déjà pour les demandes suivantes.
+pub fn resolve(claims: &[Claim], map: &mut Map, seed: u32, round: u64) -> Vec<(RobotKey, Verdict)> {
+    let mut ordered = claims.to_vec();
+    ordered.sort_by_key(|claim| (claim.robot.0.name(), claim.robot.1));
+    ordered.shuffle(&mut StdRng::seed_from_u64(((seed as u64) << 32) ^ round));
+    // Tri stable : le tirage au sort départage les égalités
+    ordered.sort_by_key(|claim| Reverse(claim.priority));
+
+    ordered
+        .into_iter()
+        .map(|claim| (claim.robot, apply(&claim, map)))
+        .collect()
+}
+
+fn apply(claim: &Claim, map: &mut Map) -> Verdict {
+    let (x, y) = claim.position;
+    let within = |target_x: usize, target_y: usize| x.abs_diff(target_x) + y.abs_diff(target_y) <= 1;
+    match claim.action {
+        Action::Move(next_x, next_y) => {
+            if !within(next_x, next_y) || (next_x, next_y) == (x, y) {
+                return Err(format!("({}, {}) is not a neighbour", next_x, next_y));
+            }
+            if !map.is_valid(next_x, next_y) {
+                return Err(format!("({}, {}) is blocked", next_x, next_y));
+            }
+            map.vacate(x, y);
+            map.set(MapTile::new(next_x, next_y, TileType::Robot(claim.robot.0)));
+            Ok(Outcome::Moved(next_x, next_y))
+        }
+        Action::Harvest(deposit_x, deposit_y) => {
+            if !within(deposit_x, deposit_y) {
+                return Err(format!("({}, {}) is out of reach", deposit_x, deposit_y));
+            }
+            let TileType::Resource(deposit) = map.get(deposit_x, deposit_y).tile else {
+                return Err(format!("no deposit at ({}, {})", deposit_x, deposit_y));
+            };
+            let loaded = deposit
+                .scale
+                .min(claim.free_weight / deposit.resource_type.unit_weight());
+            if loaded == 0 {
+                return Err(format!("nothing loaded from ({}, {})", deposit_x, deposit_y));
+            }
+            let tile = match deposit.scale - loaded {
+                0 => TileType::Empty,
+                remaining => TileType::Resource(Resource::new(remaining, deposit.resource_type)),
+            };
+            map.set(MapTile::new(deposit_x, deposit_y, tile));
+            Ok(Outcome::Harvested((deposit_x, deposit_y), deposit))
+        }
+        Action::Build(site_x, site_y) => {
+            if !within(site_x, site_y) {
+                return Err(format!("({}, {}) is out of reach", site_x, site_y));
+            }
+            match map.complete_structure((site_x, site_y)) {
+                Some(structure) => Ok(Outcome::Built(structure.kind, site_x, site_y)),
+                None => Err(format!("no construction site at ({}, {})", site_x, site_y)),
+            }
+        }
+        action => Err(format!("{} needs no arbitration", action)),
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rounds {
+    tick: u64,
+    // Vrai entre le début du tick et la fin du dernier robot ; la base travaille seule le reste du temps
+    open: bool,
+    round: usize,
+    // Demandes déposées par chaque robot inscrit depuis le début du tick, DONE une fois son tick fini
+    submitted: HashMap<RobotKey, usize>,
+    claims: Vec<Claim>,
+    verdicts: HashMap<RobotKey, Verdict>,
+}
+
+impl Rounds {
+    fn pending(&self) -> impl Iterator<Item = RobotKey> + '_ {
+        self.submitted
+            .iter()
+            .filter(|(_, count)| **count <= self.round)
+            .map(|(robot, _)| *robot)
+    }
+
+    // Un seul robot agit à la fois, dans l'ordre des clés : le premier qui doit encore jouer ce tour
+    fn has_turn(&self, robot: RobotKey) -> bool {
+        self.open && self.pending().min_by_key(|(robot_type, id)| (robot_type.name(), *id)) == Some(robot)
+    }
+}
+
+// Point de rendez-vous entre les threads des robots et la base :
+// les robots jouent chacun leur tour et déposent leurs demandes, la base les départage tour par tour.
+// Chaque robot inscrit est attendu, si bien qu'une partie rejouée avec la même graine se déroule à l'identique.
+#[derive(Debug, Default)]
+pub struct Arbiter {
+    rounds: Mutex<Rounds>,
+    changed: Condvar,
+}
+
+impl Arbiter {
+    pub fn new() -> Self {
+        Arbiter::default()
+    }
+
+    // Un robot arrivé en cours de tick attend le suivant pour jouer
+    pub fn register(self: &Arc<Self>, robot: RobotKey) -> Seat {
+        self.rounds.lock().unwrap().submitted.insert(robot, DONE);
+        Seat {
+            arbiter: Arc::clone(self),
+            robot,
+        }
+    }
+
+    // Seul moyen pour un robot de ne plus être attendu ; appelé par sa place quand elle est lâchée
+    fn leave(&self, robot: RobotKey) {
+        let mut rounds = self.rounds.lock().unwrap();
+        rounds.submitted.remove(&robot);
+        rounds.verdicts.remove(&robot);
+        self.changed.notify_all();
+    }
+
+    // Le robot change de clé sans perdre sa place dans le tick, puis attend son tour sous la nouvelle
+    pub fn rekey(&self, old: RobotKey, new: RobotKey) {
+        let mut rounds = self.rounds.lock().unwrap();
+        let count = rounds.submitted.remove(&old).unwrap_or(DONE);
+        rounds.submitted.insert(new, count);
+        self.changed.notify_all();
+        drop(self.changed.wait_while(rounds, |rounds| rounds.open && !rounds.has_turn(new)).unwrap());
+    }
+
+    // Attend le tour du robot dans un tick plus récent que `seen` ; rien tant que la simulation est en pause
+    pub fn wait_tick(&self, robot: RobotKey, seen: u64, timeout: Duration) -> Option<u64> {
+        let rounds = self.rounds.lock().unwrap();
+        let (rounds, _) = self
+            .changed
+            .wait_timeout_while(rounds, timeout, |rounds| rounds.tick <= seen || !rounds.has_turn(robot))
+            .unwrap();
+        (rounds.tick > seen && rounds.has_turn(robot)).then_some(rounds.tick)
+    }
+
+    // Dépose une demande, ce qui passe la main au robot suivant, puis attend le verdict et son prochain tour
+    pub fn claim(&self, claim: Claim) -> Verdict {
+        let robot = claim.robot;
+        let mut rounds = self.rounds.lock().unwrap();
+        rounds.claims.push(claim);
+        if let Some(count) = rounds.submitted.get_mut(&robot) {
+            *count += 1;
+        }
+        self.changed.notify_all();
+        let mut rounds = self
+            .changed
+            .wait_while(rounds, |rounds| !rounds.verdicts.contains_key(&robot) || !rounds.has_turn(robot))
+            .unwrap();
+        rounds.verdicts.remove(&robot).unwrap()
+    }
+
+    // Tour du tick au cours duquel le robot jouera sa prochaine action
+    pub fn next_round(&self, robot: RobotKey) -> usize {
+        self.rounds.lock().unwrap().submitted.get(&robot).copied().unwrap_or(DONE)
+    }
+
+    // Le robot, encore en route, laisse passer les tours jusqu'à `round` puis attend son tour
+    pub fn wait_round(&self, robot: RobotKey, round: usize) {
+        let mut rounds = self.rounds.lock().unwrap();
+        if let Some(count) = rounds.submitted.get_mut(&robot) {
+            *count = (*count).max(round);
+        }
+        self.changed.notify_all();
+        drop(self.changed.wait_while(rounds, |rounds| rounds.open && !rounds.has_turn(robot)).unwrap());
+    }
+
+    // Le robot a fini son tick et passe la main
+    pub fn sit_out(&self, robot: RobotKey) {
+        let mut rounds = self.rounds.lock().unwrap();
+        if let Some(count) = rounds.submitted.get_mut(&robot) {
+            *count = DONE;
+        }
+        self.changed.notify_all();
+    }
+
+    pub fn start_tick(&self, tick: u64) {
+        let mut rounds = self.rounds.lock().unwrap();
+        rounds.tick = tick;
+        rounds.open = true;
+        rounds.round = 0;
+        for count in rounds.submitted.values_mut() {
+            *count = 0;
+        }
+        self.changed.notify_all();
+    }
+
+    // Attend que chaque robot ait déposé sa demande du tour ou fini son tick, puis les renvoie toutes
+    pub fn close_round(&self) -> (u64, Vec<Claim>) {
+        let rounds = self.rounds.lock().unwrap();
+        let mut rounds = self
+            .changed
+            .wait_while(rounds, |rounds| rounds.pending().next().is_some())
+            .unwrap();
+        let number = rounds.tick * ROUNDS_PER_TICK as u64 + rounds.round as u64;
+        (number, std::mem::take(&mut rounds.claims))
+    }
+
+    // Attend que chaque robot ait fini son tick ; la base peut ensuite préparer le suivant sans eux
+    pub fn finish_tick(&self) {
+        let rounds = self.rounds.lock().unwrap();
+        let mut rounds = self
+            .changed
+            .wait_while(rounds, |rounds| rounds.pending().next().is_some())
+            .unwrap();
+        rounds.open = false;
+    }
+
+    pub fn publish(&self, verdicts: Vec<(RobotKey, Verdict)>) {
+        let mut rounds = self.rounds.lock().unwrap();
+        rounds.verdicts.extend(verdicts);
+        rounds.round += 1;
+        self.changed.notify_all();
+    }
+}
+
+// Place d'un robot auprès de l'arbitre, tenue par son thread : la lâcher, même en paniquant,
+// retire le robot des attendus pour que la base ne l'attende pas indéfiniment
+#[derive(Debug)]
+pub struct Seat {
+    arbiter: Arc<Arbiter>,
+    robot: RobotKey,
+}
+
+impl Seat {
+    // Suit la nouvelle clé du robot, déjà reportée auprès de l'arbitre par `Arbiter::rekey`
+    pub fn moved_to(&mut self, robot: RobotKey) {
+        self.robot = robot;
+    }
+}
+
+impl Drop for Seat {
+    fn drop(&mut self) {
+        self.arbiter.leave(self.robot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::tile::ResourceType;
+    use crate::robots::robot::RobotType;
+
+    const SEED: u32 = 7;
+
+    // Carte dont la zone de test est dégagée, quelle que soit la génération du relief
+    fn open_map() -> Map {
+        let mut map = Map::new(25, 25, SEED);
+        for y in 0..5 {
+            for x in 0..5 {
+                map.set(MapTile::new(x, y, TileType::Empty));
+            }
+        }
+        map
+    }
+
+    fn claim(robot: RobotKey, priority: u8, position: (usize, usize), action: Action) -> Claim {
+        Claim {
+            robot,
+            priority,
+            position,
+            action,
+            free_weight: 10,
+        }
+    }
+
+    fn place(map: &mut Map, robot: RobotKey, (x, y): (usize, usize)) {
+        map.set(MapTile::new(x, y, TileType::Robot(robot.0)));
+    }
+
+    fn winners(verdicts: &[(RobotKey, Verdict)]) -> Vec<RobotKey> {
+        verdicts
+            .iter()
+            .filter(|(_, verdict)| verdict.is_ok())
+            .map(|(robot, _)| *robot)
+            .collect()
+    }
+
+    #[test]
+    fn only_one_robot_enters_a_contested_tile() {
+        let (left, right) = ((RobotType::EXPLORER, 1), (RobotType::HARVESTER, 2));
+        let mut map = open_map();
+        place(&mut map, left, (1, 2));
+        place(&mut map, right, (3, 2));
+        let claims = [
+            claim(left, PRIORITY_DEFAULT, (1, 2), Action::Move(2, 2)),
+            claim(right, PRIORITY_DEFAULT, (3, 2), Action::Move(2, 2)),
+        ];
+
+        let verdicts = resolve(&claims, &mut map, SEED, 0);
+
+        assert_eq!(winners(&verdicts).len(), 1);
+        let winner = winners(&verdicts)[0];
+        assert_eq!(map.get(2, 2).tile, TileType::Robot(winner.0));
+    }
+
+    #[test]
+    fn outcome_does_not_depend_on_arrival_order() {
+        let robots = [(RobotType::EXPLORER, 1), (RobotType::SCOUT, 2), (RobotType::HARVESTER, 3)];
+        let starts = [(1, 2), (3, 2), (2, 1)];
+        let claims: Vec<Claim> = robots
+            .iter()
+            .zip(starts)
+            .map(|(robot, start)| claim(*robot, PRIORITY_DEFAULT, start, Action::Move(2, 2)))
+            .collect();
+
+        for round in 0..20 {
+            let mut forward_map = open_map();
+            let mut backward_map = open_map();
+            for (robot, start) in robots.iter().zip(starts) {
+                place(&mut forward_map, *robot, start);
+                place(&mut backward_map, *robot, start);
+            }
+            let reversed: Vec<Claim> = claims.iter().rev().copied().collect();
+
+            let forward = resolve(&claims, &mut forward_map, SEED, round);
+            let backward = resolve(&reversed, &mut backward_map, SEED, round);
+
+            assert_eq!(winners(&forward), winners(&backward));
+            assert_eq!(forward_map.get(2, 2), backward_map.get(2, 2));
+        }
+    }
+
+    #[test]
+    fn ties_are_shared_across_rounds() {
+        let (left, right) = ((RobotType::EXPLORER, 1), (RobotType::EXPLORER, 2));
+        let mut wins = HashMap::new();
+        for round in 0..50 {
+            let mut map = open_map();
+            place(&mut map, left, (1, 2));
+            place(&mut map, right, (3, 2));
+            let claims = [
+                claim(left, PRIORITY_DEFAULT, (1, 2), Action::Move(2, 2)),
+                claim(right, PRIORITY_DEFAULT, (3, 2), Action::Move(2, 2)),
+            ];
+            for winner in winners(&resolve(&claims, &mut map, SEED, round)) {
+                *wins.entry(winner).or_insert(0) += 1;
+            }
+        }
+        assert!(wins.get(&left).is_some_and(|count| *count > 0));
+        assert!(wins.get(&right).is_some_and(|count| *count > 0));
+    }
+
+    #[test]
+    fn higher_priority_wins_regardless_of_id() {
+        let (operator, autonomous) = ((RobotType::EXPLORER, 9), (RobotType::EXPLORER, 0));
+        for round in 0..20 {
+            let mut map = open_map();
+            place(&mut map, operator, (1, 2));
+            place(&mut map, autonomous, (3, 2));
+            let claims = [
+                claim(autonomous, PRIORITY_RETURNING, (3, 2), Action::Move(2, 2)),
+                claim(operator, PRIORITY_OPERATOR, (1, 2), Action::Move(2, 2)),
+            ];
+            assert_eq!(winners(&resolve(&claims, &mut map, SEED, round)), vec![operator]);
+        }
+    }
+
+    #[test]
+    fn tile_freed_earlier_in_the_round_can_be_entered() {
+        let (leaving, following) = ((RobotType::HARVESTER, 1), (RobotType::HARVESTER, 2));
+        let mut map = open_map();
+        place(&mut map, leaving, (2, 2));
+        place(&mut map, following, (1, 2));
+        let claims = [
+            claim(leaving, PRIORITY_RETURNING, (2, 2), Action::Move(3, 2)),
+            claim(following, PRIORITY_DEFAULT, (1, 2), Action::Move(2, 2)),
+        ];
+
+        let verdicts = resolve(&claims, &mut map, SEED, 0);
+
+        assert_eq!(winners(&verdicts), vec![leaving, following]);
+        assert_eq!(map.get(1, 2).tile, TileType::Empty);
+    }
+
+    #[test]
+    fn last_units_of_a_deposit_go_to_the_first_in_order() {
+        let (first, second) = ((RobotType::HARVESTER, 1), (RobotType::HARVESTER, 2));
+        let mut map = open_map();
+        map.set(MapTile::new(2, 2, TileType::Resource(Resource::new(4, ResourceType::Energy))));
+        let claims = [
+            claim(first, PRIORITY_RETURNING, (1, 2), Action::Harvest(2, 2)),
+            claim(second, PRIORITY_DEFAULT, (3, 2), Action::Harvest(2, 2)),
+        ];
+
+        let verdicts = resolve(&claims, &mut map, SEED, 0);
+
+        assert_eq!(
+            verdicts[0],
+            (first, Ok(Outcome::Harvested((2, 2), Resource::new(4, ResourceType::Energy))))
+        );
+        assert!(verdicts[1].1.is_err());
+        assert_eq!(map.get(2, 2).tile, TileType::Empty);
+    }
+
+    #[test]
+    fn deposit_is_shared_in_order_when_it_lasts() {
+        let (first, second) = ((RobotType::HARVESTER, 1), (RobotType::HARVESTER, 2));
+        let mut map = open_map();
+        map.set(MapTile::new(2, 2, TileType::Resource(Resource::new(14, ResourceType::Energy))));
+        let claims = [
+            claim(first, PRIORITY_RETURNING, (1, 2), Action::Harvest(2, 2)),
+            claim(second, PRIORITY_DEFAULT, (3, 2), Action::Harvest(2, 2)),
+        ];
+
+        let verdicts = resolve(&claims, &mut map, SEED, 0);
+
+        assert_eq!(
+            verdicts[1],
+            (second, Ok(Outcome::Harvested((2, 2), Resource::new(4, ResourceType::Energy))))
+        );
+        assert_eq!(map.get(2, 2).tile, TileType::Empty);
+    }
+
+    #[test]
+    fn a_robot_thread_that_panics_is_no_longer_awaited() {
+        let arbiter = Arc::new(Arbiter::new());
+        let (steady, crashing) = ((RobotType::EXPLORER, 1), (RobotType::HARVESTER, 2));
+        let _steady_seat = arbiter.register(steady);
+        let crashing_seat = arbiter.register(crashing);
+        arbiter.start_tick(1);
+        arbiter.sit_out(steady);
+
+        // Le second robot panique avant d'avoir rien déposé : sa place est lâchée pendant le déroulement
+        let crash = std::thread::spawn(move || {
+            let _seat = crashing_seat;
+            panic!("robot thread crashed");
+        });
+        assert!(crash.join().is_err());
+
+        // La base clôt le tour et le tick sans rester bloquée sur le robot disparu
+        let (done, finished) = std::sync::mpsc::channel();
+        let base = Arc::clone(&arbiter);
+        std::thread::spawn(move || {
+            let (_, claims) = base.close_round();
+            base.finish_tick();
+            done.send(claims.len()).unwrap();
+        });
+        assert_eq!(finished.recv_timeout(Duration::from_secs(5)), Ok(0));
+    }
+
+    #[test]
+    fn a_robot_still_travelling_plays_in_the_round_its_clock_reaches() {
+        let arbiter = Arc::new(Arbiter::new());
+        let (traveller, idle) = ((RobotType::EXPLORER, 1), (RobotType::HARVESTER, 2));
+        let _traveller_seat = arbiter.register(traveller);
+        let _idle_seat = arbiter.register(idle);
+        arbiter.start_tick(1);
+        arbiter.sit_out(idle);
+
+        let robot = Arc::clone(&arbiter);
+        let travelling = std::thread::spawn(move || {
+            // Son trajet se termine au troisième tour du tick
+            robot.wait_round(traveller, 2);
+            let verdict = robot.claim(claim(traveller, PRIORITY_DEFAULT, (1, 1), Action::Move(1, 2)));
+            robot.sit_out(traveller);
+            verdict
+        });
+
+        let mut played = Vec::new();
+        for _ in 0..ROUNDS_PER_TICK {
+            let (round, claims) = arbiter.close_round();
+            if !claims.is_empty() {
+                played.push(round);
+            }
+            arbiter.publish(claims.iter().map(|claim| (claim.robot, Ok(Outcome::Moved(1, 2)))).collect());
+        }
+        arbiter.finish_tick();
+
+        assert_eq!(travelling.join().unwrap(), Ok(Outcome::Moved(1, 2)));
+        assert_eq!(played, vec![ROUNDS_PER_TICK as u64 + 2]);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::environment::tile::{Resource, ResourceType};
+
+use super::radio::RobotKey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositState {
+    // Signalé à la base, en attente d'un récolteur
+    Discovered,
+    Assigned,
+    BeingHarvested,
+    Depleted,
+    // Disparu avant d'être épuisé par la flotte, ou jugé inaccessible
+    Lost,
+}
+
+impl DepositState {
+    pub const ALL: [DepositState; 5] = [
+        DepositState::Discovered,
+        DepositState::Assigned,
+        DepositState::BeingHarvested,
+        DepositState::Depleted,
+        DepositState::Lost,
+    ];
+
+    // Encore à récolter, du point de vue de la base
+    pub fn is_available(&self) -> bool {
+        matches!(
+            self,
+            DepositState::Discovered | DepositState::Assigned | DepositState::BeingHarvested
+        )
+    }
+}
+
+impl fmt::Display for DepositState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositState::Discovered => write!(f, "discovered"),
+            DepositState::Assigned => write!(f, "assigned"),
+            DepositState::BeingHarvested => write!(f, "being harvested"),
+            DepositState::Depleted => write!(f, "depleted"),
+            DepositState::Lost => write!(f, "lost"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Deposit {
+    pub position: (usize, usize),
+    pub resource_type: ResourceType,
+    // Dernière quantité connue de la base
+    pub amount: u32,
+    pub state: DepositState,
+    // Robot à l'origine du dernier changement d'état
+    pub robot: Option<RobotKey>,
+    pub discovered_at: u64,
+    pub updated_at: u64,
+}
+
+impl fmt::Display for Deposit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({}, {}) {:?} {}: {}",
+            self.position.0, self.position.1, self.resource_type, self.amount, self.state
+        )?;
+        if let Some((robot_type, id)) = self.robot {
+            write!(f, " by {} {}", robot_type, id)?;
+        }
+        write!(f, " since tick {} (found tick {})", self.updated_at, self.discovered_at)
+    }
+}
+
+// Registre des gisements connus de la base et de leur cycle de vie
+#[derive(Debug, Clone, Default)]
+pub struct DepositRegistry {
+    deposits: HashMap<(usize, usize), Deposit>,
+}
+
+impl DepositRegistry {
+    pub fn new() -> Self {
+        DepositRegistry::default()
+    }
+
+    // Vrai pour un gisement jusque-là inconnu ; un gisement déjà suivi garde son état
+    pub fn discover(&mut self, position: (usize, usize), resource: Resource, robot: RobotKey, tick: u64) -> bool {
+        if self.deposits.contains_key(&position) {
+            return false;
+        }
+        self.deposits.insert(
+            position,
+            Deposit {
+                position,
+                resource_type: resource.resource_type,
+                amount: resource.scale,
+                state: DepositState::Discovered,
+                robot: Some(robot),
+                discovered_at: tick,
+                updated_at: tick,
+            },
+        );
+        true
+    }
+
+    pub fn assign(&mut self, position: (usize, usize), robot: RobotKey, tick: u64) {
+        self.update(position, DepositState::Assigned, Some(robot), tick);
+    }
+
+    // Tâche remise en jeu : le gisement attend un autre récolteur
+    pub fn release(&mut self, position: (usize, usize), tick: u64) {
+        self.update(position, DepositState::Discovered, None, tick);
+    }
+
+    // Un gisement récolté sans avoir été signalé (pilotage manuel, tournée) est ajouté au registre
+    pub fn harvest(&mut self, position: (usize, usize), deposit: Resource, remaining: u32, robot: RobotKey, tick: u64) {
+        let entry = self.deposits.entry(position).or_insert(Deposit {
+            position,
+            resource_type: deposit.resource_type,
+            amount: deposit.scale,
+            state: DepositState::BeingHarvested,
+            robot: Some(robot),
+            discovered_at: tick,
+            updated_at: tick,
+        });
+        entry.amount = remaining;
+        let state = match remaining {
+            0 => DepositState::Depleted,
+            _ => DepositState::BeingHarvested,
+        };
+        self.update(position, state, Some(robot), tick);
+    }
+
+    // Le récolteur en a fini avec le gisement ; s'il restait quelque chose, il a disparu entre-temps
+    pub fn finish(&mut self, position: (usize, usize), robot: RobotKey, tick: u64) {
+        if self
+            .deposits
+            .get(&position)
+            .is_some_and(|deposit| deposit.state != DepositState::Depleted)
+        {
+            self.lose(position, robot, tick);
+        }
+    }
+
+    pub fn lose(&mut self, position: (usize, usize), robot: RobotKey, tick: u64) {
+        self.update(position, DepositState::Lost, Some(robot), tick);
+    }
+
+    // Du plus récemment mis à jour au plus ancien
+    pub fn deposits(&self) -> Vec<Deposit> {
+        let mut deposits: Vec<Deposit> = self.deposits.values().copied().collect();
+        deposits.sort_by_key(|deposit| (std::cmp::Reverse(deposit.updated_at), deposit.position));
+        deposits
+    }
+
+    pub fn count(&self, state: DepositState) -> usize {
+        self.deposits.values().filter(|deposit| deposit.state == state).count()
+    }
+
+    // Quantité encore à récolter, par type de ressource
+    pub fn remaining(&self, resource_type: ResourceType) -> u32 {
+        self.deposits
+            .values()
+            .filter(|deposit| deposit.state.is_available() && deposit.resource_type == resource_type)
+            .map(|deposit| deposit.amount)
+            .sum()
+    }
+
+    fn update(&mut self, position: (usize, usize), state: DepositState, robot: Option<RobotKey>, tick: u64) {
+        if let Some(deposit) = self.deposits.get_mut(&position) {
+            deposit.state = state;
+            deposit.robot = robot;
+            deposit.updated_at = tick;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robots::robot::RobotType;
+
+    const EXPLORER: RobotKey = (RobotType::EXPLORER, 1);
+    const HARVESTER: RobotKey = (RobotType::HARVESTER, 2);
+    const SITE: (usize, usize) = (4, 7);
+
+    fn state(registry: &DepositRegistry) -> DepositState {
+        registry.deposits.get(&SITE).unwrap().state
+    }
+
+    fn discovered() -> DepositRegistry {
+        let mut registry = DepositRegistry::new();
+        assert!(registry.discover(SITE, Resource::new(10, ResourceType::Mineral), EXPLORER, 1));
+        registry
+    }
+
+    #[test]
+    fn a_report_registers_a_new_deposit() {
+        let registry = discovered();
+        assert_eq!(state(&registry), DepositState::Discovered);
+        assert_eq!(registry.remaining(ResourceType::Mineral), 10);
+        assert_eq!(registry.remaining(ResourceType::Energy), 0);
+    }
+
+    #[test]
+    fn an_assigned_deposit_can_be_released() {
+        let mut registry = discovered();
+        registry.assign(SITE, HARVESTER, 2);
+        assert_eq!(state(&registry), DepositState::Assigned);
+        assert_eq!(registry.deposits()[0].robot, Some(HARVESTER));
+
+        registry.release(SITE, 3);
+        assert_eq!(state(&registry), DepositState::Discovered);
+        assert_eq!(registry.deposits()[0].robot, None);
+    }
+
+    #[test]
+    fn harvesting_down_to_zero_depletes_the_deposit() {
+        let mut registry = discovered();
+        registry.assign(SITE, HARVESTER, 2);
+        registry.harvest(SITE, Resource::new(10, ResourceType::Mineral), 4, HARVESTER, 3);
+        assert_eq!(state(&registry), DepositState::BeingHarvested);
+        assert_eq!(registry.remaining(ResourceType::Mineral), 4);
+
+        registry.harvest(SITE, Resource::new(4, ResourceType::Mineral), 0, HARVESTER, 4);
+        registry.finish(SITE, HARVESTER, 5);
+        // Un gisement épuisé n'est pas perdu et ne compte plus dans le stock
+        assert_eq!(state(&registry), DepositState::Depleted);
+        assert_eq!(registry.remaining(ResourceType::Mineral), 0);
+        assert_eq!(registry.deposits()[0].updated_at, 4);
+    }
+
+    #[test]
+    fn finishing_a_deposit_that_was_not_empty_loses_it() {
+        let mut registry = discovered();
+        registry.harvest(SITE, Resource::new(10, ResourceType::Mineral), 6, HARVESTER, 2);
+        registry.finish(SITE, HARVESTER, 3);
+        assert_eq!(state(&registry), DepositState::Lost);
+        assert_eq!(registry.count(DepositState::Lost), 1);
+        assert_eq!(registry.remaining(ResourceType::Mineral), 0);
+    }
+
+    #[test]
+    fn an_unreported_harvest_adds_the_deposit() {
+        let mut registry = DepositRegistry::new();
+        registry.harvest(SITE, Resource::new(10, ResourceType::Energy), 7, HARVESTER, 5);
+        let deposit = registry.deposits()[0];
+        assert_eq!(deposit.state, DepositState::BeingHarvested);
+        assert_eq!(deposit.amount, 7);
+        assert_eq!(deposit.discovered_at, 5);
+    }
+
+    #[test]
+    fn reporting_a_finished_deposit_again_keeps_its_state() {
+        let mut registry = discovered();
+        registry.harvest(SITE, Resource::new(10, ResourceType::Mineral), 0, HARVESTER, 2);
+        assert!(!registry.discover(SITE, Resource::new(10, ResourceType::Mineral), EXPLORER, 3));
+        assert_eq!(state(&registry), DepositState::Depleted);
+
+        registry.discover((9, 9), Resource::new(3, ResourceType::Energy), EXPLORER, 4);
+        registry.lose((9, 9), HARVESTER, 5);
+        assert!(!registry.discover((9, 9), Resource::new(3, ResourceType::Energy), EXPLORER, 6));
+        assert_eq!(registry.count(DepositState::Lost), 1);
+        assert_eq!(registry.count(DepositState::Discovered), 0);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::environment::tile::{Resource, ResourceType};
+use crate::robots::registry::registry;
+use crate::robots::robot::RobotType;
+
+use super::stock::Stock;
+
+pub fn robot_cost(robot_type: RobotType) -> Vec<Resource> {
+    registry()
+        .read()
+        .unwrap()
+        .get(robot_type)
+        .map(|kind| kind.config.cost())
+        .unwrap_or_default()
+}
+
+pub fn build_ticks(robot_type: RobotType) -> u32 {
+    registry()
+        .read()
+        .unwrap()
+        .get(robot_type)
+        .map(|kind| kind.config.build_ticks)
+        .unwrap_or(1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProductionPolicy {
+    // 0 désactive la règle correspondante
+    pub keep_explorers: usize,
+    pub deposits_per_harvester: usize,
+    pub energy_reserve: u32,
+    pub mineral_reserve: u32,
+}
+
+impl Default for ProductionPolicy {
+    fn default() -> Self {
+        ProductionPolicy {
+            keep_explorers: 0,
+            deposits_per_harvester: 1,
+            energy_reserve: 0,
+            mineral_reserve: 0,
+        }
+    }
+}
+
+impl ProductionPolicy {
+    pub fn reserve(&self, resource_type: ResourceType) -> u32 {
+        match resource_type {
+            ResourceType::Energy => self.energy_reserve,
+            ResourceType::Mineral => self.mineral_reserve,
+        }
+    }
+
+    pub fn adjust(&mut self, setting: PolicySetting, increase: bool) {
+        match setting {
+            PolicySetting::KeepExplorers => {
+                self.keep_explorers = step(self.keep_explorers, 1, increase)
+            }
+            PolicySetting::DepositsPerHarvester => {
+                self.deposits_per_harvester = step(self.deposits_per_harvester, 1, increase)
+            }
+            PolicySetting::EnergyReserve => {
+                self.energy_reserve = step(self.energy_reserve as usize, 5, increase) as u32
+            }
+            PolicySetting::MineralReserve => {
+                self.mineral_reserve = step(self.mineral_reserve as usize, 5, increase) as u32
+            }
+        }
+    }
+
+    pub fn value(&self, setting: PolicySetting) -> usize {
+        match setting {
+            PolicySetting::KeepExplorers => self.keep_explorers,
+            PolicySetting::DepositsPerHarvester => self.deposits_per_harvester,
+            PolicySetting::EnergyReserve => self.energy_reserve as usize,
+            PolicySetting::MineralReserve => self.mineral_reserve as usize,
+        }
+    }
+}
+
+fn step(value: usize, amount: usize, increase: bool) -> usize {
+    match increase {
+        true => value + amount,
+        false => value.saturating_sub(amount),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicySetting {
+    KeepExplorers,
+    DepositsPerHarvester,
+    EnergyReserve,
+    MineralReserve,
+}
+
+impl PolicySetting {
+    pub const ALL: [PolicySetting; 4] = [
+        PolicySetting::KeepExplorers,
+        PolicySetting::DepositsPerHarvester,
+        PolicySetting::EnergyReserve,
+        PolicySetting::MineralReserve,
+    ];
+}
+
+impl fmt::Display for PolicySetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PolicySetting::KeepExplorers => "Explorers kept",
+            PolicySetting::DepositsPerHarvester => "Deposits/harvester",
+            PolicySetting::EnergyReserve => "Energy reserve",
+            PolicySetting::MineralReserve => "Mineral reserve",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProductionOrder {
+    pub robot_type: RobotType,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Factory {
+    pub queue: VecDeque<ProductionOrder>,
+    pub current: Option<(ProductionOrder, u32)>,
+    pub policy: ProductionPolicy,
+}
+
+impl Factory {
+    pub fn new() -> Self {
+        Factory {
+            queue: VecDeque::new(),
+            current: None,
+            policy: ProductionPolicy::default(),
+        }
+    }
+
+    pub fn enqueue(&mut self, order: ProductionOrder) {
+        self.queue.push_back(order);
+    }
+
+    pub fn pending(&self, robot_type: RobotType) -> usize {
+        self.queue
+            .iter()
+            .chain(self.current.iter().map(|(order, _)| order))
+            .filter(|order| order.robot_type == robot_type)
+            .count()
+    }
+
+    // Applique les politiques de production en complétant la file d'attente.
+    // Les récolteurs ne reçoivent pas de cible : ils enchérissent ensuite sur les tâches.
+    // Chaque rôle est donné avec le type produit pour lui et le nombre de robots actifs
+    pub fn plan(
+        &mut self,
+        (explorer, active_explorers): (RobotType, usize),
+        (harvester, active_harvesters): (RobotType, usize),
+        outstanding_tasks: usize,
+    ) {
+        while active_explorers + self.pending(explorer) < self.policy.keep_explorers {
+            self.enqueue(ProductionOrder { robot_type: explorer });
+        }
+
+        let wanted = self.wanted_harvesters(outstanding_tasks);
+        while active_harvesters + self.pending(harvester) < wanted {
+            self.enqueue(ProductionOrder { robot_type: harvester });
+        }
+    }
+
+    pub fn wanted_harvesters(&self, outstanding_tasks: usize) -> usize {
+        match self.policy.deposits_per_harvester {
+            0 => 0,
+            per_harvester => outstanding_tasks.div_ceil(per_harvester),
+        }
+    }
+
+    // Avance la production d'un tick et renvoie le robot terminé, s'il y en a un
+    pub fn advance(&mut self, stock: &mut Stock) -> Option<ProductionOrder> {
+        if self.current.is_none() {
+            let order = *self.queue.front()?;
+            let policy = self.policy;
+            if !stock.spend_all(&robot_cost(order.robot_type), |resource_type| {
+                policy.reserve(resource_type)
+            }) {
+                return None;
+            }
+            self.queue.pop_front();
+            self.current = Some((order, 0));
+        }
+
+        let (order, progress) = self.current.as_mut()?;
+        *progress += 1;
+        if *progress >= build_ticks(order.robot_type) {
+            let order = *order;
+            self.current = None;
+            return Some(order);
+        }
+        None
+    }
+}
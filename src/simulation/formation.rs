@@ -0,0 +1,332 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use super::radio::RobotKey;
+
+// Écart toléré, en tuiles, entre un suiveur et le meneur au-delà de sa place
+const SLACK: usize = 2;
+// Pas pendant lesquels le meneur attend un groupe dispersé avant de repartir
+const MAX_WAIT: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    // Colonne derrière le meneur
+    Line,
+    // En V, deux par rang
+    Wedge,
+    // Autour du meneur, sur les côtés d'abord
+    Escort,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 3] = [Shape::Line, Shape::Wedge, Shape::Escort];
+
+    // Place du suiveur `index` relativement au meneur, `heading` étant le sens de marche
+    fn offset(&self, index: usize, (dx, dy): (isize, isize)) -> (isize, isize) {
+        let back = (-dx, -dy);
+        let side = (-dy, dx);
+        match self {
+            Shape::Line => {
+                let rank = index as isize + 1;
+                (back.0 * rank, back.1 * rank)
+            }
+            Shape::Wedge => {
+                let rank = (index / 2) as isize + 1;
+                let sign = match index % 2 {
+                    0 => 1,
+                    _ => -1,
+                };
+                (
+                    back.0 * rank + side.0 * rank * sign,
+                    back.1 * rank + side.1 * rank * sign,
+                )
+            }
+            Shape::Escort => {
+                let ring = (index / 4) as isize + 1;
+                let (x, y) = [side, (-side.0, -side.1), back, (dx, dy)][index % 4];
+                (x * ring, y * ring)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Shape::Line => "Line",
+            Shape::Wedge => "Wedge",
+            Shape::Escort => "Escort",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Formation {
+    pub id: usize,
+    pub shape: Shape,
+    // Le meneur en tête, puis les suiveurs dans l'ordre de leurs places
+    pub members: Vec<RobotKey>,
+    pub destination: (usize, usize),
+    positions: HashMap<RobotKey, (usize, usize)>,
+    // Dernières positions du meneur, la plus récente en tête
+    trail: VecDeque<(usize, usize)>,
+    waited: u32,
+}
+
+// Ce qu'un membre doit faire à ce pas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormationOrder {
+    // Avance vers la destination, ou attend que le groupe se resserre
+    Lead {
+        group: usize,
+        destination: (usize, usize),
+        hold: bool,
+    },
+    // Rejoint sa place ; `fallback` est la trace du meneur, suivie quand la place est bloquée
+    Follow {
+        slot: Option<(usize, usize)>,
+        fallback: (usize, usize),
+    },
+}
+
+impl Formation {
+    pub fn leader(&self) -> RobotKey {
+        self.members[0]
+    }
+
+    // Sens de marche du meneur : son dernier pas, sinon la direction de la destination
+    fn heading(&self) -> (isize, isize) {
+        let toward = |from: (usize, usize), to: (usize, usize)| {
+            let (dx, dy) = (to.0 as isize - from.0 as isize, to.1 as isize - from.1 as isize);
+            match dx.abs() >= dy.abs() {
+                true => (dx.signum(), 0),
+                false => (0, dy.signum()),
+            }
+        };
+        match (self.trail.front(), self.trail.get(1)) {
+            (Some(current), Some(previous)) if current != previous => toward(*previous, *current),
+            (Some(current), _) => toward(*current, self.destination),
+            _ => (1, 0),
+        }
+    }
+
+    fn slot(&self, index: usize) -> Option<(usize, usize)> {
+        let (leader_x, leader_y) = *self.trail.front()?;
+        let (dx, dy) = self.shape.offset(index, self.heading());
+        let (x, y) = (leader_x as isize + dx, leader_y as isize + dy);
+        match x >= 0 && y >= 0 {
+            true => Some((x as usize, y as usize)),
+            false => None,
+        }
+    }
+
+    // Chaque suiveur est à portée de sa place ou de la trace du meneur ; un membre qui n'a
+    // pas encore bougé (à quai, en panne) ne retient pas le groupe
+    fn is_gathered(&self) -> bool {
+        let Some(leader) = self.trail.front() else {
+            return false;
+        };
+        self.members.iter().skip(1).enumerate().all(|(index, member)| {
+            let Some((x, y)) = self.positions.get(member) else {
+                return true;
+            };
+            let (dx, dy) = self.shape.offset(index, self.heading());
+            let spread = (dx.unsigned_abs() + dy.unsigned_abs()).max(index + 1);
+            x.abs_diff(leader.0) + y.abs_diff(leader.1) <= spread + SLACK
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Formations {
+    next_id: usize,
+    groups: Vec<Formation>,
+}
+
+impl Formations {
+    pub fn new() -> Self {
+        Formations::default()
+    }
+
+    // Un robot n'appartient qu'à un seul groupe : il quitte l'ancien en rejoignant le nouveau
+    pub fn create(&mut self, shape: Shape, members: Vec<RobotKey>, destination: (usize, usize)) -> usize {
+        for member in &members {
+            self.leave(*member);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.groups.push(Formation {
+            id,
+            shape,
+            members,
+            destination,
+            positions: HashMap::new(),
+            trail: VecDeque::new(),
+            waited: 0,
+        });
+        id
+    }
+
+    pub fn groups(&self) -> &[Formation] {
+        &self.groups
+    }
+
+    pub fn order(&self, robot: RobotKey) -> Option<FormationOrder> {
+        let group = self
+            .groups
+            .iter()
+            .find(|group| group.members.contains(&robot))?;
+        let index = group.members.iter().position(|member| *member == robot)?;
+        if index == 0 {
+            return Some(FormationOrder::Lead {
+                group: group.id,
+                destination: group.destination,
+                hold: !group.is_gathered() && group.waited < MAX_WAIT,
+            });
+        }
+        let fallback = group
+            .trail
+            .get(index)
+            .or(group.trail.back())
+            .copied()
+            .or_else(|| group.positions.get(&robot).copied())?;
+        Some(FormationOrder::Follow {
+            slot: group.slot(index - 1),
+            fallback,
+        })
+    }
+
+    pub fn report(&mut self, robot: RobotKey, position: (usize, usize)) {
+        let Some(group) = self
+            .groups
+            .iter_mut()
+            .find(|group| group.members.contains(&robot))
+        else {
+            return;
+        };
+        group.positions.insert(robot, position);
+        if group.leader() != robot || group.trail.front() == Some(&position) {
+            return;
+        }
+        group.trail.push_front(position);
+        group.trail.truncate(group.members.len() + 1);
+        group.waited = 0;
+    }
+
+    pub fn hold(&mut self, group: usize) {
+        if let Some(group) = self.groups.iter_mut().find(|formation| formation.id == group) {
+            group.waited += 1;
+        }
+    }
+
+    // Le premier suiveur reprend la tête si le meneur s'en va ; un groupe d'un seul robot est dissous
+    pub fn leave(&mut self, robot: RobotKey) {
+        for group in self.groups.iter_mut() {
+            if group.leader() == robot {
+                group.trail.clear();
+            }
+            group.members.retain(|member| *member != robot);
+            group.positions.remove(&robot);
+        }
+        self.groups.retain(|group| group.members.len() > 1);
+    }
+
+    pub fn disband(&mut self, group: usize) -> Option<Formation> {
+        let index = self.groups.iter().position(|formation| formation.id == group)?;
+        Some(self.groups.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robots::robot::RobotType;
+
+    fn members(count: usize) -> Vec<RobotKey> {
+        (0..count).map(|id| (RobotType::HARVESTER, id)).collect()
+    }
+
+    // Places des suiveurs une fois que le meneur a fait un pas vers l'est, de (9, 10) à (10, 10)
+    fn slots(shape: Shape, count: usize) -> Vec<Option<(usize, usize)>> {
+        let mut formations = Formations::new();
+        formations.create(shape, members(count), (20, 10));
+        formations.report(members(1)[0], (9, 10));
+        formations.report(members(1)[0], (10, 10));
+        members(count)
+            .into_iter()
+            .skip(1)
+            .map(|member| match formations.order(member) {
+                Some(FormationOrder::Follow { slot, .. }) => slot,
+                order => panic!("{:?} should follow, got {:?}", member, order),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_line_queues_behind_the_leader() {
+        assert_eq!(slots(Shape::Line, 3), [Some((9, 10)), Some((8, 10))]);
+    }
+
+    #[test]
+    fn a_wedge_alternates_sides_rank_by_rank() {
+        assert_eq!(
+            slots(Shape::Wedge, 4),
+            [Some((9, 11)), Some((9, 9)), Some((8, 12))]
+        );
+    }
+
+    #[test]
+    fn an_escort_fills_the_sides_first() {
+        assert_eq!(
+            slots(Shape::Escort, 6),
+            [Some((10, 11)), Some((10, 9)), Some((9, 10)), Some((11, 10)), Some((10, 12))]
+        );
+    }
+
+    #[test]
+    fn a_leader_that_has_not_moved_faces_its_destination() {
+        let mut formations = Formations::new();
+        formations.create(Shape::Line, members(2), (10, 0));
+        formations.report(members(1)[0], (10, 10));
+        assert_eq!(
+            formations.order(members(2)[1]),
+            Some(FormationOrder::Follow {
+                slot: Some((10, 11)),
+                fallback: (10, 10),
+            })
+        );
+    }
+
+    #[test]
+    fn a_slot_off_the_map_falls_back_to_the_trail() {
+        let mut formations = Formations::new();
+        formations.create(Shape::Line, members(2), (10, 5));
+        // Le meneur part du bord ouest vers l'est : la place derrière lui est hors de la carte
+        formations.report(members(1)[0], (0, 5));
+        assert_eq!(
+            formations.order(members(2)[1]),
+            Some(FormationOrder::Follow {
+                slot: None,
+                fallback: (0, 5),
+            })
+        );
+    }
+
+    #[test]
+    fn the_first_follower_takes_over_when_the_leader_leaves() {
+        let mut formations = Formations::new();
+        let group = formations.create(Shape::Wedge, members(3), (20, 10));
+        formations.leave(members(1)[0]);
+        assert_eq!(formations.groups()[0].leader(), members(2)[1]);
+        assert!(matches!(
+            formations.order(members(2)[1]),
+            Some(FormationOrder::Lead { group: id, .. }) if id == group
+        ));
+
+        // Seul, le dernier robot n'est plus en formation
+        formations.leave(members(2)[1]);
+        assert!(formations.groups().is_empty());
+        assert_eq!(formations.order(members(3)[2]), None);
+    }
+}
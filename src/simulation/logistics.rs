@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::environment::tile::{Resource, ResourceType};
+
+use super::stock::Stock;
+
+// Unités stockées dans un dépôt avant qu'un transporteur ne soit envoyé
+pub const PICKUP_THRESHOLD: u32 = 10;
+// Fenêtre, en ticks, sur laquelle est mesuré le débit livré à la base
+pub const THROUGHPUT_WINDOW: u64 = 100;
+
+// Chaîne d'approvisionnement : les récolteurs vident leur soute dans le dépôt le plus proche,
+// les transporteurs ramènent le contenu des dépôts à la base
+#[derive(Debug, Clone, Default)]
+pub struct Logistics {
+    depots: HashMap<(usize, usize), Stock>,
+    // Dépôts vers lesquels un transporteur est déjà en route
+    claimed: HashSet<(usize, usize)>,
+    pub dropped: u32,
+    pub hauled: u32,
+    // Unités livrées à la base, par tick de livraison
+    deliveries: VecDeque<(u64, u32)>,
+}
+
+impl Logistics {
+    pub fn new() -> Self {
+        Logistics::default()
+    }
+
+    pub fn store(&mut self, depot: (usize, usize), cargo: Vec<Resource>) -> u32 {
+        let stock = self.depots.entry(depot).or_default();
+        let mut units = 0;
+        for resource in cargo {
+            units += resource.scale;
+            stock.add(resource);
+        }
+        self.dropped += units;
+        units
+    }
+
+    // Retire du dépôt ce qui tient dans le poids libre, énergie d'abord
+    pub fn take(&mut self, depot: (usize, usize), free_weight: u32) -> Vec<Resource> {
+        let Some(stock) = self.depots.get_mut(&depot) else {
+            return Vec::new();
+        };
+        let mut free_weight = free_weight;
+        let mut taken = Vec::new();
+        for resource_type in ResourceType::ALL {
+            let amount = stock
+                .get(resource_type)
+                .min(free_weight / resource_type.unit_weight());
+            if amount > 0 && stock.spend(resource_type, amount) {
+                free_weight -= amount * resource_type.unit_weight();
+                taken.push(Resource::new(amount, resource_type));
+            }
+        }
+        taken
+    }
+
+    pub fn stored(&self) -> u32 {
+        self.depots.values().map(Stock::total).sum()
+    }
+
+    // Dépôt le plus rempli au-delà du seuil que personne ne vient déjà vider
+    pub fn next_pickup(&self) -> Option<(usize, usize)> {
+        self.depots
+            .iter()
+            .filter(|(depot, stock)| !self.claimed.contains(*depot) && stock.total() >= PICKUP_THRESHOLD)
+            .max_by_key(|(depot, stock)| (stock.total(), std::cmp::Reverse(**depot)))
+            .map(|(depot, _)| *depot)
+    }
+
+    pub fn claim(&mut self, depot: (usize, usize)) {
+        self.claimed.insert(depot);
+    }
+
+    pub fn release(&mut self, depot: (usize, usize)) {
+        self.claimed.remove(&depot);
+    }
+
+    pub fn record_delivery(&mut self, tick: u64, units: u32) {
+        if units > 0 {
+            self.deliveries.push_back((tick, units));
+        }
+        while self
+            .deliveries
+            .front()
+            .is_some_and(|(delivered, _)| delivered + THROUGHPUT_WINDOW <= tick)
+        {
+            self.deliveries.pop_front();
+        }
+    }
+
+    // Unités livrées à la base sur les derniers THROUGHPUT_WINDOW ticks
+    pub fn throughput(&self, tick: u64) -> u32 {
+        self.deliveries
+            .iter()
+            .filter(|(delivered, _)| delivered + THROUGHPUT_WINDOW > tick)
+            .map(|(_, units)| units)
+            .sum()
+    }
+}
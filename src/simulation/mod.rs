@@ -1 +1,15 @@
-pub mod simulation;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod simulation;
+pub mod allocation;
+pub mod arbiter;
+pub mod deposits;
+pub mod factory;
+pub mod formation;
+pub mod logistics;
+pub mod motion;
+pub mod pool;
+pub mod radio;
+pub mod stock;
+pub mod teleop;
+pub mod watchdog;
+pub mod zones;
\ No newline at end of file
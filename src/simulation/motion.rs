@@ -0,0 +1,73 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use super::radio::RobotKey;
+
+// Retard d'animation au-delà duquel le robot est replacé directement sur sa tuile
+const MAX_LAG: Duration = Duration::from_secs(1);
+
+// Trajet d'une tuile à sa voisine, tel que l'interface doit l'animer
+#[derive(Debug, Clone, Copy)]
+struct Leg {
+    from: (usize, usize),
+    to: (usize, usize),
+    start: Instant,
+    end: Instant,
+}
+
+// Déplacements récents de chaque robot : la carte est mise à jour d'un coup,
+// l'interface fait glisser le robot d'une tuile à l'autre pendant la durée du trajet
+#[derive(Debug, Clone, Default)]
+pub struct Motions {
+    legs: HashMap<RobotKey, VecDeque<Leg>>,
+}
+
+impl Motions {
+    pub fn new() -> Self {
+        Motions::default()
+    }
+
+    // Un trajet commence à la fin du précédent, pour qu'un robot rapide enchaîne ses pas
+    pub fn record(&mut self, robot: RobotKey, from: (usize, usize), to: (usize, usize), duration: Duration) {
+        let now = Instant::now();
+        let legs = self.legs.entry(robot).or_default();
+        legs.retain(|leg| leg.end > now);
+        let start = match legs.back() {
+            Some(last) if last.to == from && last.end < now + MAX_LAG => last.end,
+            _ => {
+                legs.clear();
+                now
+            }
+        };
+        legs.push_back(Leg {
+            from,
+            to,
+            start,
+            end: start + duration,
+        });
+    }
+
+    pub fn forget(&mut self, robot: RobotKey) {
+        self.legs.remove(&robot);
+    }
+
+    // Position affichée, en tuiles, des robots encore en mouvement, indexée par leur tuile d'arrivée
+    pub fn positions(&self, now: Instant) -> HashMap<(usize, usize), (f32, f32)> {
+        let mut positions = HashMap::new();
+        for legs in self.legs.values() {
+            let (Some(last), Some(current)) = (legs.back(), legs.iter().find(|leg| leg.end > now)) else {
+                continue;
+            };
+            let progress = match now.checked_duration_since(current.start) {
+                Some(elapsed) => elapsed.as_secs_f32() / (current.end - current.start).as_secs_f32(),
+                None => 0.0,
+            };
+            let lerp = |from: usize, to: usize| from as f32 + (to as f32 - from as f32) * progress;
+            positions.insert(
+                last.to,
+                (lerp(current.from.0, current.to.0), lerp(current.from.1, current.to.1)),
+            );
+        }
+        positions
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::thread::JoinHandle;
+
+use crate::robots::robot::{Robot, RobotType};
+
+use super::radio::RobotKey;
+
+// Mission confiée à un robot à quai, appliquée par son propre thread
+pub type Assignment = Box<dyn FnOnce(&mut Box<dyn Robot + Send>) + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolStatus {
+    Active,
+    Docked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FleetEntry {
+    pub robot: RobotKey,
+    pub status: PoolStatus,
+}
+
+impl fmt::Display for FleetEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (robot_type, id) = self.robot;
+        match self.status {
+            PoolStatus::Active => write!(f, "{} {}", robot_type, id),
+            PoolStatus::Docked => write!(f, "{} {} (docked)", robot_type, id),
+        }
+    }
+}
+
+struct PoolEntry {
+    handle: JoinHandle<()>,
+    status: PoolStatus,
+}
+
+// Flotte persistante : les robots restent à quai entre deux missions
+// et leurs threads ne sont rendus qu'au démantèlement
+#[derive(Default)]
+pub struct RobotPool {
+    // Identifiants uniques sur toute la flotte, jamais réutilisés
+    next_id: usize,
+    robots: HashMap<RobotKey, PoolEntry>,
+    assignments: HashMap<RobotKey, Assignment>,
+    // Robot rééquipé qui remplacera celui à quai au prochain passage de son thread
+    refits: HashMap<RobotKey, Box<dyn Robot + Send>>,
+    decommissioned: HashSet<RobotKey>,
+}
+
+impl RobotPool {
+    pub fn new() -> Self {
+        RobotPool::default()
+    }
+
+    pub fn next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn insert(&mut self, robot: RobotKey, handle: JoinHandle<()>) {
+        self.robots.insert(
+            robot,
+            PoolEntry {
+                handle,
+                status: PoolStatus::Active,
+            },
+        );
+    }
+
+    pub fn set_status(&mut self, robot: RobotKey, status: PoolStatus) {
+        if let Some(entry) = self.robots.get_mut(&robot) {
+            entry.status = status;
+        }
+    }
+
+    // Robot à quai du type demandé, sans mission en attente ni démantèlement prévu
+    pub fn docked(&self, robot_type: RobotType) -> Option<RobotKey> {
+        self.robots
+            .keys()
+            .filter(|key| key.0 == robot_type && self.is_available(**key))
+            .min_by_key(|(_, id)| *id)
+            .copied()
+    }
+
+    pub fn is_available(&self, robot: RobotKey) -> bool {
+        self.robots
+            .get(&robot)
+            .is_some_and(|entry| entry.status == PoolStatus::Docked)
+            && !self.assignments.contains_key(&robot)
+            && !self.refits.contains_key(&robot)
+            && !self.decommissioned.contains(&robot)
+    }
+
+    pub fn assign(&mut self, robot: RobotKey, assignment: Assignment) {
+        self.assignments.insert(robot, assignment);
+        self.set_status(robot, PoolStatus::Active);
+    }
+
+    pub fn take_assignment(&mut self, robot: RobotKey) -> Option<Assignment> {
+        self.assignments.remove(&robot)
+    }
+
+    pub fn refit(&mut self, robot: RobotKey, replacement: Box<dyn Robot + Send>) {
+        self.refits.insert(robot, replacement);
+    }
+
+    pub fn take_refit(&mut self, robot: RobotKey) -> Option<Box<dyn Robot + Send>> {
+        self.refits.remove(&robot)
+    }
+
+    // Le type fait partie de la clé : un robot rééquipé change de clé, pas d'identifiant
+    pub fn rekey(&mut self, old: RobotKey, new: RobotKey) {
+        if let Some(entry) = self.robots.remove(&old) {
+            self.robots.insert(new, entry);
+        }
+        if self.decommissioned.remove(&old) {
+            self.decommissioned.insert(new);
+        }
+    }
+
+    pub fn decommission(&mut self, robot: RobotKey) -> bool {
+        self.robots.contains_key(&robot) && self.decommissioned.insert(robot)
+    }
+
+    pub fn is_decommissioned(&self, robot: RobotKey) -> bool {
+        self.decommissioned.contains(&robot)
+    }
+
+    // Un robot en attente de rééquipement compte déjà dans son nouveau rôle
+    pub fn count(&self, matches: impl Fn(RobotKey, PoolStatus) -> bool) -> usize {
+        self.robots
+            .iter()
+            .filter(|(key, entry)| {
+                let key = match self.refits.get(key) {
+                    Some(replacement) => (replacement.get_type(), key.1),
+                    None => **key,
+                };
+                matches(key, entry.status)
+            })
+            .count()
+    }
+
+    pub fn fleet(&self) -> Vec<FleetEntry> {
+        let mut fleet: Vec<FleetEntry> = self
+            .robots
+            .iter()
+            .map(|(robot, entry)| FleetEntry {
+                robot: *robot,
+                status: entry.status,
+            })
+            .collect();
+        fleet.sort_by_key(|entry| entry.robot.1);
+        fleet
+    }
+
+    // Rejoint les threads terminés et renvoie les robots retirés de la flotte,
+    // chacun avec vrai si son thread a paniqué
+    pub fn reap(&mut self) -> Vec<(RobotKey, bool)> {
+        let mut finished: Vec<RobotKey> = self
+            .robots
+            .iter()
+            .filter(|(_, entry)| entry.handle.is_finished())
+            .map(|(key, _)| *key)
+            .collect();
+        finished.sort_by_key(|(robot_type, id)| (robot_type.name(), *id));
+
+        let mut reaped = Vec::new();
+        for key in finished {
+            if let Some(entry) = self.robots.remove(&key) {
+                reaped.push((key, entry.handle.join().is_err()));
+            }
+            self.assignments.remove(&key);
+            self.refits.remove(&key);
+            self.decommissioned.remove(&key);
+        }
+        reaped
+    }
+
+    // Vide la flotte à l'arrêt de la simulation ; les threads sont rejoints hors du verrou,
+    // puisqu'ils le prennent eux-mêmes jusqu'à leur dernier tick
+    pub fn drain(&mut self) -> Vec<(RobotKey, JoinHandle<()>)> {
+        self.assignments.clear();
+        self.refits.clear();
+        self.decommissioned.clear();
+        self.robots.drain().map(|(key, entry)| (key, entry.handle)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robots::harvester::harvester_kind;
+    use crate::robots::registry::Spawn;
+    use std::thread;
+
+    const EXPLORER: RobotKey = (RobotType::EXPLORER, 0);
+
+    // Thread qui se termine aussitôt, comme celui d'un robot déjà rentré
+    fn finished_thread() -> JoinHandle<()> {
+        let handle = thread::spawn(|| {});
+        while !handle.is_finished() {
+            thread::yield_now();
+        }
+        handle
+    }
+
+    fn harvester(id: usize) -> Box<dyn Robot + Send> {
+        let kind = harvester_kind();
+        (kind.constructor)(&Spawn {
+            robot_type: kind.robot_type,
+            id,
+            position: (0, 0),
+            config: kind.config,
+            behaviour: None,
+        })
+    }
+
+    #[test]
+    fn a_docked_robot_is_offered_until_it_gets_a_mission() {
+        let mut pool = RobotPool::new();
+        pool.insert(EXPLORER, finished_thread());
+        assert_eq!(pool.docked(RobotType::EXPLORER), None);
+
+        pool.set_status(EXPLORER, PoolStatus::Docked);
+        assert_eq!(pool.docked(RobotType::EXPLORER), Some(EXPLORER));
+        assert_eq!(pool.docked(RobotType::HARVESTER), None);
+
+        pool.assign(EXPLORER, Box::new(|_| {}));
+        assert!(!pool.is_available(EXPLORER));
+        assert_eq!(pool.fleet(), vec![FleetEntry { robot: EXPLORER, status: PoolStatus::Active }]);
+        assert!(pool.take_assignment(EXPLORER).is_some());
+        assert!(pool.take_assignment(EXPLORER).is_none());
+    }
+
+    #[test]
+    fn a_refitted_robot_counts_in_its_new_role_and_changes_key() {
+        let mut pool = RobotPool::new();
+        pool.insert(EXPLORER, finished_thread());
+        pool.set_status(EXPLORER, PoolStatus::Docked);
+
+        // En attente de rééquipement, le robot n'est plus proposé et compte déjà comme récolteur
+        pool.refit(EXPLORER, harvester(0));
+        assert_eq!(pool.docked(RobotType::EXPLORER), None);
+        assert_eq!(pool.count(|key, _| key.0 == RobotType::HARVESTER), 1);
+
+        let replacement = pool.take_refit(EXPLORER).unwrap();
+        let new = (replacement.get_type(), replacement.get_id());
+        pool.rekey(EXPLORER, new);
+        assert_eq!(pool.fleet(), vec![FleetEntry { robot: new, status: PoolStatus::Docked }]);
+        assert_eq!(pool.docked(RobotType::HARVESTER), Some(new));
+    }
+
+    #[test]
+    fn a_decommissioned_robot_leaves_the_fleet_once_its_thread_ends() {
+        let mut pool = RobotPool::new();
+        let harvester = (RobotType::HARVESTER, 1);
+        pool.insert(EXPLORER, finished_thread());
+        pool.set_status(EXPLORER, PoolStatus::Docked);
+
+        assert!(!pool.decommission(harvester));
+        assert!(pool.decommission(EXPLORER));
+        assert!(!pool.decommission(EXPLORER));
+        assert_eq!(pool.docked(RobotType::EXPLORER), None);
+        // Le démantèlement suit le robot rééquipé sous sa nouvelle clé
+        pool.rekey(EXPLORER, harvester);
+        assert!(pool.is_decommissioned(harvester));
+
+        assert_eq!(pool.reap(), vec![(harvester, false)]);
+        assert!(pool.fleet().is_empty());
+        assert!(!pool.is_decommissioned(harvester));
+    }
+
+    #[test]
+    fn a_panicked_thread_is_reported_when_reaped() {
+        let mut pool = RobotPool::new();
+        let handle = thread::spawn(|| panic!("robot thread crashed"));
+        while !handle.is_finished() {
+            thread::yield_now();
+        }
+        pool.insert(EXPLORER, handle);
+        // Robot encore en activité jusqu'à l'arrêt de la simulation
+        let (stop, stopped) = std::sync::mpsc::channel::<()>();
+        pool.insert((RobotType::HARVESTER, 1), thread::spawn(move || while stopped.recv().is_ok() {}));
+
+        // Seul le thread terminé est rejoint
+        assert_eq!(pool.reap(), vec![(EXPLORER, true)]);
+        assert_eq!(pool.fleet().len(), 1);
+
+        let robots = pool.drain();
+        assert!(pool.fleet().is_empty());
+        drop(stop);
+        for (_, handle) in robots {
+            handle.join().unwrap();
+        }
+    }
+}
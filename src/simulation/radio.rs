@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::environment::tile::Resource;
+use crate::robots::robot::RobotType;
+
+pub type RobotKey = (RobotType, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageKind {
+    Deposit(usize, usize, Resource),
+    PathBlocked(usize, usize),
+    NeedHelp(usize, usize),
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageKind::Deposit(x, y, resource) => {
+                write!(f, "{:?} deposit at ({}, {})", resource.resource_type, x, y)
+            }
+            MessageKind::PathBlocked(x, y) => write!(f, "path blocked at ({}, {})", x, y),
+            MessageKind::NeedHelp(x, y) => write!(f, "need help at ({}, {})", x, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadioMessage {
+    pub id: u64,
+    pub sender: RobotKey,
+    pub kind: MessageKind,
+    // Nombre de relais déjà traversés
+    pub hops: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RadioConfig {
+    pub range: usize,
+    pub antenna_range: usize,
+    // Ticks de la base nécessaires pour franchir un saut
+    pub latency: u64,
+    // Messages qu'un récepteur peut décoder par tick, le reste attend
+    pub bandwidth: usize,
+    pub max_hops: u32,
+}
+
+impl Default for RadioConfig {
+    fn default() -> Self {
+        RadioConfig {
+            range: 5,
+            antenna_range: 8,
+            latency: 2,
+            bandwidth: 2,
+            max_hops: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Receiver {
+    Base,
+    Robot(RobotKey),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Transmission {
+    message: RadioMessage,
+    receiver: Receiver,
+    arrival: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Radio {
+    pub config: RadioConfig,
+    tick: u64,
+    next_id: u64,
+    base: (usize, usize),
+    antennas: Vec<(usize, usize)>,
+    positions: HashMap<RobotKey, (usize, usize)>,
+    in_flight: VecDeque<Transmission>,
+    inboxes: HashMap<Receiver, Vec<RadioMessage>>,
+    // Un récepteur ne traite chaque message qu'une fois, ce qui arrête les relais en boucle ;
+    // on retient le tick de réception pour oublier les messages qui ne peuvent plus revenir
+    seen: HashMap<Receiver, HashMap<u64, u64>>,
+    pub delivered: usize,
+}
+
+impl Radio {
+    pub fn new(base: (usize, usize)) -> Self {
+        Radio {
+            base,
+            ..Radio::default()
+        }
+    }
+
+    pub fn set_antennas(&mut self, antennas: Vec<(usize, usize)>) {
+        self.antennas = antennas;
+    }
+
+    pub fn update_position(&mut self, robot: RobotKey, position: (usize, usize)) {
+        self.positions.insert(robot, position);
+    }
+
+    pub fn forget(&mut self, robot: RobotKey) {
+        self.positions.remove(&robot);
+        self.inboxes.remove(&Receiver::Robot(robot));
+        self.seen.remove(&Receiver::Robot(robot));
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn broadcast(&mut self, sender: RobotKey, kind: MessageKind) {
+        let message = RadioMessage {
+            id: self.next_id,
+            sender,
+            kind,
+            hops: 0,
+        };
+        self.next_id += 1;
+        self.seen
+            .entry(Receiver::Robot(sender))
+            .or_default()
+            .insert(message.id, self.tick);
+        if let Some(&from) = self.positions.get(&sender) {
+            self.transmit(message, from);
+        }
+    }
+
+    fn transmit(&mut self, message: RadioMessage, from: (usize, usize)) {
+        let arrival = self.tick + self.config.latency;
+        let receivers: Vec<Receiver> = self
+            .positions
+            .iter()
+            .map(|(robot, position)| (Receiver::Robot(*robot), *position))
+            .chain(std::iter::once((Receiver::Base, self.base)))
+            .filter(|(receiver, position)| {
+                !self.has_seen(*receiver, message.id) && self.can_reach(from, *position)
+            })
+            .map(|(receiver, _)| receiver)
+            .collect();
+
+        for receiver in receivers {
+            self.in_flight.push_back(Transmission {
+                message,
+                receiver,
+                arrival,
+            });
+        }
+    }
+
+    // Portée directe, ou via une antenne relais à portée de l'émetteur
+    fn can_reach(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let within = |a: (usize, usize), b: (usize, usize), range: usize| {
+            let (dx, dy) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+            dx * dx + dy * dy <= range * range
+        };
+        within(from, to, self.config.range)
+            || (self
+                .antennas
+                .iter()
+                .any(|antenna| within(from, *antenna, self.config.range))
+                && self
+                    .antennas
+                    .iter()
+                    .any(|antenna| within(*antenna, to, self.config.antenna_range)))
+    }
+
+    fn has_seen(&self, receiver: Receiver, id: u64) -> bool {
+        self.seen.get(&receiver).is_some_and(|seen| seen.contains_key(&id))
+    }
+
+    // Au-delà, plus aucun relais d'un message ne peut encore être en route
+    fn horizon(&self) -> u64 {
+        self.config.latency * (u64::from(self.config.max_hops) + 1)
+    }
+
+    // Oublie les messages plus vieux que l'horizon de relais, sauf ceux dont une copie attend encore
+    // faute de bande passante
+    fn prune_seen(&mut self) {
+        let queued: HashSet<u64> = self.in_flight.iter().map(|transmission| transmission.message.id).collect();
+        let oldest = self.tick.saturating_sub(self.horizon());
+        for seen in self.seen.values_mut() {
+            seen.retain(|id, tick| *tick >= oldest || queued.contains(id));
+        }
+        self.seen.retain(|_, seen| !seen.is_empty());
+    }
+
+    // Fait avancer le temps radio : livraisons dans la limite de la bande passante, puis relais
+    pub fn tick(&mut self) {
+        self.tick += 1;
+        let mut decoded: HashMap<Receiver, usize> = HashMap::new();
+        let mut waiting = VecDeque::new();
+
+        while let Some(transmission) = self.in_flight.pop_front() {
+            let receiver = transmission.receiver;
+            let count = decoded.entry(receiver).or_default();
+            if transmission.arrival > self.tick || *count >= self.config.bandwidth {
+                waiting.push_back(transmission);
+                continue;
+            }
+            if self.has_seen(receiver, transmission.message.id) {
+                continue;
+            }
+            let position = match receiver {
+                Receiver::Base => Some(self.base),
+                Receiver::Robot(robot) => self.positions.get(&robot).copied(),
+            };
+            // Le destinataire a quitté la carte pendant la transmission
+            let Some(position) = position else {
+                continue;
+            };
+
+            *count += 1;
+            self.delivered += 1;
+            self.seen
+                .entry(receiver)
+                .or_default()
+                .insert(transmission.message.id, self.tick);
+            self.inboxes
+                .entry(receiver)
+                .or_default()
+                .push(transmission.message);
+
+            if receiver != Receiver::Base && transmission.message.hops < self.config.max_hops {
+                let relayed = RadioMessage {
+                    hops: transmission.message.hops + 1,
+                    ..transmission.message
+                };
+                // Les relais émis pendant ce tick attendent leur propre délai d'arrivée
+                let before = self.in_flight.len();
+                self.transmit(relayed, position);
+                waiting.extend(self.in_flight.drain(before..));
+            }
+        }
+        self.in_flight = waiting;
+        self.prune_seen();
+    }
+
+    pub fn receive(&mut self, robot: RobotKey) -> Vec<RadioMessage> {
+        self.inboxes
+            .remove(&Receiver::Robot(robot))
+            .unwrap_or_default()
+    }
+
+    pub fn receive_at_base(&mut self) -> Vec<RadioMessage> {
+        self.inboxes.remove(&Receiver::Base).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELAY: RobotKey = (RobotType::EXPLORER, 1);
+    const SENDER: RobotKey = (RobotType::EXPLORER, 2);
+
+    fn radio() -> Radio {
+        let mut radio = Radio::new((0, 0));
+        radio.update_position(RELAY, (1, 1));
+        radio.update_position(SENDER, (2, 2));
+        radio
+    }
+
+    fn remembered(radio: &Radio, receiver: Receiver) -> usize {
+        radio.seen.get(&receiver).map_or(0, HashMap::len)
+    }
+
+    #[test]
+    fn base_forgets_messages_older_than_the_relay_horizon() {
+        let mut radio = radio();
+        for _ in 0..50 {
+            radio.broadcast(SENDER, MessageKind::NeedHelp(2, 2));
+            radio.tick();
+        }
+        // Une fois l'horizon passé, la base ne retient plus que les messages récents
+        let bound = (radio.horizon() as usize + 1) * radio.config.bandwidth;
+        assert!(remembered(&radio, Receiver::Base) <= bound);
+        assert!(remembered(&radio, Receiver::Robot(RELAY)) <= bound);
+
+        // Le trafic écoulé, tout finit par être oublié
+        while radio.in_flight() > 0 {
+            radio.tick();
+        }
+        for _ in 0..=radio.horizon() {
+            radio.tick();
+        }
+        assert!(radio.seen.is_empty());
+    }
+
+    #[test]
+    fn pruned_messages_are_not_delivered_twice() {
+        let mut radio = radio();
+        radio.broadcast(SENDER, MessageKind::PathBlocked(3, 3));
+        let mut delivered = Vec::new();
+        for _ in 0..4 * radio.horizon() {
+            radio.tick();
+            delivered.extend(radio.receive_at_base());
+        }
+
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(radio.in_flight(), 0);
+    }
+}
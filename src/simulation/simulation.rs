@@ -1,43 +1,132 @@
 use crate::environment::map::Map;
-use crate::environment::tile::{MapTile, Resource, TileType};
-use crate::robots::robot::{RobotState, RobotType};
-use crate::robots::{explorer::Explorer, harvester::Harvester, robot::Robot};
+use crate::environment::restriction::RestrictionKind;
+use crate::environment::structure::{Structure, StructureType};
+use crate::environment::tile::{MapTile, Resource, ResourceType, TileType};
+use crate::robots::battery::Battery;
+use crate::robots::behaviour::{BehaviourSource, BehaviourTree};
+use crate::robots::perception::{Action, Perception, Route, UNREACHABLE};
+use crate::robots::modules::refit_cost;
+use crate::robots::registry::{registry, RobotKind, RobotRole, Spawn};
+use crate::robots::reliability::{
+    Breakdown, ReliabilityModel, RepairOrder, RepairStatus, Wear,
+};
+use crate::robots::robot::{Order, ResourceTarget, Robot, RobotState, RobotType};
+use crate::robots::sensor::SensorSpec;
+use crate::robots::trace::{Decision, DecisionTrace};
+use crate::robots::upgrades::{UpgradeKind, UpgradeLevels};
+use crate::simulation::allocation::{TaskAllocator, TaskStatus};
+use crate::simulation::arbiter::{
+    resolve, robot_rng, Arbiter, Claim, Outcome, PRIORITY_DEFAULT, PRIORITY_OPERATOR, PRIORITY_RETURNING,
+    ROUNDS_PER_TICK,
+};
+use crate::simulation::deposits::DepositRegistry;
+use crate::simulation::factory::{Factory, ProductionOrder};
+use crate::simulation::formation::{FormationOrder, Formations, Shape};
+use crate::simulation::motion::Motions;
+use crate::simulation::logistics::Logistics;
+use crate::simulation::pool::{FleetEntry, PoolStatus, RobotPool};
+use crate::simulation::radio::{MessageKind, Radio, RobotKey};
+use crate::simulation::stock::Stock;
+use crate::simulation::teleop::{Teleop, TeleopCommand};
+use crate::simulation::watchdog::{Recovery, Watchdog, STALL_TICKS};
+use crate::simulation::zones::{Area, Zones};
 use crate::windows::utils::open_window;
-use std::collections::{HashMap, VecDeque};
-use std::sync::atomic::{AtomicBool, Ordering};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use std::time::Duration;
 
+const MAX_EVENTS: usize = 50;
+const CHARGING_RATE: u32 = 10;
+const STARTING_ENERGY: u32 = 30;
+const STARTING_MINERALS: u32 = 15;
+// Ticks sans avancer avant de signaler un passage bloqué
+const BLOCKED_TICKS: u32 = 5;
+// Pendant une pause, les robots vérifient à ce rythme si la base a repris
+const PAUSE_POLL: u64 = 100;
+// Secondes de simulation écoulées à chaque tick, quelle que soit la cadence choisie dans l'interface
+const TICK_DURATION: f32 = 1.0;
+
+pub type SensorFootprints = Arc<Mutex<HashMap<(RobotType, usize), Vec<(usize, usize)>>>>;
+pub type PendingFits = Arc<Mutex<HashMap<(RobotType, usize), Vec<UpgradeKind>>>>;
+pub type RunningNodes = Arc<Mutex<HashMap<(RobotType, usize), String>>>;
+pub type DecisionTraces = Arc<Mutex<HashMap<(RobotType, usize), DecisionTrace>>>;
+
 #[derive(Clone)]
 pub struct Simulation {
     pub map: Arc<RwLock<Map>>,
-    pub energy_count: Arc<Mutex<u32>>,
+    pub stock: Arc<Mutex<Stock>>,
+    pub reliability: ReliabilityModel,
+    pub fleet_upgrades: Arc<Mutex<UpgradeLevels>>,
+    pending_fits: PendingFits,
     pub running: Arc<AtomicBool>,
+    // Levé à la fermeture de la fenêtre : la base et les robots quittent leur boucle
+    stopping: Arc<AtomicBool>,
+    // Ticks de la base écoulés pendant que la simulation tourne
+    pub tick: Arc<AtomicU64>,
     pub speed: Arc<Mutex<u64>>,
     frame_count: u64,
     pub fps: f32,
     last_frame_time: std::time::Instant,
-    explorer_threads: Arc<Mutex<HashMap<usize, thread::JoinHandle<()>>>>,
-    harvester_threads: Arc<Mutex<HashMap<usize, thread::JoinHandle<()>>>>,
-    pub located_resources: Arc<Mutex<VecDeque<Vec<(usize, usize, Resource)>>>>,
+    pool: Arc<Mutex<RobotPool>>,
+    pub broken_robots: Arc<Mutex<HashSet<(RobotType, usize)>>>,
+    pub deposits: Arc<Mutex<DepositRegistry>>,
+    pub allocator: Arc<Mutex<TaskAllocator>>,
+    pub factory: Arc<Mutex<Factory>>,
+    pub logistics: Arc<Mutex<Logistics>>,
+    pub sensor_footprints: SensorFootprints,
+    pub running_nodes: RunningNodes,
+    traces: DecisionTraces,
+    pub radio: Arc<Mutex<Radio>>,
+    pub teleop: Arc<Mutex<Teleop>>,
+    pub formations: Arc<Mutex<Formations>>,
+    pub motions: Arc<Mutex<Motions>>,
+    pub zones: Arc<Mutex<Zones>>,
+    arbiter: Arc<Arbiter>,
+    pub events: Arc<Mutex<VecDeque<String>>>,
 }
 
 impl Simulation {
     pub fn new(map_seed: u32) -> Self {
-        let map = Arc::new(RwLock::new(Map::new(25, 25, map_seed)));
+        let map = Map::new(25, 25, map_seed);
+        let radio = Radio::new(map.base_position);
+        let map = Arc::new(RwLock::new(map));
+        let mut stock = Stock::new();
+        stock.add(Resource::new(STARTING_ENERGY, ResourceType::Energy));
+        stock.add(Resource::new(STARTING_MINERALS, ResourceType::Mineral));
 
         Simulation {
             map,
-            energy_count: Arc::new(Mutex::new(0)),
+            stock: Arc::new(Mutex::new(stock)),
+            reliability: ReliabilityModel::default(),
+            fleet_upgrades: Arc::new(Mutex::new(UpgradeLevels::default())),
+            pending_fits: Arc::new(Mutex::new(HashMap::new())),
             speed: Arc::new(Mutex::new(500)),
             running: Arc::new(AtomicBool::new(false)),
+            stopping: Arc::new(AtomicBool::new(false)),
+            tick: Arc::new(AtomicU64::new(0)),
             frame_count: 0,
             fps: 0.0,
             last_frame_time: std::time::Instant::now(),
-            explorer_threads: Arc::new(Mutex::new(HashMap::new())),
-            harvester_threads: Arc::new(Mutex::new(HashMap::new())),
-            located_resources: Arc::new(Mutex::new(VecDeque::new())),
+            pool: Arc::new(Mutex::new(RobotPool::new())),
+            broken_robots: Arc::new(Mutex::new(HashSet::new())),
+            deposits: Arc::new(Mutex::new(DepositRegistry::new())),
+            allocator: Arc::new(Mutex::new(TaskAllocator::new())),
+            factory: Arc::new(Mutex::new(Factory::new())),
+            logistics: Arc::new(Mutex::new(Logistics::new())),
+            sensor_footprints: Arc::new(Mutex::new(HashMap::new())),
+            running_nodes: Arc::new(Mutex::new(HashMap::new())),
+            traces: Arc::new(Mutex::new(HashMap::new())),
+            radio: Arc::new(Mutex::new(radio)),
+            teleop: Arc::new(Mutex::new(Teleop::new())),
+            formations: Arc::new(Mutex::new(Formations::new())),
+            motions: Arc::new(Mutex::new(Motions::new())),
+            zones: Arc::new(Mutex::new(Zones::new())),
+            arbiter: Arc::new(Arbiter::new()),
+            events: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
@@ -50,7 +139,340 @@ impl Simulation {
     }
 
     pub fn run(&mut self) {
+        let base = self.start_base_loop();
         let _ = open_window(self);
+        self.shut_down(base);
+    }
+
+    // La base a son propre thread, cadencé comme les robots
+    fn start_base_loop(&self) -> thread::JoinHandle<()> {
+        let mut self_clone = self.clone();
+        thread::spawn(move || {
+            while !self_clone.stopping.load(Ordering::SeqCst) {
+                let sleep_time = *self_clone.speed.lock().unwrap();
+                if self_clone.running.load(Ordering::SeqCst) {
+                    self_clone.step();
+                }
+                thread::sleep(Duration::from_millis(sleep_time));
+            }
+        })
+    }
+
+    // La base termine son tick puis s'arrête ; chaque robot quitte sa boucle et son thread est rejoint
+    fn shut_down(&mut self, base: thread::JoinHandle<()>) {
+        self.stopping.store(true, Ordering::SeqCst);
+        if base.join().is_err() {
+            self.log_event(String::from("Base thread panicked"));
+        }
+        let robots = self.pool.lock().unwrap().drain();
+        for (key, handle) in robots {
+            if handle.join().is_err() {
+                self.log_event(format!("Thread of {} {} panicked", key.0, key.1));
+            }
+        }
+    }
+
+    // Un tick complet : la base prépare le tick pendant que les robots attendent, puis ils jouent
+    fn step(&mut self) {
+        let tick = self.tick.fetch_add(1, Ordering::SeqCst) + 1;
+        self.reap_robots();
+        self.pheromone_tick();
+        self.radio_tick();
+        self.auction_tick();
+        self.factory_tick();
+        self.logistics_tick();
+        self.arbiter.start_tick(tick);
+        self.resolution_tick();
+    }
+
+    // Phase de résolution : les demandes des robots sont départagées tour par tour, voir arbiter::resolve
+    fn resolution_tick(&mut self) {
+        for _ in 0..ROUNDS_PER_TICK {
+            let (round, claims) = self.arbiter.close_round();
+            let verdicts = {
+                let mut map = self.map.write().unwrap();
+                let seed = map.seed;
+                resolve(&claims, &mut map, seed, round)
+            };
+            self.arbiter.publish(verdicts);
+        }
+        self.arbiter.finish_tick();
+    }
+
+    fn pheromone_tick(&mut self) {
+        if let Some(field) = self.map.write().unwrap().pheromones.as_mut() {
+            field.tick();
+        }
+    }
+
+    fn radio_tick(&mut self) {
+        let antennas = self
+            .map
+            .read()
+            .unwrap()
+            .built_structures()
+            .into_iter()
+            .filter(|structure| structure.kind.relays_radio())
+            .map(|structure| structure.origin)
+            .collect();
+
+        let heard = {
+            let mut radio = self.radio.lock().unwrap();
+            radio.set_antennas(antennas);
+            radio.tick();
+            radio.receive_at_base()
+        };
+
+        // La base a sa propre antenne : les découvertes peuvent lui parvenir sans retour physique
+        for message in heard {
+            let (sender_type, sender_id) = message.sender;
+            self.log_event(format!(
+                "Base heard {} {}: {}",
+                sender_type, sender_id, message.kind
+            ));
+            if let MessageKind::Deposit(x, y, resource) = message.kind {
+                self.report_deposit(x, y, resource, message.sender);
+            }
+        }
+    }
+
+    // Un robot à quai du même type repart avant d'en fabriquer un nouveau
+    pub fn queue_robot(&mut self, robot_type: RobotType) {
+        if self.reassign(robot_type, |_| {}).is_ok() {
+            return;
+        }
+        self.factory
+            .lock()
+            .unwrap()
+            .enqueue(ProductionOrder { robot_type });
+    }
+
+    fn auction_tick(&mut self) {
+        let (awarded, tasks) = {
+            let mut allocator = self.allocator.lock().unwrap();
+            let awarded = allocator.auction();
+            let tasks: Vec<_> = awarded.iter().map(|bid| allocator.task(bid.task)).collect();
+            (awarded, tasks)
+        };
+        let tick = self.tick.load(Ordering::SeqCst);
+        for (bid, task) in awarded.iter().zip(tasks) {
+            self.deposits.lock().unwrap().assign(task.position, bid.bidder, tick);
+            let (bidder_type, bidder_id) = bid.bidder;
+            self.log_event(format!(
+                "Task ({}, {}) awarded to {} {} (cost {})",
+                task.position.0, task.position.1, bidder_type, bidder_id, bid.cost
+            ));
+        }
+    }
+
+    fn factory_tick(&mut self) {
+        let roles = {
+            let registry = registry().read().unwrap();
+            (
+                registry.for_role(RobotRole::Exploration),
+                registry.for_role(RobotRole::Harvesting),
+            )
+        };
+        let (Some(explorer), Some(harvester)) = roles else {
+            return;
+        };
+        let keep_explorers = self.factory.lock().unwrap().policy.keep_explorers;
+        let mut active_explorers =
+            self.count_robots(RobotRole::Exploration, |status| status == PoolStatus::Active);
+        while active_explorers < keep_explorers && self.reassign(explorer, |_| {}).is_ok() {
+            active_explorers += 1;
+        }
+        // Les récolteurs à quai enchérissent sur les tâches : ils font partie de la flotte disponible
+        let mut active_harvesters = self.count_robots(RobotRole::Harvesting, |_| true);
+        let outstanding_tasks = self
+            .allocator
+            .lock()
+            .unwrap()
+            .count(|status| matches!(status, TaskStatus::Open | TaskStatus::Awarded(_)));
+
+        // Les rôles suivent la demande : un robot à quai est rééquipé plutôt que d'en fabriquer un autre
+        let wanted_harvesters = self.factory.lock().unwrap().wanted_harvesters(outstanding_tasks);
+        while active_harvesters < wanted_harvesters
+            && self.refit_docked(RobotRole::Exploration, harvester)
+        {
+            active_harvesters += 1;
+        }
+        // Les explorateurs à quai restants sont ceux en cours de rééquipement
+        active_explorers += self.count_robots(RobotRole::Exploration, |status| status == PoolStatus::Docked);
+        if active_explorers < keep_explorers
+            && active_harvesters > wanted_harvesters
+            && self.refit_docked(RobotRole::Harvesting, explorer)
+        {
+            active_explorers += 1;
+            active_harvesters -= 1;
+        }
+
+        let finished = {
+            let mut factory = self.factory.lock().unwrap();
+            factory.plan((explorer, active_explorers), (harvester, active_harvesters), outstanding_tasks);
+            let mut stock = self.stock.lock().unwrap();
+            factory.advance(&mut stock)
+        };
+
+        let Some(order) = finished else {
+            return;
+        };
+        self.log_event(format!("Factory produced: {}", order.robot_type));
+        self.spawn_robot(order.robot_type, |_| {});
+    }
+
+    // Un dépôt assez rempli reçoit un transporteur, à quai ou construit pour l'occasion
+    fn logistics_tick(&mut self) {
+        let depot = {
+            let mut logistics = self.logistics.lock().unwrap();
+            let Some(depot) = logistics.next_pickup() else {
+                return;
+            };
+            logistics.claim(depot);
+            depot
+        };
+        self.log_event(format!("Carrier sent to depot ({}, {})", depot.0, depot.1));
+        self.send_role(RobotRole::Logistics, move |carrier| {
+            carrier.set_order(Some(Order::Haul(depot.0, depot.1)));
+        });
+    }
+
+    pub fn finish_haul(&mut self, depot: (usize, usize), units: u32) {
+        let mut logistics = self.logistics.lock().unwrap();
+        logistics.release(depot);
+        logistics.hauled += units;
+    }
+
+    // Rééquipe un robot à quai du rôle `from`, du type le moins cher à convertir
+    fn refit_docked(&mut self, from: RobotRole, target: RobotType) -> bool {
+        let candidates: Vec<RobotType> = {
+            let registry = registry().read().unwrap();
+            let Some(target_kind) = registry.get(target) else {
+                return false;
+            };
+            let mut candidates: Vec<(RobotType, u32)> = registry
+                .kinds()
+                .iter()
+                .filter(|kind| kind.role == from && !kind.config.modules.is_empty())
+                .map(|kind| {
+                    let cost = refit_cost(kind.config.modules, target_kind.config.modules);
+                    (kind.robot_type, cost.iter().map(|resource| resource.scale).sum())
+                })
+                .collect();
+            candidates.sort_by_key(|(_, cost)| *cost);
+            candidates.into_iter().map(|(robot_type, _)| robot_type).collect()
+        };
+        let docked = {
+            let pool = self.pool.lock().unwrap();
+            candidates.into_iter().find_map(|robot_type| pool.docked(robot_type))
+        };
+        docked.is_some_and(|robot| self.try_refit(robot, target).is_ok())
+    }
+
+    // Rééquipement demandé depuis l'interface
+    pub fn refit(&mut self, robot: RobotKey, target: RobotType) {
+        if let Err(reason) = self.try_refit(robot, target) {
+            self.log_event(reason);
+        }
+    }
+
+    // Seuls les modules manquants sont payés ; le robot change de type à son prochain passage à quai
+    fn try_refit(&mut self, robot: RobotKey, target: RobotType) -> Result<(), String> {
+        let (robot_type, id) = robot;
+        let (current, kind) = {
+            let registry = registry().read().unwrap();
+            (
+                registry.get(robot_type).map(|kind| kind.config.modules),
+                registry.get(target).cloned(),
+            )
+        };
+        let (Some(current), Some(kind)) = (current, kind) else {
+            return Err(format!("Unknown robot type: {}", target));
+        };
+        if robot_type == target || current.is_empty() || kind.config.modules.is_empty() {
+            return Err(format!("{} {} cannot be refitted as {}", robot_type, id, target));
+        }
+
+        let cost = refit_cost(current, kind.config.modules);
+        let base_position = self.map.read().unwrap().base_position;
+        let mut replacement = self.build_robot(&kind, id, base_position);
+        replacement.set_state(RobotState::Waiting);
+        let policy = self.factory.lock().unwrap().policy;
+        {
+            let mut pool = self.pool.lock().unwrap();
+            if !pool.is_available(robot) {
+                return Err(format!("{} {} must be docked to be refitted", robot_type, id));
+            }
+            let paid = self
+                .stock
+                .lock()
+                .unwrap()
+                .spend_all(&cost, |resource_type| policy.reserve(resource_type));
+            if !paid {
+                return Err(format!("Not enough resources to refit {} {} as {}", robot_type, id, target));
+            }
+            pool.refit(robot, replacement);
+        }
+        self.log_event(format!("{} {} refit as {} ordered", robot_type, id, target));
+        Ok(())
+    }
+
+    fn count_robots(&self, role: RobotRole, matches: fn(PoolStatus) -> bool) -> usize {
+        let registry = registry().read().unwrap();
+        self.pool.lock().unwrap().count(|(robot_type, _), status| {
+            registry.role_of(robot_type) == Some(role) && matches(status)
+        })
+    }
+
+    fn reap_robots(&mut self) {
+        let reaped = self.pool.lock().unwrap().reap();
+        for (robot, panicked) in reaped {
+            self.pending_fits.lock().unwrap().remove(&robot);
+            self.traces.lock().unwrap().remove(&robot);
+            // Un thread qui a paniqué n'a rien rendu : la base oublie le robot à sa place
+            if panicked {
+                self.log_event(format!("Thread of {} {} panicked", robot.0, robot.1));
+                self.forget(robot);
+            }
+        }
+    }
+
+    // Confie une mission à un robot à quai ; rend la mission si aucun n'est disponible
+    fn reassign<F>(&mut self, robot_type: RobotType, call_bak: F) -> Result<(), F>
+    where
+        F: FnOnce(&mut Box<dyn Robot + Send>) + Send + 'static,
+    {
+        let robot = {
+            let mut pool = self.pool.lock().unwrap();
+            let Some(robot) = pool.docked(robot_type) else {
+                return Err(call_bak);
+            };
+            pool.assign(robot, Box::new(call_bak));
+            robot
+        };
+        self.log_event(format!("{} {} reassigned", robot.0, robot.1));
+        Ok(())
+    }
+
+    // Dernières décisions du robot, de la plus récente à la plus ancienne
+    pub fn decisions(&self, robot: RobotKey, count: usize) -> Vec<Decision> {
+        self.traces
+            .lock()
+            .unwrap()
+            .get(&robot)
+            .map(|trace| trace.latest(count))
+            .unwrap_or_default()
+    }
+
+    pub fn fleet(&self) -> Vec<FleetEntry> {
+        self.pool.lock().unwrap().fleet()
+    }
+
+    // Le robot quitte la flotte dès qu'il est à quai
+    pub fn decommission(&mut self, robot: RobotKey) {
+        if self.pool.lock().unwrap().decommission(robot) {
+            self.log_event(format!("{} {} scheduled for decommissioning", robot.0, robot.1));
+        }
     }
 
     pub fn compute_fps(&mut self) {
@@ -78,122 +500,1307 @@ impl Simulation {
         }
     }
 
+    pub fn log_event(&self, event: String) {
+        let mut events = self.events.lock().unwrap();
+        events.push_back(event);
+        if events.len() > MAX_EVENTS {
+            events.pop_front();
+        }
+    }
+
+    pub fn send_builder(&mut self, kind: StructureType) {
+        let site = {
+            let mut map = self.map.write().unwrap();
+            let Some((site_x, site_y)) = map.find_build_site(kind) else {
+                drop(map);
+                self.log_event(format!("No valid site for {}", kind));
+                return;
+            };
+
+            if !self
+                .stock
+                .lock()
+                .unwrap()
+                .spend(ResourceType::Mineral, kind.mineral_cost())
+            {
+                drop(map);
+                self.log_event(format!(
+                    "Not enough minerals for {} ({} needed)",
+                    kind,
+                    kind.mineral_cost()
+                ));
+                return;
+            }
+
+            map.place_structure(Structure::new(kind, (site_x, site_y)));
+            (site_x, site_y)
+        };
+
+        self.log_event(format!("{} ordered at ({}, {})", kind, site.0, site.1));
+        self.send_role(RobotRole::Construction, move |builder| {
+            builder.set_order(Some(Order::Build(kind, site.0, site.1)));
+        });
+    }
+
+    // Envoie un robot du type que le registre associe au rôle
+    fn send_role<F>(&mut self, role: RobotRole, call_bak: F)
+    where
+        F: FnOnce(&mut Box<dyn Robot + Send>) + Send + 'static,
+    {
+        let robot_type = registry().read().unwrap().for_role(role);
+        match robot_type {
+            Some(robot_type) => self.send_robot(robot_type, call_bak),
+            None => self.log_event(format!("No robot type registered for {:?}", role)),
+        }
+    }
+
     pub fn send_robot<F>(&mut self, robot_type: RobotType, call_bak: F)
+    where
+        F: FnOnce(&mut Box<dyn Robot + Send>) + Send + 'static,
+    {
+        if let Err(call_bak) = self.reassign(robot_type, call_bak) {
+            self.spawn_robot(robot_type, call_bak);
+        }
+    }
+
+    fn spawn_robot<F>(&mut self, robot_type: RobotType, call_bak: F)
     where
         F: FnOnce(&mut Box<dyn Robot + Send>) + Send + 'static,
     {
         let map_guard = self.map.read().unwrap();
         let base_pos = map_guard.base_position;
+        let base_seed = map_guard.seed;
         drop(map_guard);
 
-        let mut robot: Box<dyn Robot + Send> = match robot_type {
-            RobotType::Explorer => Box::new(Explorer::new(
-                base_pos.0,
-                base_pos.1,
-                self.explorer_threads.lock().unwrap().len(),
-            )),
-            RobotType::Harvester => Box::new(Harvester::new(
-                base_pos.0,
-                base_pos.1,
-                self.harvester_threads.lock().unwrap().len(),
-            )),
+        let Some(kind) = registry().read().unwrap().get(robot_type).cloned() else {
+            self.log_event(format!("Unknown robot type: {}", robot_type));
+            return;
         };
+        let robot_id = self.pool.lock().unwrap().next_id();
+        let mut config = kind.config;
+        let mut role = kind.role;
+        let mut robot = self.build_robot(&kind, robot_id, base_pos);
 
-        if robot_type == RobotType::Harvester {
-            call_bak(&mut robot);
-        }
+        call_bak(&mut robot);
+
+        let mut upgrades = *self.fleet_upgrades.lock().unwrap();
+
+        let mut key = (robot_type, robot_id);
+        let mut seat = self.arbiter.register(key);
 
         let map = Arc::clone(&self.map);
-        let running = Arc::clone(&self.running);
-        let speed = Arc::clone(&self.speed);
+        let arbiter = Arc::clone(&self.arbiter);
+        let stopping = Arc::clone(&self.stopping);
+        let sensor_footprints = Arc::clone(&self.sensor_footprints);
+        let running_nodes = Arc::clone(&self.running_nodes);
+        let radio = Arc::clone(&self.radio);
+        let teleop = Arc::clone(&self.teleop);
+        let formations = Arc::clone(&self.formations);
+        let motions = Arc::clone(&self.motions);
+        let zones = Arc::clone(&self.zones);
+        let traces = Arc::clone(&self.traces);
+        let reliability = self.reliability;
         let mut self_clone = self.clone();
-        let robot_id = robot.get_id();
-        let thread_handle = thread::spawn(move || loop {
-            if robot.get_state() == RobotState::Reporting {
-                self_clone.robot_came_back(&mut robot);
-            }
-            let sleep_time = {
-                let speed = speed.lock().unwrap();
-                *speed
-            };
-            if !running.load(Ordering::SeqCst) {
-                thread::sleep(Duration::from_millis(sleep_time));
-                continue;
-            }
+        let mut breakdown_rng = ReliabilityModel::rng_for(base_seed, robot_type, robot_id);
+        let thread_handle = thread::spawn(move || {
+            let mut wear = Wear::default();
+            let mut breakdown: Option<Breakdown> = None;
+            let mut battery = config
+                .battery_capacity
+                .map(|capacity| Battery::new(capacity + upgrades.battery_bonus()));
+            let mut recharge_resume: Option<RobotState> = None;
+            // Horloge du robot, en ticks : date à laquelle il peut commencer son prochain pas
+            let mut ready_at = 0.0;
+            let mut blocked_ticks = 0;
+            // Une cible inaccessible sous contraintes n'est signalée qu'une fois
+            let mut constrained = false;
+            let mut watchdog = Watchdog::default();
+            // Résultat d'un balayage, joint aux perceptions jusqu'à ce que le robot reprenne la main
+            let mut scanned: Vec<MapTile> = Vec::new();
+            let mut seen_tick = 0;
+            while !stopping.load(Ordering::SeqCst) {
+                // Le robot avance au rythme des ticks de la base, qui s'arrêtent pendant une pause
+                let Some(current_tick) = arbiter.wait_tick(key, seen_tick, Duration::from_millis(PAUSE_POLL)) else {
+                    continue;
+                };
+                seen_tick = current_tick;
 
-            let mut map_guard = map.write().unwrap();
-            robot.update(&mut map_guard);
-            drop(map_guard);
+                if robot.get_state() == RobotState::Reporting {
+                    match recharge_resume.take() {
+                        Some(resume_state) => self_clone.dock_for_recharge(&mut robot, resume_state),
+                        None => self_clone.robot_came_back(&mut robot),
+                    }
+                    if robot.get_state() != RobotState::Idle {
+                        self_clone.fit_pending_upgrades(&mut robot, &mut upgrades);
+                        if let (Some(battery), Some(capacity)) =
+                            (battery.as_mut(), config.battery_capacity)
+                        {
+                            battery.set_capacity(capacity + upgrades.battery_bonus());
+                            battery.refill();
+                        }
+                    }
+                }
+                // Un robot déjà à quai n'attend pas de repartir pour recevoir ses améliorations
+                if robot.get_state() == RobotState::Waiting
+                    && self_clone.fit_pending_upgrades(&mut robot, &mut upgrades)
+                {
+                    if let (Some(battery), Some(capacity)) = (battery.as_mut(), config.battery_capacity) {
+                        battery.set_capacity(capacity + upgrades.battery_bonus());
+                        battery.refill();
+                    }
+                }
+                if robot.get_state() == RobotState::Waiting {
+                    self_clone.follow_pool(&mut robot);
+                    // Rééquipé à quai : le thread adopte la configuration du nouveau rôle
+                    if robot.get_type() != key.0 {
+                        key = (robot.get_type(), robot.get_id());
+                        seat.moved_to(key);
+                        if let Some(kind) = registry().read().unwrap().get(key.0) {
+                            config = kind.config;
+                            role = kind.role;
+                        }
+                        battery = config
+                            .battery_capacity
+                            .map(|capacity| Battery::new(capacity + upgrades.battery_bonus()));
+                        ready_at = 0.0;
+                    }
+                }
+                if robot.get_state() == RobotState::Idle {
+                    break;
+                }
+                if breakdown.is_none() && role == RobotRole::Harvesting {
+                    self_clone.follow_allocation(&mut robot);
+                }
+                // Un robot à quai ne bouge pas, ne s'use pas et ne consomme rien
+                if robot.get_state() == RobotState::Waiting {
+                    arbiter.sit_out(key);
+                    continue;
+                }
+
+                if let Some(current) = breakdown.as_mut() {
+                    if self_clone.follow_repair(&mut robot, current) {
+                        wear = Wear::default();
+                        breakdown = None;
+                        if let Some(battery) = battery.as_mut() {
+                            battery.refill();
+                        }
+                    }
+                    arbiter.sit_out(key);
+                    continue;
+                }
+
+                // Chaque pas coûte le temps de traverser la tuile d'arrivée à la vitesse du robot et avance son horloge ;
+                // un pas plus long que le reste du tick (robot lent, sol accidenté) déborde sur les ticks suivants
+                let speed = config.speed * upgrades.speed_factor();
+                let tick_length = Duration::from_millis(*self_clone.speed.lock().unwrap());
+                let tick_start = current_tick as f64 * TICK_DURATION as f64;
+                let tick_end = tick_start + TICK_DURATION as f64;
+                // Un robot resté immobile n'a pas pris d'avance
+                ready_at = f64::max(ready_at, tick_start);
+
+                let sensor = SensorSpec {
+                    radius: config.sensor.radius + upgrades.sensor_bonus(),
+                    ..config.sensor
+                };
+                let position = robot.get_position();
+                let state = robot.get_state();
+                let mut rng = robot_rng(base_seed, current_tick, key);
+                let sensed = sensor.sense(position, &map.read().unwrap(), &mut rng);
+                let seen: Vec<(usize, usize)> = sensed.iter().map(|tile| (tile.x, tile.y)).collect();
+                if zones.lock().unwrap().cover(key, &seen) {
+                    self_clone.log_event(format!("{} {} finished surveying its zone", key.0, key.1));
+                }
+                let inputs = describe_inputs(&sensed, battery.as_ref(), blocked_ticks);
+                let controlled = teleop.lock().unwrap().controlled() == Some(key);
+                let mut messages = radio.lock().unwrap().receive(key);
+                let mut actions = Vec::new();
+                let mut moved = 0;
+                let mut in_formation = false;
+                let mut steps = 0;
+                let mut route = None;
+                let mut worked = false;
+                while ready_at < tick_end {
+                    // Le pas est joué au tour qui couvre l'horloge du robot, et jamais avant son prochain tour
+                    let slot = ((ready_at - tick_start) / TICK_DURATION as f64 * ROUNDS_PER_TICK as f64) as usize;
+                    let round = arbiter.next_round(key);
+                    if slot.max(round) >= ROUNDS_PER_TICK {
+                        break;
+                    }
+                    if slot > round {
+                        arbiter.wait_round(key, slot);
+                    }
+                    let round_start = tick_start + slot.max(round) as f64 * TICK_DURATION as f64 / ROUNDS_PER_TICK as f64;
+                    ready_at = f64::max(ready_at, round_start);
+                    steps += 1;
+                    let before = robot.get_position();
+                    let mut tiles = sensed.clone();
+                    tiles.extend(scanned.iter().copied());
+                    let mut perception =
+                        Perception::new(&map.read().unwrap(), key.0, before, tiles, std::mem::take(&mut messages))
+                            .with_rng(StdRng::from_rng(&mut rng));
+                    let formation = match controlled {
+                        true => None,
+                        false => formations.lock().unwrap().order(key),
+                    };
+                    in_formation |= formation.is_some();
+                    let action = match (controlled, formation) {
+                        (true, _) => {
+                            let command = teleop.lock().unwrap().next_command(key);
+                            command.and_then(|command| {
+                                robot.note(format!("operator command {:?}", command));
+                                self_clone.teleop_action(robot.as_ref(), command, &perception)
+                            })
+                        }
+                        (false, Some(order)) => Some(self_clone.formation_action(&mut robot, order, &perception)),
+                        (false, None) => {
+                            scanned.clear();
+                            perception.waypoint = zones.lock().unwrap().waypoint(key, before);
+                            perception.reserved = self_clone.allocator.lock().unwrap().awarded_to_others(key);
+                            Some(robot.update(&perception))
+                        }
+                    };
+                    if let Some(action) = action {
+                        let name = format!("{} {}", robot.get_type(), robot.get_id());
+                        let priority = match (controlled, robot.get_state()) {
+                            (true, _) => PRIORITY_OPERATOR,
+                            (false, RobotState::ReturningToBase) => PRIORITY_RETURNING,
+                            _ => PRIORITY_DEFAULT,
+                        };
+                        let result = self_clone.perform(&mut robot, action, priority, &sensor, &mut scanned, &mut rng);
+                        worked |= result.is_ok()
+                            && matches!(
+                                action,
+                                Action::Harvest(..) | Action::Build(..) | Action::Unload(..) | Action::Pickup(..)
+                            );
+                        match result {
+                            Ok(outcome) if controlled => self_clone.log_event(format!("{} {}", name, outcome)),
+                            Ok(_) => {}
+                            Err(reason) if controlled => {
+                                self_clone.log_event(format!("{} cannot {}: {}", name, action, reason))
+                            }
+                            Err(reason) => robot.note(format!("{} rejected: {}", action, reason)),
+                        }
+                        actions.push(action.to_string());
+                    }
+                    route = perception.last_route().or(route);
+                    let (x, y) = robot.get_position();
+                    formations.lock().unwrap().report(key, (x, y));
+                    if let (Some(kind), Some(field)) = (robot.pheromone(), map.write().unwrap().pheromones.as_mut()) {
+                        field.deposit(kind, x, y);
+                    }
+                    moved += (x.abs_diff(before.0) + y.abs_diff(before.1)) as u32;
+                    let duration = match (x, y) == before {
+                        true => 1.0 / speed,
+                        false => map.read().unwrap().travel_cost(x, y) / speed,
+                    };
+                    ready_at += duration as f64;
+                    if (x, y) != before {
+                        let animation = tick_length.mul_f32(duration / TICK_DURATION);
+                        motions.lock().unwrap().record(key, before, (x, y), animation);
+                    }
+                }
+                for message in robot.take_messages() {
+                    self_clone.log_event(message);
+                }
+                let mut reasons = robot.take_notes();
+                if steps == 0 {
+                    reasons.push(String::from("still travelling"));
+                }
+                let unreachable = reasons.iter().find(|reason| reason.starts_with(UNREACHABLE));
+                if let (Some(reason), false) = (unreachable, constrained) {
+                    self_clone.log_event(format!("{} {} {}", key.0, key.1, reason));
+                }
+                if steps > 0 {
+                    constrained = unreachable.is_some();
+                }
+                let intents = match actions.is_empty() {
+                    true => String::from("nothing"),
+                    false => actions.join(", "),
+                };
+                let action = match (controlled, in_formation) {
+                    (true, _) => format!("teleoperated ({})", intents),
+                    (false, true) => format!("in formation ({})", intents),
+                    (false, false) => format!(
+                        "{} ({})",
+                        robot
+                            .running_node()
+                            .unwrap_or_else(|| format!("{:?}", robot.get_state())),
+                        intents
+                    ),
+                };
+                let moving = steps > 0
+                    && !controlled
+                    && !in_formation
+                    && matches!(
+                        robot.get_state(),
+                        RobotState::Exploring | RobotState::Harvesting | RobotState::ReturningToBase
+                    );
+                blocked_ticks = match moved == 0 && moving {
+                    true => blocked_ticks + 1,
+                    false => 0,
+                };
+                {
+                    let mut radio = radio.lock().unwrap();
+                    radio.update_position(key, robot.get_position());
+                    if blocked_ticks == BLOCKED_TICKS {
+                        let (x, y) = robot.get_position();
+                        radio.broadcast(key, MessageKind::PathBlocked(x, y));
+                    }
+                }
+                let (x, y) = robot.get_position();
+                let map_guard = map.read().unwrap();
+                let footprint = sensor.footprint((x, y), &map_guard);
+                let near_charger = map_guard.is_near_charger(x, y);
+                let (base_x, base_y) = map_guard.base_position;
+                drop(map_guard);
+                if robot.get_state() != RobotState::Idle {
+                    sensor_footprints.lock().unwrap().insert(key, footprint);
+                    let mut running_nodes = running_nodes.lock().unwrap();
+                    match robot.running_node() {
+                        Some(node) => running_nodes.insert(key, node),
+                        None => running_nodes.remove(&key),
+                    };
+                }
+
+                wear.record(moved);
+                if let Some(battery) = battery.as_mut() {
+                    battery.drain(moved);
+                    if near_charger {
+                        battery.recharge(CHARGING_RATE);
+                    }
+                    let state = robot.get_state();
+                    if battery.is_empty() && state != RobotState::Idle {
+                        self_clone.log_event(format!(
+                            "{} {} ran out of power",
+                            robot.get_type(),
+                            robot.get_id()
+                        ));
+                        reasons.push(String::from("out of power"));
+                        breakdown = Some(self_clone.break_down(&mut robot, &wear));
+                    } else if recharge_resume.is_none()
+                        && matches!(state, RobotState::Exploring | RobotState::Harvesting)
+                        && battery.needs_return(x.abs_diff(base_x) + y.abs_diff(base_y))
+                    {
+                        recharge_resume = Some(state);
+                        formations.lock().unwrap().leave(key);
+                        reasons.push(String::from("battery low, returning to recharge"));
+                        robot.set_state(RobotState::ReturningToBase);
+                    }
+                }
+
+                if breakdown.is_none()
+                    && config.breaks_down
+                    && robot.get_state() != RobotState::Idle
+                    && breakdown_rng.random_bool(reliability.breakdown_probability(&wear))
+                {
+                    reasons.push(String::from("worn out, broke down"));
+                    breakdown = Some(self_clone.break_down(&mut robot, &wear));
+                }
+
+                // Le chien de garde ne juge que les robots autonomes qui ont eu le temps d'agir
+                if breakdown.is_none() && steps > 0 && !controlled && !in_formation {
+                    let recovery = watchdog.observe(route, robot.get_position(), worked);
+                    if let (Some(recovery), Some((goal, _))) = (recovery, route) {
+                        reasons.push(format!("no progress towards ({}, {}), {}", goal.0, goal.1, recovery));
+                        self_clone.recover(&mut robot, recovery, goal);
+                        // Rappelé à quai, le robot n'a plus de retour pour recharge en cours
+                        if robot.get_state() == RobotState::Waiting {
+                            recharge_resume = None;
+                        }
+                    }
+                }
 
-            thread::sleep(Duration::from_millis(sleep_time));
-            if robot.get_state() == RobotState::Idle {
-                break;
+                let reason = match reasons.is_empty() {
+                    true => String::from("nothing new"),
+                    false => reasons.join("; "),
+                };
+                traces.lock().unwrap().entry(key).or_default().record(Decision {
+                    tick: current_tick,
+                    state,
+                    position,
+                    inputs,
+                    action,
+                    reason,
+                });
+
+                if robot.get_state() == RobotState::Idle {
+                    break;
+                }
+                arbiter.sit_out(key);
             }
+            drop(seat);
         });
 
-        match robot_type {
-            RobotType::Explorer => {
-                self.frame_count += 1;
-                let mut explorer_threads = self.explorer_threads.lock().unwrap();
-                explorer_threads.insert(robot_id, thread_handle);
+        self.pool
+            .lock()
+            .unwrap()
+            .insert((robot_type, robot_id), thread_handle);
+    }
+
+    // Nouveau robot à la base, avec les améliorations de la flotte
+    fn build_robot(&self, kind: &RobotKind, id: usize, position: (usize, usize)) -> Box<dyn Robot + Send> {
+        let behaviour = kind
+            .behaviour
+            .map(|source| Arc::new(self.load_behaviour(&source)));
+        let mut robot = (kind.constructor)(&Spawn {
+            robot_type: kind.robot_type,
+            id,
+            position,
+            config: kind.config,
+            behaviour,
+        });
+        robot.apply_upgrades(&self.fleet_upgrades.lock().unwrap());
+        robot
+    }
+
+    // Un fichier d'arbre invalide ne bloque pas l'envoi : on retombe sur l'arbre embarqué
+    fn load_behaviour(&self, source: &BehaviourSource) -> BehaviourTree {
+        source.load().unwrap_or_else(|error| {
+            self.log_event(format!(
+                "Invalid behaviour {}: {}, using built-in tree",
+                source.file, error
+            ));
+            source.load_builtin()
+        })
+    }
+
+    fn robot_came_back(&mut self, robot: &mut Box<dyn Robot + Send>) {
+        let handler = registry()
+            .read()
+            .unwrap()
+            .get(robot.get_type())
+            .map(|kind| kind.on_arrival);
+        match handler {
+            Some(on_arrival) => on_arrival(self, robot),
+            None => self.join_thread(robot),
+        }
+    }
+
+    // Un gisement déjà suivi, même épuisé ou perdu, ne redevient pas une tâche
+    pub fn report_deposit(&mut self, res_x: usize, res_y: usize, resource: Resource, reporter: RobotKey) {
+        let tick = self.tick.load(Ordering::SeqCst);
+        if self.deposits.lock().unwrap().discover((res_x, res_y), resource, reporter, tick) {
+            self.allocator
+                .lock()
+                .unwrap()
+                .add_task((res_x, res_y), resource);
+        }
+    }
+
+    // Remet en jeu la tâche du robot ; renvoie la position du gisement s'il en avait une
+    fn release_task(&self, robot: RobotKey) -> Option<(usize, usize)> {
+        let released = self.allocator.lock().unwrap().release(robot);
+        if let Some(position) = released {
+            self.deposits
+                .lock()
+                .unwrap()
+                .release(position, self.tick.load(Ordering::SeqCst));
+        }
+        released
+    }
+
+    // Un récolteur sans tâche enchérit sur les gisements annoncés ; une tâche remportée devient sa cible
+    fn follow_allocation(&mut self, robot: &mut Box<dyn Robot + Send>) {
+        let key = (robot.get_type(), robot.get_id());
+
+        if let Some(target) = robot.get_current_resource() {
+            if target.remaining {
+                return;
             }
-            RobotType::Harvester => {
-                let mut harvester_threads = self.harvester_threads.lock().unwrap();
-                harvester_threads.insert(robot_id, thread_handle);
+            let position = (target.x, target.y);
+            robot.set_order(None);
+            // Le récolteur a pu croire à tort le gisement disparu : s'il est encore là, il est annoncé de nouveau
+            let still_there = matches!(
+                self.map.read().unwrap().get(position.0, position.1).tile,
+                TileType::Resource(_)
+            );
+            match still_there {
+                true => {
+                    self.release_task(key);
+                    self.log_event(format!(
+                        "Task ({}, {}) back to auction, {} {} lost track of it",
+                        position.0, position.1, key.0, key.1
+                    ));
+                }
+                false => {
+                    self.allocator.lock().unwrap().complete(position);
+                    self.deposits
+                        .lock()
+                        .unwrap()
+                        .finish(position, key, self.tick.load(Ordering::SeqCst));
+                }
+            }
+        }
+
+        let (award, announced) = {
+            let mut allocator = self.allocator.lock().unwrap();
+            (allocator.take_award(key), allocator.announced())
+        };
+        match award {
+            Some(task) => {
+                let (x, y) = task.position;
+                robot.set_order(Some(Order::Harvest(ResourceTarget::new(x, y, task.resource))));
+                if robot.get_state() == RobotState::Waiting {
+                    robot.set_state(RobotState::Harvesting);
+                    self.pool.lock().unwrap().set_status(key, PoolStatus::Active);
+                }
+            }
+            None if announced.is_empty() => {}
+            None => {
+                // Le robot chiffre lui-même chaque tâche, sur la carte telle qu'il la connaît
+                let positions: Vec<(usize, usize)> = announced.iter().map(|(_, position)| *position).collect();
+                let perception = Perception::new(
+                    &self.map.read().unwrap(),
+                    key.0,
+                    robot.get_position(),
+                    Vec::new(),
+                    Vec::new(),
+                );
+                let offers = announced
+                    .iter()
+                    .zip(robot.bid(&perception, &positions))
+                    .filter_map(|((task, _), cost)| Some((*task, cost?)))
+                    .collect();
+                self.allocator.lock().unwrap().bid(key, offers);
             }
         }
     }
 
-    fn robot_came_back(&mut self, robot: &mut Box<dyn Robot + Send>) {
-        let mut self_clone = self.clone();
-        match robot.get_type() {
-            RobotType::Explorer => {
-                let found_resource = robot.get_current_resource();
-                if let Some((res_x, res_y, resource, _)) = found_resource {
-                    let mut located_resources = self.located_resources.lock().unwrap();
-                    let resource_exists = located_resources.iter().any(|resources| {
-                        resources.iter().any(|(x, y, _)| *x == res_x && *y == res_y)
-                    });
-                    if !resource_exists {
-                        located_resources.push_back(vec![(res_x, res_y, resource.clone())]);
-                        self_clone.send_robot(RobotType::Harvester, move |harvester| {
-                            harvester.set_target_resource(Some((
-                                res_x,
-                                res_y,
-                                resource,
-                                true,
-                            )));
-                        });
-                    }
-                }
-                self.join_thread(robot);
-            }
-            RobotType::Harvester => {
-                let target = robot.get_current_resource();
-                if let Some((_, _, resource, remind)) = target {
-                    match resource.resource_type {
-                        crate::environment::tile::ResourceType::Energy => {
-                            let mut energy_count = self_clone.energy_count.lock().unwrap();
-                            *energy_count += resource.scale;
+    // Le robot rentre dans la base et libère sa case, en attendant une nouvelle tâche
+    pub fn dock(&mut self, robot: &mut Box<dyn Robot + Send>) {
+        let (x, y) = robot.get_position();
+        let (base_x, base_y) = {
+            let mut map = self.map.write().unwrap();
+            map.vacate(x, y);
+            map.base_position
+        };
+        robot.set_position(base_x, base_y);
+        let key = (robot.get_type(), robot.get_id());
+        self.sensor_footprints.lock().unwrap().remove(&key);
+        self.running_nodes.lock().unwrap().remove(&key);
+        self.radio.lock().unwrap().forget(key);
+        self.teleop.lock().unwrap().forget(key);
+        self.formations.lock().unwrap().leave(key);
+        self.motions.lock().unwrap().forget(key);
+        let mut pool = self.pool.lock().unwrap();
+        pool.set_status(key, PoolStatus::Docked);
+        // Une zone ou une ronde en cours renvoie l'explorateur dès le tick suivant
+        if self.zones.lock().unwrap().missions().contains_key(&key) && pool.is_available(key) {
+            pool.assign(key, Box::new(|_| {}));
+        }
+        drop(pool);
+        robot.set_state(RobotState::Waiting);
+    }
+
+    pub fn take_control(&mut self, robot: RobotKey) {
+        let docked = self
+            .fleet()
+            .iter()
+            .any(|entry| entry.robot == robot && entry.status == PoolStatus::Docked);
+        if docked {
+            self.log_event(format!("{} {} is docked and cannot be driven", robot.0, robot.1));
+            return;
+        }
+        self.teleop.lock().unwrap().take_control(robot);
+        self.log_event(format!("Operator took control of {} {}", robot.0, robot.1));
+    }
+
+    pub fn release_control(&mut self) {
+        if let Some((robot_type, id)) = self.teleop.lock().unwrap().release() {
+            self.log_event(format!("{} {} back to autonomous mode", robot_type, id));
+        }
+    }
+
+    pub fn send_teleop(&mut self, command: TeleopCommand) {
+        self.teleop.lock().unwrap().push(command);
+    }
+
+    // Ordre de l'opérateur traduit en intention, vérifiée ensuite comme celles des robots autonomes
+    fn teleop_action(
+        &self,
+        robot: &(dyn Robot + Send),
+        command: TeleopCommand,
+        perception: &Perception,
+    ) -> Option<Action> {
+        let (x, y) = robot.get_position();
+        let name = format!("{} {}", robot.get_type(), robot.get_id());
+        match command {
+            TeleopCommand::Move(dx, dy) => {
+                let (next_x, next_y) = (x as isize + dx, y as isize + dy);
+                if next_x < 0 || next_y < 0 {
+                    self.log_event(format!("{} cannot move to ({}, {})", name, next_x, next_y));
+                    return None;
+                }
+                Some(Action::Move(next_x as usize, next_y as usize))
+            }
+            TeleopCommand::Harvest => match perception.adjacent_deposit() {
+                Some((deposit_x, deposit_y)) => Some(Action::Harvest(deposit_x, deposit_y)),
+                None => {
+                    self.log_event(format!("{} has nothing to harvest", name));
+                    None
+                }
+            },
+            TeleopCommand::Scan => Some(Action::Scan),
+        }
+    }
+
+    // Groupe qui se déplace en formation derrière son premier membre, le meneur
+    pub fn order_formation(&mut self, members: Vec<RobotKey>, shape: Shape, destination: (usize, usize)) {
+        let (width, height) = {
+            let map = self.map.read().unwrap();
+            (map.width, map.height)
+        };
+        if destination.0 >= width || destination.1 >= height {
+            self.log_event(format!(
+                "Formation destination ({}, {}) is off the map",
+                destination.0, destination.1
+            ));
+            return;
+        }
+        let fleet = self.fleet();
+        let broken = self.broken_robots.lock().unwrap().clone();
+        let mut group: Vec<RobotKey> = Vec::new();
+        for member in members {
+            let active = fleet
+                .iter()
+                .any(|entry| entry.robot == member && entry.status == PoolStatus::Active);
+            if active && !broken.contains(&member) && !group.contains(&member) {
+                group.push(member);
+            }
+        }
+        if group.len() < 2 {
+            self.log_event(String::from("A formation needs at least two active robots"));
+            return;
+        }
+        let (leader_type, leader_id) = group[0];
+        let count = group.len();
+        let id = self.formations.lock().unwrap().create(shape, group, destination);
+        self.log_event(format!(
+            "Formation {}: {} of {} led by {} {} to ({}, {})",
+            id, shape, count, leader_type, leader_id, destination.0, destination.1
+        ));
+    }
+
+    // Zone à couvrir tracée par l'opérateur ; seules les tuiles franchissables restent à voir
+    pub fn assign_survey(&mut self, robot: RobotKey, area: Area) {
+        let tiles: HashSet<(usize, usize)> = {
+            let map = self.map.read().unwrap();
+            self.area_tiles(&area)
+                .into_iter()
+                .filter(|&(x, y)| !matches!(map.get(x, y).tile, TileType::Terrain))
+                .collect()
+        };
+        if tiles.is_empty() {
+            self.log_event(String::from("The zone has nothing left to survey"));
+            return;
+        }
+        if !self.dispatch_explorer(robot) {
+            return;
+        }
+        let count = tiles.len();
+        self.zones.lock().unwrap().survey(robot, area, tiles);
+        self.log_event(format!("{} {} surveying a zone of {} tiles", robot.0, robot.1, count));
+    }
+
+    fn area_tiles(&self, area: &Area) -> HashSet<(usize, usize)> {
+        let map = self.map.read().unwrap();
+        (0..map.height)
+            .flat_map(|y| (0..map.width).map(move |x| (x, y)))
+            .filter(|&tile| area.contains(tile))
+            .collect()
+    }
+
+    // Zone interdite ou à éviter, pour toute la flotte ou un seul type de robot
+    pub fn restrict(&mut self, kind: RestrictionKind, robot_type: Option<RobotType>, tiles: HashSet<(usize, usize)>) {
+        if tiles.is_empty() {
+            return;
+        }
+        let count = tiles.len();
+        self.map.write().unwrap().restrictions.add(kind, robot_type, tiles);
+        let robots = robot_type.map_or(String::from("all robots"), |robot_type| robot_type.to_string());
+        self.log_event(format!("{} area of {} tiles for {}", kind, count, robots));
+    }
+
+    pub fn restrict_area(&mut self, kind: RestrictionKind, robot_type: Option<RobotType>, area: &Area) {
+        let tiles = self.area_tiles(area);
+        self.restrict(kind, robot_type, tiles);
+    }
+
+    pub fn clear_restrictions(&mut self) {
+        let cleared = self.map.write().unwrap().restrictions.clear();
+        if cleared > 0 {
+            self.log_event(format!("{} path constraints lifted", cleared));
+        }
+    }
+
+    // Ronde entre les points tracés, jusqu'au rappel du robot
+    pub fn assign_patrol(&mut self, robot: RobotKey, waypoints: Vec<(usize, usize)>) {
+        let (width, height) = {
+            let map = self.map.read().unwrap();
+            (map.width, map.height)
+        };
+        if waypoints.is_empty() || waypoints.iter().any(|&(x, y)| x >= width || y >= height) {
+            self.log_event(String::from("Patrol points must be on the map"));
+            return;
+        }
+        if !self.dispatch_explorer(robot) {
+            return;
+        }
+        let count = waypoints.len();
+        self.zones.lock().unwrap().patrol(robot, waypoints);
+        self.log_event(format!("{} {} patrolling {} points", robot.0, robot.1, count));
+    }
+
+    pub fn clear_zone(&mut self, robot: RobotKey) {
+        if self.zones.lock().unwrap().clear(robot) {
+            self.log_event(format!("{} {} back to free exploration", robot.0, robot.1));
+        }
+    }
+
+    // Seul un explorateur en état de marche reçoit une zone ; à quai, il est envoyé aussitôt
+    fn dispatch_explorer(&mut self, robot: RobotKey) -> bool {
+        if registry().read().unwrap().role_of(robot.0) != Some(RobotRole::Exploration) {
+            self.log_event(format!("{} {} is not an explorer", robot.0, robot.1));
+            return false;
+        }
+        if self.broken_robots.lock().unwrap().contains(&robot) {
+            self.log_event(format!("{} {} is broken down", robot.0, robot.1));
+            return false;
+        }
+        let mut pool = self.pool.lock().unwrap();
+        if pool.is_available(robot) {
+            pool.assign(robot, Box::new(|_| {}));
+            return true;
+        }
+        let active = pool
+            .fleet()
+            .iter()
+            .any(|entry| entry.robot == robot && entry.status == PoolStatus::Active);
+        drop(pool);
+        if !active {
+            self.log_event(format!("{} {} is not available", robot.0, robot.1));
+        }
+        active
+    }
+
+    // Pas imposé par la formation : le meneur avance quand son groupe suit, chaque suiveur
+    // rejoint sa place, ou la trace du meneur lorsque sa place est bloquée (couloir)
+    fn formation_action(
+        &self,
+        robot: &mut Box<dyn Robot + Send>,
+        order: FormationOrder,
+        perception: &Perception,
+    ) -> Action {
+        // Son comportement propre ne tourne plus : la perception est mémorisée ici
+        robot.knowledge_mut().observe(&perception.tiles);
+        match order {
+            FormationOrder::Lead { group, hold: true, .. } => {
+                self.formations.lock().unwrap().hold(group);
+                robot.note(String::from("waiting for the formation to close up"));
+                Action::Wait
+            }
+            FormationOrder::Lead { group, destination, .. } => {
+                match robot.calculate_next_step(destination.0, destination.1, perception) {
+                    Route::Step(x, y) => Action::Move(x, y),
+                    Route::Arrived => {
+                        if self.formations.lock().unwrap().disband(group).is_some() {
+                            self.log_event(format!(
+                                "Formation {} reached ({}, {})",
+                                group, destination.0, destination.1
+                            ));
+                        }
+                        Action::Wait
+                    }
+                    Route::Unreachable => {
+                        if self.formations.lock().unwrap().disband(group).is_some() {
+                            self.log_event(format!(
+                                "Formation {} cannot reach ({}, {}) and breaks up",
+                                group, destination.0, destination.1
+                            ));
                         }
-                        _ => {}
+                        Action::Wait
+                    }
+                }
+            }
+            FormationOrder::Follow { slot, fallback } => {
+                let position = robot.get_position();
+                let target = slot
+                    .filter(|(x, y)| (*x, *y) == position || perception.passable(robot.knowledge(), *x, *y))
+                    .unwrap_or(fallback);
+                if target == position {
+                    return Action::Wait;
+                }
+                if target.0.abs_diff(position.0) + target.1.abs_diff(position.1) == 1 {
+                    return Action::Move(target.0, target.1);
+                }
+                match robot.calculate_next_step(target.0, target.1, perception) {
+                    Route::Step(x, y) => Action::Move(x, y),
+                    Route::Arrived | Route::Unreachable => Action::Wait,
+                }
+            }
+        }
+    }
+
+    // Les actions qui modifient la carte passent par la phase de résolution de la base ;
+    // les autres ne concernent que le robot et sont appliquées tout de suite
+    fn perform(
+        &self,
+        robot: &mut Box<dyn Robot + Send>,
+        action: Action,
+        priority: u8,
+        sensor: &SensorSpec,
+        scanned: &mut Vec<MapTile>,
+        rng: &mut StdRng,
+    ) -> Result<String, String> {
+        let key = (robot.get_type(), robot.get_id());
+        match action {
+            Action::Move(..) | Action::Harvest(..) | Action::Build(..) => {
+                // On ne fait que sortir d'une zone interdite par le plus court chemin, jamais y entrer
+                if let Action::Move(x, y) = action {
+                    let map = self.map.read().unwrap();
+                    if map.restrictions.is_no_go(key.0, (x, y))
+                        && !map
+                            .restrictions
+                            .no_go(key.0, map.width, map.height)
+                            .allows(robot.get_position(), (x, y))
+                    {
+                        return Err(format!("({}, {}) is a no-go area", x, y));
+                    }
+                }
+                let outcome = self.arbiter.claim(Claim {
+                    robot: key,
+                    priority,
+                    position: robot.get_position(),
+                    action,
+                    free_weight: robot.free_cargo(),
+                })?;
+                match outcome {
+                    Outcome::Moved(x, y) => robot.set_position(x, y),
+                    Outcome::Harvested(position, deposit) => {
+                        robot.load_from(position, deposit);
+                        let remaining = match self.map.read().unwrap().get(position.0, position.1).tile {
+                            TileType::Resource(left) => left.scale,
+                            _ => 0,
+                        };
+                        self.deposits.lock().unwrap().harvest(
+                            position,
+                            deposit,
+                            remaining,
+                            key,
+                            self.tick.load(Ordering::SeqCst),
+                        );
                     }
+                    Outcome::Built(..) => {}
+                }
+                Ok(outcome.to_string())
+            }
+            // Balayage actif : portée doublée et détection parfaite
+            Action::Scan => {
+                let scan = SensorSpec {
+                    radius: sensor.radius * 2,
+                    ..SensorSpec::default()
+                };
+                *scanned = scan.sense(robot.get_position(), &self.map.read().unwrap(), rng);
+                let deposits = scanned
+                    .iter()
+                    .filter(|tile| matches!(tile.tile, TileType::Resource(_)))
+                    .count();
+                Ok(format!("scanned {} tiles, {} deposits", scanned.len(), deposits))
+            }
+            Action::Transmit(kind) => {
+                self.radio.lock().unwrap().broadcast(key, kind);
+                Ok(format!("transmitted {}", kind))
+            }
+            Action::Unload(depot_x, depot_y) => {
+                self.depot_in_reach(robot.as_ref(), (depot_x, depot_y))?;
+                let units = self
+                    .logistics
+                    .lock()
+                    .unwrap()
+                    .store((depot_x, depot_y), robot.drop_cargo());
+                Ok(format!("unloaded {} units at ({}, {})", units, depot_x, depot_y))
+            }
+            Action::Pickup(depot_x, depot_y) => {
+                self.depot_in_reach(robot.as_ref(), (depot_x, depot_y))?;
+                let taken = self
+                    .logistics
+                    .lock()
+                    .unwrap()
+                    .take((depot_x, depot_y), robot.free_cargo());
+                let units: u32 = taken
+                    .into_iter()
+                    .map(|resource| robot.load_from((depot_x, depot_y), resource))
+                    .sum();
+                Ok(format!("picked up {} units at ({}, {})", units, depot_x, depot_y))
+            }
+            Action::Wait => Ok(String::from("waited")),
+        }
+    }
 
-                    match remind {
-                        true => robot.set_state(RobotState::Harvesting),
-                        _ => self.join_thread(robot),
+    // Le contenu d'un dépôt ne passe pas par la carte : seul un dépôt construit et voisin est accepté
+    fn depot_in_reach(&self, robot: &(dyn Robot + Send), (depot_x, depot_y): (usize, usize)) -> Result<(), String> {
+        let (x, y) = robot.get_position();
+        if x.abs_diff(depot_x) + y.abs_diff(depot_y) > 1 {
+            return Err(format!("({}, {}) is out of reach", depot_x, depot_y));
+        }
+        let built_depot = self
+            .map
+            .read()
+            .unwrap()
+            .structures
+            .get(&(depot_x, depot_y))
+            .is_some_and(|structure| structure.built && structure.kind == StructureType::Depot);
+        match built_depot {
+            true => Ok(()),
+            false => Err(format!("no depot at ({}, {})", depot_x, depot_y)),
+        }
+    }
+
+    // À quai : démantèlement demandé, ou nouvelle mission à appliquer
+    fn follow_pool(&mut self, robot: &mut Box<dyn Robot + Send>) {
+        let key = (robot.get_type(), robot.get_id());
+        let (decommissioned, refit, assignment) = {
+            let mut pool = self.pool.lock().unwrap();
+            (pool.is_decommissioned(key), pool.take_refit(key), pool.take_assignment(key))
+        };
+        if decommissioned {
+            self.join_thread(robot);
+            self.log_event(format!("{} {} decommissioned", key.0, key.1));
+        } else if let Some(replacement) = refit {
+            self.apply_refit(robot, replacement);
+        } else if let Some(assignment) = assignment {
+            assignment(robot);
+            robot.deploy();
+        }
+    }
+
+    // Le robot à quai prend son nouvel équipement ; les tables de la base suivent sa nouvelle clé
+    fn apply_refit(&mut self, robot: &mut Box<dyn Robot + Send>, replacement: Box<dyn Robot + Send>) {
+        let old = (robot.get_type(), robot.get_id());
+        *robot = replacement;
+        let new = (robot.get_type(), robot.get_id());
+        self.pool.lock().unwrap().rekey(old, new);
+        self.release_task(old);
+        self.arbiter.rekey(old, new);
+        {
+            let mut traces = self.traces.lock().unwrap();
+            if let Some(trace) = traces.remove(&old) {
+                traces.insert(new, trace);
+            }
+        }
+        {
+            let mut pending_fits = self.pending_fits.lock().unwrap();
+            if let Some(fits) = pending_fits.remove(&old) {
+                pending_fits.insert(new, fits);
+            }
+        }
+        robot.note(format!("refitted from {}", old.0));
+        self.log_event(format!("{} {} refitted as {}", old.0, old.1, new.0));
+    }
+
+    pub fn deliver_cargo(&mut self, cargo: Vec<Resource>) {
+        let units = cargo.iter().map(|resource| resource.scale).sum();
+        let mut stock = self.stock.lock().unwrap();
+        for resource in cargo {
+            stock.add(resource);
+        }
+        let tick = self.tick.load(Ordering::SeqCst);
+        self.logistics.lock().unwrap().record_delivery(tick, units);
+    }
+
+    // Achète le niveau suivant pour tous les robots construits ensuite
+    pub fn buy_fleet_upgrade(&mut self, kind: UpgradeKind) {
+        let mut levels = self.fleet_upgrades.lock().unwrap();
+        let Some(level) = levels.next_level(kind) else {
+            drop(levels);
+            self.log_event(format!("{} upgrade already at max level", kind));
+            return;
+        };
+
+        if self.stock.lock().unwrap().spend(kind.currency(), kind.price(level)) {
+            levels.raise(kind);
+            drop(levels);
+            self.log_event(format!("Fleet {} upgraded to level {}", kind, level));
+        } else {
+            drop(levels);
+            self.log_event(format!(
+                "Not enough {:?} for {} level {} ({} needed)",
+                kind.currency(),
+                kind,
+                level,
+                kind.price(level)
+            ));
+        }
+    }
+
+    // L'amélioration est posée (et payée) au prochain passage du robot à la base, ou au tick suivant s'il est à quai
+    pub fn order_fit(&mut self, robot: (RobotType, usize), kind: UpgradeKind) {
+        self.pending_fits
+            .lock()
+            .unwrap()
+            .entry(robot)
+            .or_default()
+            .push(kind);
+    }
+
+    // Vrai si des améliorations attendaient le robot, posées ou refusées faute de stock
+    fn fit_pending_upgrades(&mut self, robot: &mut Box<dyn Robot + Send>, levels: &mut UpgradeLevels) -> bool {
+        let key = (robot.get_type(), robot.get_id());
+        let Some(kinds) = self.pending_fits.lock().unwrap().remove(&key) else {
+            return false;
+        };
+
+        for kind in kinds {
+            let Some(level) = levels.next_level(kind) else {
+                continue;
+            };
+            if self.stock.lock().unwrap().spend(kind.currency(), kind.price(level)) {
+                levels.raise(kind);
+                self.log_event(format!(
+                    "{} {} fitted with {} level {}",
+                    key.0, key.1, kind, level
+                ));
+            } else {
+                self.log_event(format!(
+                    "Not enough {:?} to fit {} on {} {}",
+                    kind.currency(),
+                    kind,
+                    key.0,
+                    key.1
+                ));
+            }
+        }
+        robot.apply_upgrades(levels);
+        true
+    }
+
+    fn dock_for_recharge(&mut self, robot: &mut Box<dyn Robot + Send>, resume_state: RobotState) {
+        self.deliver_cargo(robot.unload_cargo());
+        robot.set_state(resume_state);
+    }
+
+    fn break_down(&mut self, robot: &mut Box<dyn Robot + Send>, wear: &Wear) -> Breakdown {
+        let resume_state = robot.get_state();
+        let (x, y) = robot.get_position();
+        let robot_type = robot.get_type();
+        let robot_id = robot.get_id();
+
+        robot.set_state(RobotState::Broken);
+        let lost: u32 = robot.unload_cargo().iter().map(|resource| resource.scale).sum();
+        {
+            let mut map = self.map.write().unwrap();
+            if map.get(x, y).tile == TileType::Robot(robot_type) {
+                map.set(MapTile::new(x, y, TileType::BrokenRobot(robot_type)));
+            }
+        }
+        self.broken_robots.lock().unwrap().insert((robot_type, robot_id));
+        self.formations.lock().unwrap().leave((robot_type, robot_id));
+        self.radio
+            .lock()
+            .unwrap()
+            .broadcast((robot_type, robot_id), MessageKind::NeedHelp(x, y));
+        let released = self.release_task((robot_type, robot_id));
+        if let Some((task_x, task_y)) = released {
+            robot.set_order(None);
+            self.log_event(format!(
+                "Task ({}, {}) back to auction after {} {} broke down",
+                task_x, task_y, robot_type, robot_id
+            ));
+        }
+        self.log_event(format!(
+            "{} {} broke down at ({}, {}), {} units of cargo lost",
+            robot_type, robot_id, x, y, lost
+        ));
+
+        let order = RepairOrder {
+            patient: (robot_type, robot_id),
+            position: (x, y),
+            tow: wear.level() >= self.reliability.tow_threshold,
+            status: Arc::new(Mutex::new(RepairStatus::Waiting)),
+        };
+        let repair_order = order.clone();
+        self.send_role(RobotRole::Maintenance, move |repair| {
+            repair.set_order(Some(Order::Repair(repair_order)));
+        });
+
+        Breakdown {
+            resume_state,
+            order,
+            towed: false,
+        }
+    }
+
+    // Renvoie vrai lorsque le robot est de nouveau opérationnel
+    fn follow_repair(&mut self, robot: &mut Box<dyn Robot + Send>, breakdown: &mut Breakdown) -> bool {
+        let (x, y) = robot.get_position();
+        match breakdown.order.status() {
+            RepairStatus::Waiting => false,
+            RepairStatus::InTow => {
+                if !breakdown.towed {
+                    self.map.write().unwrap().vacate(x, y);
+                    self.sensor_footprints
+                        .lock()
+                        .unwrap()
+                        .remove(&(robot.get_type(), robot.get_id()));
+                    breakdown.towed = true;
+                }
+                false
+            }
+            RepairStatus::Repaired => {
+                {
+                    let mut map = self.map.write().unwrap();
+                    if map.get(x, y).tile == TileType::BrokenRobot(robot.get_type()) {
+                        map.set(MapTile::new(x, y, TileType::Robot(robot.get_type())));
                     }
                 }
+                self.resume_after_repair(robot, breakdown, "repaired in the field");
+                true
+            }
+            RepairStatus::Delivered => {
+                let (base_x, base_y) = self.map.read().unwrap().base_position;
+                robot.set_position(base_x, base_y);
+                self.resume_after_repair(robot, breakdown, "towed back to base");
+                true
             }
         }
     }
 
-    fn join_thread(&mut self, robot: &mut Box<dyn Robot + Send>) {
-        self.map.write().unwrap().set(MapTile::new(
-            robot.get_position().0,
-            robot.get_position().1,
-            TileType::Empty,
+    fn resume_after_repair(
+        &mut self,
+        robot: &mut Box<dyn Robot + Send>,
+        breakdown: &Breakdown,
+        how: &str,
+    ) {
+        robot.set_state(breakdown.resume_state);
+        self.broken_robots
+            .lock()
+            .unwrap()
+            .remove(&(robot.get_type(), robot.get_id()));
+        self.log_event(format!("{} {} {}", robot.get_type(), robot.get_id(), how));
+    }
+
+    // Secourt un robot qui ne progresse plus vers `goal`
+    fn recover(&mut self, robot: &mut Box<dyn Robot + Send>, recovery: Recovery, goal: (usize, usize)) {
+        let (robot_type, robot_id) = (robot.get_type(), robot.get_id());
+        self.log_event(format!(
+            "Watchdog: {} {} made no progress towards ({}, {}) for {} ticks, {}",
+            robot_type, robot_id, goal.0, goal.1, STALL_TICKS, recovery
         ));
+        let keeps_task = registry()
+            .read()
+            .unwrap()
+            .get(robot_type)
+            .is_none_or(|kind| kind.resumes_order);
+        match recovery {
+            Recovery::Replan => robot.knowledge_mut().forget_obstacles(),
+            Recovery::ReturnToBase if keeps_task => robot.set_state(RobotState::ReturningToBase),
+            Recovery::ReturnToBase | Recovery::Abandon => self.abandon_task(robot),
+        }
+    }
+
+    // Le robot rend sa mission, selon son type, et rentre à quai
+    fn abandon_task(&mut self, robot: &mut Box<dyn Robot + Send>) {
+        let handler = registry()
+            .read()
+            .unwrap()
+            .get(robot.get_type())
+            .map(|kind| kind.on_abandon);
+        if let Some(on_abandon) = handler {
+            on_abandon(self, robot);
+        }
+        robot.set_order(None);
+        self.deliver_cargo(robot.unload_cargo());
+        self.dock(robot);
+    }
+
+    // Un gisement abandonné est annoncé aux autres récolteurs, jusqu'à ce que trop d'entre eux y aient renoncé
+    pub fn abandon_deposit(&mut self, robot: RobotKey, position: (usize, usize)) {
+        let closed = self.allocator.lock().unwrap().abandon(robot, position);
+        let tick = self.tick.load(Ordering::SeqCst);
+        match closed {
+            true => {
+                self.deposits.lock().unwrap().lose(position, robot, tick);
+                self.log_event(format!("Deposit ({}, {}) marked unreachable", position.0, position.1));
+            }
+            false => {
+                self.deposits.lock().unwrap().release(position, tick);
+                self.log_event(format!(
+                    "Task ({}, {}) back to auction after {} {} gave up",
+                    position.0, position.1, robot.0, robot.1
+                ));
+            }
+        }
+    }
+
+    pub fn join_thread(&mut self, robot: &mut Box<dyn Robot + Send>) {
+        let (x, y) = robot.get_position();
+        self.map.write().unwrap().vacate(x, y);
+        self.forget((robot.get_type(), robot.get_id()));
         robot.set_state(RobotState::Idle);
     }
+
+    // Retire le robot des tables de la base et remet sa tâche en jeu
+    fn forget(&mut self, key: RobotKey) {
+        self.sensor_footprints.lock().unwrap().remove(&key);
+        self.running_nodes.lock().unwrap().remove(&key);
+        self.radio.lock().unwrap().forget(key);
+        self.teleop.lock().unwrap().forget(key);
+        self.formations.lock().unwrap().leave(key);
+        self.motions.lock().unwrap().forget(key);
+        self.zones.lock().unwrap().clear(key);
+        self.release_task(key);
+    }
+}
+
+// Résumé de la perception d'un tick pour la trace de décisions
+fn describe_inputs(sensed: &[MapTile], battery: Option<&Battery>, blocked_ticks: u32) -> String {
+    let deposits = sensed
+        .iter()
+        .filter(|tile| matches!(tile.tile, TileType::Resource(_)))
+        .count();
+    let mut inputs = format!("{} tiles seen, {} deposits", sensed.len(), deposits);
+    if let Some(battery) = battery {
+        inputs.push_str(&format!(", battery {}", battery));
+    }
+    if blocked_ticks > 0 {
+        inputs.push_str(&format!(", blocked for {} ticks", blocked_ticks));
+    }
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Décisions de chaque robot (position comprise) et journal de la base au bout de `ticks` ticks
+    fn replay(seed: u32, ticks: usize) -> (Vec<(RobotKey, Vec<Decision>)>, Vec<String>) {
+        let mut simulation = Simulation::new(seed);
+        simulation.factory.lock().unwrap().policy.keep_explorers = 3;
+        for _ in 0..ticks {
+            simulation.step();
+        }
+        let mut traces: Vec<(RobotKey, Vec<Decision>)> = simulation
+            .traces
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(robot, trace)| (*robot, trace.latest(usize::MAX)))
+            .collect();
+        traces.sort_by_key(|((robot_type, id), _)| (robot_type.name(), *id));
+        let events = simulation.events.lock().unwrap().iter().cloned().collect();
+        (traces, events)
+    }
+
+    #[test]
+    fn same_seed_replays_identically() {
+        let (traces, events) = replay(7, 120);
+        assert!(traces.len() >= 3);
+        assert!(traces.iter().any(|(_, decisions)| decisions
+            .windows(2)
+            .any(|pair| pair[0].position != pair[1].position)));
+
+        let (replayed_traces, replayed_events) = replay(7, 120);
+        assert_eq!(traces, replayed_traces);
+        assert_eq!(events, replayed_events);
+    }
 }
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use crate::environment::tile::{Resource, ResourceType};
+
+#[derive(Debug, Clone, Default)]
+pub struct Stock {
+    amounts: HashMap<ResourceType, u32>,
+}
+
+impl Stock {
+    pub fn new() -> Self {
+        Stock {
+            amounts: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, resource: Resource) {
+        *self.amounts.entry(resource.resource_type).or_insert(0) += resource.scale;
+    }
+
+    pub fn get(&self, resource_type: ResourceType) -> u32 {
+        self.amounts.get(&resource_type).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u32 {
+        self.amounts.values().sum()
+    }
+
+    // Dépense un coût composé seulement si chaque ressource reste au-dessus de sa réserve
+    pub fn spend_all(&mut self, cost: &[Resource], reserve: impl Fn(ResourceType) -> u32) -> bool {
+        let affordable = cost
+            .iter()
+            .all(|resource| self.get(resource.resource_type) >= resource.scale + reserve(resource.resource_type));
+        if affordable {
+            for resource in cost {
+                self.spend(resource.resource_type, resource.scale);
+            }
+        }
+        affordable
+    }
+
+    pub fn spend(&mut self, resource_type: ResourceType, amount: u32) -> bool {
+        let available = self.amounts.entry(resource_type).or_insert(0);
+        if *available < amount {
+            return false;
+        }
+        *available -= amount;
+        true
+    }
+}
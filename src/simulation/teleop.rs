@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+use super::radio::RobotKey;
+
+// Ordres en attente au-delà desquels les touches maintenues sont ignorées
+const MAX_QUEUED: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeleopCommand {
+    Move(isize, isize),
+    Harvest,
+    Scan,
+}
+
+// Pilotage manuel d'un seul robot : ses ordres remplacent sa mise à jour autonome
+#[derive(Debug, Clone, Default)]
+pub struct Teleop {
+    controlled: Option<RobotKey>,
+    commands: VecDeque<TeleopCommand>,
+}
+
+impl Teleop {
+    pub fn new() -> Self {
+        Teleop::default()
+    }
+
+    pub fn controlled(&self) -> Option<RobotKey> {
+        self.controlled
+    }
+
+    pub fn take_control(&mut self, robot: RobotKey) {
+        self.controlled = Some(robot);
+        self.commands.clear();
+    }
+
+    pub fn release(&mut self) -> Option<RobotKey> {
+        self.commands.clear();
+        self.controlled.take()
+    }
+
+    // Le robot quitte la carte : il n'y a plus rien à piloter
+    pub fn forget(&mut self, robot: RobotKey) {
+        if self.controlled == Some(robot) {
+            self.release();
+        }
+    }
+
+    pub fn push(&mut self, command: TeleopCommand) {
+        if self.controlled.is_some() && self.commands.len() < MAX_QUEUED {
+            self.commands.push_back(command);
+        }
+    }
+
+    pub fn next_command(&mut self, robot: RobotKey) -> Option<TeleopCommand> {
+        match self.controlled == Some(robot) {
+            true => self.commands.pop_front(),
+            false => None,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::robots::perception::Route;
+
+// Ticks sans se rapprocher de la même cible avant d'intervenir
+pub const STALL_TICKS: u32 = 20;
+
+// Interventions, de la plus légère à la plus radicale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    // Oublie les obstacles mémorisés et recalcule le chemin
+    Replan,
+    // Rentre à la base en gardant sa tâche
+    ReturnToBase,
+    // Rend sa tâche et rentre à quai
+    Abandon,
+}
+
+impl fmt::Display for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recovery::Replan => write!(f, "replanning"),
+            Recovery::ReturnToBase => write!(f, "returning to base"),
+            Recovery::Abandon => write!(f, "abandoning its task"),
+        }
+    }
+}
+
+// Suit la cible que poursuit un robot : il progresse s'il s'en rapproche, l'atteint ou travaille
+#[derive(Debug, Clone, Default)]
+pub struct Watchdog {
+    goal: Option<(usize, usize)>,
+    best: usize,
+    stalled: u32,
+    // Cible pour laquelle le robot a déjà été secouru, et combien de fois
+    rescued: Option<((usize, usize), u32)>,
+}
+
+impl Watchdog {
+    // `route` : dernière recherche de chemin du tick, None si le robot ne cherchait rien à atteindre
+    pub fn observe(
+        &mut self,
+        route: Option<((usize, usize), Route)>,
+        position: (usize, usize),
+        worked: bool,
+    ) -> Option<Recovery> {
+        let Some((goal, route)) = route else {
+            self.goal = None;
+            return None;
+        };
+        let distance = goal.0.abs_diff(position.0) + goal.1.abs_diff(position.1);
+        if worked || route == Route::Arrived {
+            if worked || self.rescued.is_some_and(|(rescued, _)| rescued == goal) {
+                self.rescued = None;
+            }
+            self.goal = None;
+            return None;
+        }
+        if self.goal != Some(goal) || distance < self.best {
+            self.goal = Some(goal);
+            self.best = distance;
+            self.stalled = 0;
+            return None;
+        }
+
+        self.stalled += 1;
+        if self.stalled < STALL_TICKS {
+            return None;
+        }
+        self.stalled = 0;
+        // Une cible qui résiste encore après une intervention appelle la suivante
+        let attempts = match self.rescued {
+            Some((rescued, attempts)) if rescued == goal => attempts + 1,
+            _ => 1,
+        };
+        self.rescued = Some((goal, attempts));
+        Some(match attempts {
+            1 => Recovery::Replan,
+            2 => Recovery::ReturnToBase,
+            _ => Recovery::Abandon,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOAL: (usize, usize) = (10, 10);
+    const STUCK: (usize, usize) = (2, 2);
+
+    // Le robot reste sur place sans trouver de chemin vers la cible pendant `ticks` ticks
+    fn stall(watchdog: &mut Watchdog, position: (usize, usize), ticks: u32) -> Vec<Recovery> {
+        (0..ticks)
+            .filter_map(|_| watchdog.observe(Some((GOAL, Route::Unreachable)), position, false))
+            .collect()
+    }
+
+    #[test]
+    fn a_stalled_robot_gets_stronger_recoveries() {
+        let mut watchdog = Watchdog::default();
+        // Le premier tick fixe la cible, puis chaque intervention attend STALL_TICKS ticks sans progrès
+        assert!(stall(&mut watchdog, STUCK, STALL_TICKS).is_empty());
+        assert_eq!(stall(&mut watchdog, STUCK, 1), vec![Recovery::Replan]);
+        assert_eq!(stall(&mut watchdog, STUCK, STALL_TICKS), vec![Recovery::ReturnToBase]);
+        assert_eq!(stall(&mut watchdog, STUCK, STALL_TICKS), vec![Recovery::Abandon]);
+        assert_eq!(stall(&mut watchdog, STUCK, STALL_TICKS), vec![Recovery::Abandon]);
+    }
+
+    #[test]
+    fn getting_closer_resets_the_count() {
+        let mut watchdog = Watchdog::default();
+        assert!(stall(&mut watchdog, STUCK, STALL_TICKS).is_empty());
+        // Un pas vers la cible juste avant l'échéance repart de zéro
+        assert!(stall(&mut watchdog, (3, 2), 1).is_empty());
+        assert!(stall(&mut watchdog, (3, 2), STALL_TICKS - 1).is_empty());
+        assert_eq!(stall(&mut watchdog, (3, 2), 1), vec![Recovery::Replan]);
+    }
+
+    #[test]
+    fn working_or_arriving_clears_past_recoveries() {
+        let mut watchdog = Watchdog::default();
+        assert_eq!(stall(&mut watchdog, STUCK, STALL_TICKS + 1), vec![Recovery::Replan]);
+
+        // Le robot récolte : la prochaine impasse recommence par un recalcul
+        assert_eq!(watchdog.observe(Some((GOAL, Route::Step(3, 2))), STUCK, true), None);
+        assert_eq!(stall(&mut watchdog, STUCK, STALL_TICKS + 1), vec![Recovery::Replan]);
+
+        // Arrivé à la cible secourue, il est lui aussi tiré d'affaire
+        assert_eq!(watchdog.observe(Some((GOAL, Route::Arrived)), GOAL, false), None);
+        assert_eq!(stall(&mut watchdog, STUCK, STALL_TICKS + 1), vec![Recovery::Replan]);
+    }
+
+    #[test]
+    fn a_robot_without_a_goal_is_not_watched() {
+        let mut watchdog = Watchdog::default();
+        for _ in 0..STALL_TICKS * 3 {
+            assert_eq!(watchdog.observe(None, STUCK, false), None);
+        }
+    }
+}
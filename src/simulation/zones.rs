@@ -0,0 +1,251 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::radio::RobotKey;
+
+// Pas passés à viser la même tuile avant de la considérer comme inaccessible
+const MAX_ATTEMPTS: u32 = 30;
+
+// Zone tracée par l'opérateur sur la carte
+#[derive(Debug, Clone, PartialEq)]
+pub enum Area {
+    // Deux coins opposés
+    Rectangle((usize, usize), (usize, usize)),
+    // Sommets dans l'ordre du tracé
+    Polygon(Vec<(usize, usize)>),
+}
+
+impl Area {
+    // Deux points donnent un rectangle, trois ou plus un polygone
+    pub fn from_points(points: &[(usize, usize)]) -> Option<Area> {
+        match points.len() {
+            0 | 1 => None,
+            2 => Some(Area::Rectangle(points[0], points[1])),
+            _ => Some(Area::Polygon(points.to_vec())),
+        }
+    }
+
+    // Contour à dessiner, dans l'ordre
+    pub fn outline(&self) -> Vec<(usize, usize)> {
+        match self {
+            Area::Rectangle((ax, ay), (bx, by)) => {
+                vec![(*ax, *ay), (*bx, *ay), (*bx, *by), (*ax, *by)]
+            }
+            Area::Polygon(vertices) => vertices.clone(),
+        }
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        match self {
+            Area::Rectangle((ax, ay), (bx, by)) => {
+                (*ax.min(bx)..=*ax.max(bx)).contains(&x) && (*ay.min(by)..=*ay.max(by)).contains(&y)
+            }
+            Area::Polygon(vertices) => {
+                let point = (x as f32, y as f32);
+                let edges = vertices
+                    .iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .map(|(a, b)| ((a.0 as f32, a.1 as f32), (b.0 as f32, b.1 as f32)));
+                let mut inside = false;
+                for (a, b) in edges {
+                    if on_segment(point, a, b) {
+                        return true;
+                    }
+                    // Lancer de rayon vers la droite : chaque côté traversé inverse le résultat
+                    if (a.1 > point.1) != (b.1 > point.1)
+                        && point.0 < a.0 + (point.1 - a.1) * (b.0 - a.0) / (b.1 - a.1)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+}
+
+fn on_segment(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> bool {
+    let cross = (b.0 - a.0) * (point.1 - a.1) - (b.1 - a.1) * (point.0 - a.0);
+    cross.abs() < f32::EPSILON
+        && point.0 >= a.0.min(b.0)
+        && point.0 <= a.0.max(b.0)
+        && point.1 >= a.1.min(b.1)
+        && point.1 <= a.1.max(b.1)
+}
+
+// Mission confiée à un explorateur par l'opérateur
+#[derive(Debug, Clone)]
+pub enum Mission {
+    // Couvrir toute la zone avec le capteur ; la mission s'arrête quand il ne reste rien à voir
+    Survey {
+        area: Area,
+        pending: HashSet<(usize, usize)>,
+    },
+    // Passer d'un point à l'autre, en boucle, jusqu'à ce que l'opérateur rappelle le robot
+    Patrol {
+        waypoints: Vec<(usize, usize)>,
+        next: usize,
+    },
+}
+
+impl fmt::Display for Mission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mission::Survey { pending, .. } => write!(f, "surveying, {} tiles left", pending.len()),
+            Mission::Patrol { waypoints, next } => {
+                let (x, y) = waypoints[*next];
+                write!(f, "patrolling, next point ({}, {})", x, y)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Zones {
+    missions: HashMap<RobotKey, Mission>,
+    // Tuile visée par chaque robot, et depuis combien de pas
+    targets: HashMap<RobotKey, ((usize, usize), u32)>,
+}
+
+impl Zones {
+    pub fn new() -> Self {
+        Zones::default()
+    }
+
+    // `tiles` : les tuiles de la zone qui restent à voir
+    pub fn survey(&mut self, robot: RobotKey, area: Area, tiles: HashSet<(usize, usize)>) {
+        self.targets.remove(&robot);
+        self.missions.insert(robot, Mission::Survey { area, pending: tiles });
+    }
+
+    pub fn patrol(&mut self, robot: RobotKey, waypoints: Vec<(usize, usize)>) {
+        self.targets.remove(&robot);
+        self.missions.insert(robot, Mission::Patrol { waypoints, next: 0 });
+    }
+
+    pub fn clear(&mut self, robot: RobotKey) -> bool {
+        self.targets.remove(&robot);
+        self.missions.remove(&robot).is_some()
+    }
+
+    pub fn missions(&self) -> &HashMap<RobotKey, Mission> {
+        &self.missions
+    }
+
+    // Tuiles vues par le robot ; vrai quand sa zone vient d'être entièrement couverte
+    pub fn cover(&mut self, robot: RobotKey, seen: &[(usize, usize)]) -> bool {
+        let Some(Mission::Survey { pending, .. }) = self.missions.get_mut(&robot) else {
+            return false;
+        };
+        for tile in seen {
+            pending.remove(tile);
+        }
+        if !pending.is_empty() {
+            return false;
+        }
+        self.clear(robot);
+        true
+    }
+
+    // Prochaine tuile à rejoindre : la plus proche encore à voir, ou le point de ronde suivant
+    pub fn waypoint(&mut self, robot: RobotKey, position: (usize, usize)) -> Option<(usize, usize)> {
+        let distance = |(x, y): (usize, usize)| x.abs_diff(position.0) + y.abs_diff(position.1);
+        // Une tuile visée trop longtemps est abandonnée : inaccessible, ou invisible depuis un passage
+        let stale = self
+            .targets
+            .get(&robot)
+            .filter(|(_, attempts)| *attempts >= MAX_ATTEMPTS)
+            .map(|(tile, _)| *tile);
+        let target = match self.missions.get_mut(&robot)? {
+            Mission::Survey { pending, .. } => {
+                if let Some(tile) = stale {
+                    pending.remove(&tile);
+                }
+                *pending.iter().min_by_key(|tile| (distance(**tile), **tile))?
+            }
+            Mission::Patrol { waypoints, next } => {
+                if stale.is_some() || distance(waypoints[*next]) <= 1 {
+                    *next = (*next + 1) % waypoints.len();
+                }
+                waypoints[*next]
+            }
+        };
+        let entry = self.targets.entry(robot).or_insert((target, 0));
+        match entry.0 == target && stale.is_none() {
+            true => entry.1 += 1,
+            false => *entry = (target, 1),
+        }
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robots::robot::RobotType;
+
+    const SCOUT: RobotKey = (RobotType::EXPLORER, 1);
+
+    fn tiles(area: &Area, size: usize) -> HashSet<(usize, usize)> {
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|tile| area.contains(*tile))
+            .collect()
+    }
+
+    #[test]
+    fn areas_contain_their_border_and_nothing_outside() {
+        let rectangle = Area::from_points(&[(5, 1), (2, 3)]).unwrap();
+        assert_eq!(tiles(&rectangle, 10).len(), 12);
+        assert!(rectangle.contains((2, 1)) && rectangle.contains((5, 3)));
+        assert!(!rectangle.contains((6, 2)));
+
+        // Triangle rectangle : l'hypoténuse fait partie de la zone
+        let triangle = Area::from_points(&[(0, 0), (4, 0), (0, 4)]).unwrap();
+        assert!(triangle.contains((2, 2)) && triangle.contains((1, 1)));
+        assert!(!triangle.contains((3, 2)));
+        assert_eq!(Area::from_points(&[(1, 1)]), None);
+    }
+
+    #[test]
+    fn a_survey_visits_the_nearest_pending_tile_until_covered() {
+        let area = Area::Rectangle((0, 0), (2, 0));
+        let mut zones = Zones::new();
+        zones.survey(SCOUT, area.clone(), tiles(&area, 5));
+
+        assert_eq!(zones.waypoint(SCOUT, (4, 0)), Some((2, 0)));
+        assert!(!zones.cover(SCOUT, &[(2, 0), (1, 0), (3, 0)]));
+        assert_eq!(zones.waypoint(SCOUT, (4, 0)), Some((0, 0)));
+        // Dernière tuile vue : la mission s'arrête et le robot n'a plus de cible
+        assert!(zones.cover(SCOUT, &[(0, 0)]));
+        assert!(zones.missions().is_empty());
+        assert_eq!(zones.waypoint(SCOUT, (0, 0)), None);
+    }
+
+    #[test]
+    fn an_unreachable_tile_is_dropped_from_the_survey() {
+        let area = Area::Rectangle((0, 0), (1, 0));
+        let mut zones = Zones::new();
+        zones.survey(SCOUT, area.clone(), tiles(&area, 5));
+
+        for _ in 0..MAX_ATTEMPTS {
+            assert_eq!(zones.waypoint(SCOUT, (3, 0)), Some((1, 0)));
+        }
+        assert_eq!(zones.waypoint(SCOUT, (3, 0)), Some((0, 0)));
+    }
+
+    #[test]
+    fn a_patrol_loops_over_its_waypoints() {
+        let mut zones = Zones::new();
+        zones.patrol(SCOUT, vec![(0, 0), (6, 0), (6, 6)]);
+
+        assert_eq!(zones.waypoint(SCOUT, (3, 3)), Some((0, 0)));
+        assert_eq!(zones.waypoint(SCOUT, (1, 0)), Some((6, 0)));
+        assert_eq!(zones.waypoint(SCOUT, (6, 0)), Some((6, 6)));
+        assert_eq!(zones.waypoint(SCOUT, (6, 5)), Some((0, 0)));
+        // Une ronde ne se termine jamais d'elle-même
+        assert!(!zones.cover(SCOUT, &[(0, 0), (6, 0), (6, 6)]));
+        assert!(zones.clear(SCOUT));
+        assert_eq!(zones.waypoint(SCOUT, (0, 0)), None);
+    }
+}
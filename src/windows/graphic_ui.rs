@@ -1,21 +1,100 @@
-use iced::widget::{Column, Container, Row, Space, Text};
-use iced::{executor, time, Application, Command, Element, Font, Length, Subscription, Theme};
+use iced::widget::{Checkbox, Column, Container, PickList, Row, Scrollable, Space, Text};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+use iced::keyboard::{self, KeyCode};
+use iced::{
+    event, executor, subscription, time, Application, Command, Element, Event, Font, Length,
+    Subscription, Theme,
+};
+
+use crate::environment::map::Map;
+use crate::environment::pheromone::{PheromoneKind, PheromoneSetting};
+use crate::environment::restriction::RestrictionKind;
+use crate::environment::structure::StructureType;
+use crate::environment::tile::ResourceType;
+use crate::robots::modules::refit_cost;
+use crate::robots::registry::{registry, RobotRole};
 use crate::robots::robot::RobotType;
+use crate::robots::upgrades::UpgradeKind;
+use crate::simulation::allocation::TaskStatus;
+use crate::simulation::deposits::DepositState;
+use crate::simulation::factory::{build_ticks, PolicySetting};
+use crate::simulation::formation::Shape;
+use crate::simulation::logistics::THROUGHPUT_WINDOW;
+use crate::simulation::pool::{FleetEntry, PoolStatus};
+use crate::simulation::radio::RobotKey;
+use crate::simulation::teleop::TeleopCommand;
+use crate::simulation::zones::Area;
 use crate::simulation::simulation::Simulation;
 
 use super::map_grid::MapGrid;
 use super::utils::create_button;
 
+// Décisions affichées dans l'inspecteur du robot sélectionné
+const INSPECTOR_DECISIONS: usize = 8;
+// Gisements listés, du plus récemment mis à jour au plus ancien
+const LISTED_DEPOSITS: usize = 12;
+
 pub struct MapWindow {
     simulation: Simulation,
     map_grid: MapGrid,
+    selected_structure: StructureType,
+    show_sensors: bool,
+    show_behaviour: bool,
+    show_deposits: bool,
+    show_pheromones: bool,
+    selected_upgrade: UpgradeKind,
+    selected_robot: RobotType,
+    selected_fleet_robot: Option<FleetEntry>,
+    selected_refit: RobotType,
+    group: Vec<RobotKey>,
+    selected_shape: Shape,
+    selected_target: GroupTarget,
+    // Tuiles cliquées sur la carte : coins d'une zone ou points d'une ronde
+    zone_points: Vec<(usize, usize)>,
+    selected_restriction: RestrictionKind,
+    selected_scope: RestrictionScope,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Tick,
-    SendExplorer,
+    SelectRobot(RobotType),
+    SendRobot,
+    SelectFleetRobot(FleetEntry),
+    Decommission,
+    SelectRefit(RobotType),
+    Refit,
+    AddToGroup,
+    ClearGroup,
+    SelectShape(Shape),
+    SelectTarget(GroupTarget),
+    SendGroup,
+    MapClicked(usize, usize),
+    SurveyZone,
+    PatrolPoints,
+    ClearPoints,
+    ClearZone,
+    SelectRestriction(RestrictionKind),
+    SelectScope(RestrictionScope),
+    Restrict,
+    LiftRestrictions,
+    TakeControl,
+    ReleaseControl,
+    Teleop(TeleopCommand),
+    ToggleSensors(bool),
+    ToggleBehaviour(bool),
+    ToggleDeposits(bool),
+    TogglePheromones(bool),
+    ShowPheromones(bool),
+    AdjustPheromones(PheromoneSetting, bool),
+    SelectStructure(StructureType),
+    SendBuilder,
+    SelectUpgrade(UpgradeKind),
+    BuyFleetUpgrade,
+    FitUpgrade,
+    AdjustPolicy(PolicySetting, bool),
     Pause,
     Play,
     UpSpeed,
@@ -36,12 +115,32 @@ impl Application for MapWindow {
         } else {
             Font::with_name("Noto Color Emoji")
         };
-        let map_grid = MapGrid::new(simulation.map.clone(), bas_font);
+        let map_grid = MapGrid::new(
+            simulation.map.clone(),
+            simulation.motions.clone(),
+            simulation.zones.clone(),
+            bas_font,
+        );
 
         (
             MapWindow {
                 map_grid,
                 simulation,
+                selected_structure: StructureType::RelayAntenna,
+                show_sensors: false,
+                show_behaviour: false,
+                show_deposits: false,
+                show_pheromones: false,
+                selected_upgrade: UpgradeKind::Speed,
+                selected_robot: RobotType::EXPLORER,
+                selected_fleet_robot: None,
+                selected_refit: RobotType::HARVESTER,
+                group: Vec::new(),
+                selected_shape: Shape::Line,
+                selected_target: GroupTarget::Base,
+                zone_points: Vec::new(),
+                selected_restriction: RestrictionKind::NoGo,
+                selected_scope: RestrictionScope::AllRobots,
             },
             Command::none(),
         )
@@ -55,14 +154,128 @@ impl Application for MapWindow {
         match message {
             Message::Tick => {
                 self.simulation.compute_fps();
-                if let Ok(mut map) = self.simulation.map.write() {
-                    self.map_grid.update(&mut map);
+                if let Ok(map) = self.simulation.map.read() {
+                    self.map_grid.update(&map);
                 } else {
                     eprintln!("Failed to lock map for update");
                 }
+                let footprints = match self.show_sensors {
+                    true => {
+                        let footprints = self.simulation.sensor_footprints.lock().unwrap();
+                        footprints.values().flatten().copied().collect()
+                    }
+                    false => HashSet::new(),
+                };
+                self.map_grid.set_highlighted(footprints);
+                let heatmap = match self.show_pheromones {
+                    true => pheromone_heatmap(&self.simulation.map.read().unwrap()),
+                    false => HashMap::new(),
+                };
+                self.map_grid.set_heatmap(heatmap);
+                self.map_grid.set_draft(self.zone_points.clone());
+            }
+            Message::SelectRobot(robot_type) => self.selected_robot = robot_type,
+            Message::SendRobot => self.simulation.queue_robot(self.selected_robot),
+            Message::SelectFleetRobot(entry) => self.selected_fleet_robot = Some(entry),
+            Message::Decommission => {
+                if let Some(entry) = self.selected_fleet_robot.take() {
+                    self.simulation.decommission(entry.robot);
+                }
+            }
+            Message::SelectRefit(robot_type) => self.selected_refit = robot_type,
+            Message::Refit => {
+                if let Some(entry) = self.selected_fleet_robot.take() {
+                    self.simulation.refit(entry.robot, self.selected_refit);
+                }
+            }
+            Message::AddToGroup => {
+                if let Some(entry) = self.selected_fleet_robot {
+                    if !self.group.contains(&entry.robot) {
+                        self.group.push(entry.robot);
+                    }
+                }
+            }
+            Message::ClearGroup => self.group.clear(),
+            Message::SelectShape(shape) => self.selected_shape = shape,
+            Message::SelectTarget(target) => self.selected_target = target,
+            Message::SendGroup => {
+                let destination = match self.selected_target {
+                    GroupTarget::Base => self.simulation.map.read().unwrap().base_position,
+                    GroupTarget::Deposit(x, y) => (x, y),
+                };
+                let members = std::mem::take(&mut self.group);
+                self.simulation
+                    .order_formation(members, self.selected_shape, destination);
+            }
+            Message::MapClicked(x, y) => self.zone_points.push((x, y)),
+            Message::SurveyZone => {
+                if let (Some(entry), Some(area)) =
+                    (self.selected_fleet_robot, Area::from_points(&self.zone_points))
+                {
+                    self.simulation.assign_survey(entry.robot, area);
+                    self.zone_points.clear();
+                }
+            }
+            Message::PatrolPoints => {
+                if let Some(entry) = self.selected_fleet_robot {
+                    let waypoints = std::mem::take(&mut self.zone_points);
+                    self.simulation.assign_patrol(entry.robot, waypoints);
+                }
+            }
+            Message::ClearPoints => self.zone_points.clear(),
+            Message::ClearZone => {
+                if let Some(entry) = self.selected_fleet_robot {
+                    self.simulation.clear_zone(entry.robot);
+                }
             }
-            Message::SendExplorer => {
-                self.simulation.send_robot(RobotType::Explorer, |_| {});
+            Message::SelectRestriction(kind) => self.selected_restriction = kind,
+            Message::SelectScope(scope) => self.selected_scope = scope,
+            // Un seul point contraint sa tuile, deux ou plus la zone tracée
+            Message::Restrict => {
+                let robot_type = match self.selected_scope {
+                    RestrictionScope::AllRobots => None,
+                    RestrictionScope::Only(robot_type) => Some(robot_type),
+                };
+                let points = std::mem::take(&mut self.zone_points);
+                match Area::from_points(&points) {
+                    Some(area) => self.simulation.restrict_area(self.selected_restriction, robot_type, &area),
+                    None => self
+                        .simulation
+                        .restrict(self.selected_restriction, robot_type, points.into_iter().collect()),
+                }
+            }
+            Message::LiftRestrictions => self.simulation.clear_restrictions(),
+            Message::TakeControl => {
+                if let Some(entry) = self.selected_fleet_robot {
+                    self.simulation.take_control(entry.robot);
+                }
+            }
+            Message::ReleaseControl => self.simulation.release_control(),
+            Message::Teleop(command) => self.simulation.send_teleop(command),
+            Message::ToggleSensors(show) => self.show_sensors = show,
+            Message::ToggleBehaviour(show) => self.show_behaviour = show,
+            Message::ToggleDeposits(show) => self.show_deposits = show,
+            Message::TogglePheromones(enabled) => {
+                self.simulation.map.write().unwrap().enable_pheromones(enabled);
+            }
+            Message::ShowPheromones(show) => self.show_pheromones = show,
+            Message::AdjustPheromones(setting, increase) => {
+                if let Some(field) = self.simulation.map.write().unwrap().pheromones.as_mut() {
+                    field.config.adjust(setting, increase);
+                }
+            }
+            Message::SelectStructure(kind) => self.selected_structure = kind,
+            Message::SendBuilder => self.simulation.send_builder(self.selected_structure),
+            Message::SelectUpgrade(kind) => self.selected_upgrade = kind,
+            Message::BuyFleetUpgrade => self.simulation.buy_fleet_upgrade(self.selected_upgrade),
+            Message::FitUpgrade => {
+                if let Some(entry) = self.selected_fleet_robot {
+                    self.simulation.order_fit(entry.robot, self.selected_upgrade);
+                }
+            }
+            Message::AdjustPolicy(setting, increase) => {
+                let mut factory = self.simulation.factory.lock().unwrap();
+                factory.policy.adjust(setting, increase);
             }
             Message::Pause => self.simulation.pause(),
             Message::Play => self.simulation.play(),
@@ -73,24 +286,378 @@ impl Application for MapWindow {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        time::every(std::time::Duration::from_millis(33)).map(|_| Message::Tick)
+        Subscription::batch([
+            time::every(std::time::Duration::from_millis(33)).map(|_| Message::Tick),
+            subscription::events_with(teleop_key),
+        ])
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let is_running = self
             .simulation
             .running
             .load(std::sync::atomic::Ordering::SeqCst);
 
-        let located_resources_count = {
-            let located_resources = self.simulation.located_resources.lock().unwrap();
-            located_resources.len()
+        let (deposit_counts, energy_left, minerals_left) = {
+            let deposits = self.simulation.deposits.lock().unwrap();
+            (
+                DepositState::ALL
+                    .iter()
+                    .map(|state| format!("{} {}", deposits.count(*state), state))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                deposits.remaining(ResourceType::Energy),
+                deposits.remaining(ResourceType::Mineral),
+            )
+        };
+        let (energy_count, mineral_count) = {
+            let stock = self.simulation.stock.lock().unwrap();
+            (
+                stock.get(ResourceType::Energy),
+                stock.get(ResourceType::Mineral),
+            )
+        };
+        let structures_count = {
+            let map = self.simulation.map.read().unwrap();
+            map.built_structures().len()
+        };
+        let broken_count = self.simulation.broken_robots.lock().unwrap().len();
+        let (radio_in_flight, radio_delivered) = {
+            let radio = self.simulation.radio.lock().unwrap();
+            (radio.in_flight(), radio.delivered)
+        };
+        let (tasks_open, tasks_awarded, tasks_done, tasks_unreachable) = {
+            let allocator = self.simulation.allocator.lock().unwrap();
+            (
+                allocator.count(|status| *status == TaskStatus::Open),
+                allocator.count(|status| matches!(status, TaskStatus::Awarded(_))),
+                allocator.count(|status| *status == TaskStatus::Done),
+                allocator.count(|status| *status == TaskStatus::Unreachable),
+            )
+        };
+        let (depot_stored, depot_dropped, depot_hauled, throughput) = {
+            let tick = self.simulation.tick.load(std::sync::atomic::Ordering::SeqCst);
+            let logistics = self.simulation.logistics.lock().unwrap();
+            (
+                logistics.stored(),
+                logistics.dropped,
+                logistics.hauled,
+                logistics.throughput(tick),
+            )
+        };
+        let fleet = self.simulation.fleet();
+        let docked_count = fleet
+            .iter()
+            .filter(|entry| entry.status == PoolStatus::Docked)
+            .count();
+        // Répartition par rôle actuel : un robot rééquipé change de ligne
+        let role_counts = {
+            let registry = registry().read().unwrap();
+            let mut counts: Vec<(RobotRole, usize)> = Vec::new();
+            for entry in &fleet {
+                let Some(role) = registry.role_of(entry.robot.0) else {
+                    continue;
+                };
+                match counts.iter_mut().find(|(counted, _)| *counted == role) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((role, 1)),
+                }
+            }
+            counts
+                .iter()
+                .map(|(role, count)| format!("{:?} {}", role, count))
+                .collect::<Vec<String>>()
+                .join(", ")
         };
-        let energy_count = self.simulation.energy_count.lock().unwrap();
         let simulation_status = format!(
-            "Simulation status\nFPS: {}\nResources located: {}\nEnergy: {}",
-            self.simulation.fps, located_resources_count, energy_count,
+            "Simulation status\nFPS: {}\nRobots: {} active, {} docked\nRoles: {}\nDeposits: {}\nStill out there: {} energy, {} minerals\nEnergy: {}\nMinerals: {}\nStructures: {}\nBroken robots: {}\nRadio: {} in flight, {} delivered\nTasks: {} open, {} awarded, {} done, {} unreachable\nDepots: {} stored, {} dropped, {} hauled\nSupply chain: {} units/{} ticks",
+            self.simulation.fps,
+            fleet.len() - docked_count,
+            docked_count,
+            role_counts,
+            deposit_counts,
+            energy_left,
+            minerals_left,
+            energy_count,
+            mineral_count,
+            structures_count,
+            broken_count,
+            radio_in_flight,
+            radio_delivered,
+            tasks_open,
+            tasks_awarded,
+            tasks_done,
+            tasks_unreachable,
+            depot_stored,
+            depot_dropped,
+            depot_hauled,
+            throughput,
+            THROUGHPUT_WINDOW,
+        );
+        let fleet_upgrades = *self.simulation.fleet_upgrades.lock().unwrap();
+        let upgrade_status = format!(
+            "Fleet: speed {} cargo {} sensor {} battery {}\n{}",
+            fleet_upgrades.speed,
+            fleet_upgrades.cargo,
+            fleet_upgrades.sensor,
+            fleet_upgrades.battery,
+            match fleet_upgrades.next_level(self.selected_upgrade) {
+                Some(level) => format!(
+                    "Next level: {} {:?}",
+                    self.selected_upgrade.price(level),
+                    self.selected_upgrade.currency()
+                ),
+                None => String::from("Max level reached"),
+            }
+        );
+        let (factory_status, policy) = {
+            let factory = self.simulation.factory.lock().unwrap();
+            let current = match factory.current {
+                Some((order, progress)) => format!(
+                    "Building {:?} {}/{}",
+                    order.robot_type,
+                    progress,
+                    build_ticks(order.robot_type)
+                ),
+                None => String::from("Factory idle"),
+            };
+            (
+                format!("{}\nQueued: {}", current, factory.queue.len()),
+                factory.policy,
+            )
+        };
+        let policy_controls = PolicySetting::ALL.iter().fold(
+            Column::new().spacing(5),
+            |column, setting| {
+                column.push(
+                    Row::new()
+                        .spacing(5)
+                        .push(
+                            Text::new(format!("{}: {}", setting, policy.value(*setting)))
+                                .size(14)
+                                .width(Length::FillPortion(3)),
+                        )
+                        .push(create_button("-", Message::AdjustPolicy(*setting, false), true))
+                        .push(create_button("+", Message::AdjustPolicy(*setting, true), true)),
+                )
+            },
         );
+        let pheromone_config = self
+            .simulation
+            .map
+            .read()
+            .unwrap()
+            .pheromones
+            .as_ref()
+            .map(|field| field.config);
+        let pheromone_controls = match pheromone_config {
+            Some(config) => PheromoneSetting::ALL.iter().fold(
+                Column::new().spacing(5),
+                |column, setting| {
+                    column.push(
+                        Row::new()
+                            .spacing(5)
+                            .push(
+                                Text::new(format!("{}: {:.2}", setting, config.value(*setting)))
+                                    .size(14)
+                                    .width(Length::FillPortion(3)),
+                            )
+                            .push(create_button("-", Message::AdjustPheromones(*setting, false), true))
+                            .push(create_button("+", Message::AdjustPheromones(*setting, true), true)),
+                    )
+                },
+            ),
+            None => Column::new(),
+        };
+        let events = {
+            let events = self.simulation.events.lock().unwrap();
+            events
+                .iter()
+                .rev()
+                .take(6)
+                .cloned()
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        let dispatchable_robots: Vec<RobotType> = registry()
+            .read()
+            .unwrap()
+            .kinds()
+            .iter()
+            .filter(|kind| kind.manual_dispatch)
+            .map(|kind| kind.robot_type)
+            .collect();
+
+        let (refit_targets, refit_status) = {
+            let registry = registry().read().unwrap();
+            let targets: Vec<RobotType> = registry
+                .kinds()
+                .iter()
+                .filter(|kind| !kind.config.modules.is_empty())
+                .map(|kind| kind.robot_type)
+                .collect();
+            let status = self
+                .selected_fleet_robot
+                .and_then(|entry| {
+                    let current = registry.get(entry.robot.0)?.config.modules;
+                    let target = registry.get(self.selected_refit)?.config.modules;
+                    Some((current, target))
+                })
+                .map(|(current, target)| match current.is_empty() {
+                    true => String::from("Specialised chassis, cannot be refitted"),
+                    false => {
+                        let cost = refit_cost(current, target)
+                            .iter()
+                            .map(|resource| format!("{} {:?}", resource.scale, resource.resource_type))
+                            .collect::<Vec<String>>();
+                        match cost.is_empty() {
+                            true => String::from("Refit cost: free"),
+                            false => format!("Refit cost: {}", cost.join(", ")),
+                        }
+                    }
+                })
+                .unwrap_or_default();
+            (targets, status)
+        };
+
+        // Destinations proposées aux groupes : la base et les gisements repérés
+        let group_targets: Vec<GroupTarget> = {
+            let deposits = self.simulation.deposits.lock().unwrap();
+            let mut targets = vec![GroupTarget::Base];
+            targets.extend(
+                deposits
+                    .deposits()
+                    .iter()
+                    .filter(|deposit| deposit.state.is_available())
+                    .map(|deposit| GroupTarget::Deposit(deposit.position.0, deposit.position.1)),
+            );
+            targets
+        };
+        let group_status = {
+            let pending = match self.group.is_empty() {
+                true => String::from("Group: empty"),
+                false => format!(
+                    "Group: {}",
+                    self.group
+                        .iter()
+                        .map(|(robot_type, id)| format!("{} {}", robot_type, id))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            };
+            let formations = self.simulation.formations.lock().unwrap();
+            formations.groups().iter().fold(pending, |status, formation| {
+                let (leader_type, leader_id) = formation.leader();
+                format!(
+                    "{}
+Formation {}: {} of {}, {} {} to ({}, {})",
+                    status,
+                    formation.id,
+                    formation.shape,
+                    formation.members.len(),
+                    leader_type,
+                    leader_id,
+                    formation.destination.0,
+                    formation.destination.1
+                )
+            })
+        };
+
+        let zone_status = {
+            let draft = match self.zone_points.len() {
+                0 => String::from("Click the map to place points"),
+                1 => String::from("1 point: patrol, or add a corner for a zone"),
+                2 => String::from("2 points: rectangle zone or patrol"),
+                count => format!("{} points: polygon zone or patrol", count),
+            };
+            let zones = self.simulation.zones.lock().unwrap();
+            let mut lines: Vec<String> = zones
+                .missions()
+                .iter()
+                .map(|((robot_type, id), mission)| format!("{} {}: {}", robot_type, id, mission))
+                .collect();
+            lines.sort();
+            lines.insert(0, draft);
+            lines.join("\n")
+        };
+
+        let restriction_scopes: Vec<RestrictionScope> = std::iter::once(RestrictionScope::AllRobots)
+            .chain(
+                registry()
+                    .read()
+                    .unwrap()
+                    .kinds()
+                    .iter()
+                    .map(|kind| RestrictionScope::Only(kind.robot_type)),
+            )
+            .collect();
+        let restriction_status = {
+            let map = self.simulation.map.read().unwrap();
+            map.restrictions
+                .rules()
+                .iter()
+                .map(|rule| {
+                    format!(
+                        "Constraint {}: {}, {} tiles, {}",
+                        rule.id,
+                        rule.kind,
+                        rule.tiles.len(),
+                        rule.robot_type
+                            .map_or(String::from("all robots"), |robot_type| robot_type.to_string())
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        let behaviour_status = match self.show_behaviour {
+            true => {
+                let running_nodes = self.simulation.running_nodes.lock().unwrap();
+                let mut lines: Vec<String> = running_nodes
+                    .iter()
+                    .map(|((robot_type, id), node)| format!("{} {}: {}", robot_type, id, node))
+                    .collect();
+                lines.sort();
+                lines.join("\n")
+            }
+            false => String::new(),
+        };
+
+        let deposit_list = match self.show_deposits {
+            true => self
+                .simulation
+                .deposits
+                .lock()
+                .unwrap()
+                .deposits()
+                .iter()
+                .take(LISTED_DEPOSITS)
+                .map(|deposit| deposit.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+            false => String::new(),
+        };
+
+        // Inspecteur : pourquoi le robot sélectionné a agi comme il l'a fait
+        let inspector = match self.selected_fleet_robot {
+            Some(entry) => self
+                .simulation
+                .decisions(entry.robot, INSPECTOR_DECISIONS)
+                .iter()
+                .map(|decision| decision.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+            None => String::new(),
+        };
+        let controlled = self.simulation.teleop.lock().unwrap().controlled();
+        let teleop_status = match controlled {
+            Some((robot_type, id)) => format!(
+                "Driving {} {}: arrows/WASD to move, H to harvest, C to scan",
+                robot_type, id
+            ),
+            None => String::from("Autonomous"),
+        };
 
         let toggle_simulation_state = || -> Message {
             match is_running {
@@ -105,11 +672,207 @@ impl Application for MapWindow {
             .width(Length::FillPortion(2))
             .push(Text::new(simulation_status).font(Font::with_name("Segoe UI Emoji")))
             .push(Space::with_height(20))
+            .push(
+                PickList::new(
+                    dispatchable_robots,
+                    Some(self.selected_robot),
+                    Message::SelectRobot,
+                )
+                .width(Length::Fill),
+            )
+            .push(create_button("Send Robot", Message::SendRobot, is_running))
+            .push(
+                PickList::new(fleet, self.selected_fleet_robot, Message::SelectFleetRobot)
+                    .placeholder("Fleet")
+                    .width(Length::Fill),
+            )
+            .push(create_button(
+                "Decommission",
+                Message::Decommission,
+                self.selected_fleet_robot.is_some(),
+            ))
+            .push(
+                Row::new()
+                    .push(
+                        PickList::new(refit_targets, Some(self.selected_refit), Message::SelectRefit)
+                            .width(Length::Fill),
+                    )
+                    .push(Space::with_width(10))
+                    .push(create_button(
+                        "Refit",
+                        Message::Refit,
+                        self.selected_fleet_robot.is_some(),
+                    )),
+            )
+            .push(Text::new(refit_status).size(14))
+            .push(
+                Row::new()
+                    .push(create_button(
+                        "Add to group",
+                        Message::AddToGroup,
+                        self.selected_fleet_robot.is_some(),
+                    ))
+                    .push(Space::with_width(10))
+                    .push(create_button("Clear group", Message::ClearGroup, !self.group.is_empty())),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        PickList::new(&Shape::ALL[..], Some(self.selected_shape), Message::SelectShape)
+                            .width(Length::Fill),
+                    )
+                    .push(Space::with_width(10))
+                    .push(
+                        PickList::new(group_targets, Some(self.selected_target), Message::SelectTarget)
+                            .width(Length::Fill),
+                    ),
+            )
             .push(create_button(
-                "Send Explorer",
-                Message::SendExplorer,
-                is_running,
+                "Send group",
+                Message::SendGroup,
+                is_running && self.group.len() > 1,
+            ))
+            .push(Text::new(group_status).size(14))
+            .push(
+                Row::new()
+                    .push(create_button(
+                        "Survey zone",
+                        Message::SurveyZone,
+                        is_running && self.selected_fleet_robot.is_some() && self.zone_points.len() > 1,
+                    ))
+                    .push(Space::with_width(10))
+                    .push(create_button(
+                        "Patrol points",
+                        Message::PatrolPoints,
+                        is_running && self.selected_fleet_robot.is_some() && !self.zone_points.is_empty(),
+                    )),
+            )
+            .push(
+                Row::new()
+                    .push(create_button("Clear points", Message::ClearPoints, !self.zone_points.is_empty()))
+                    .push(Space::with_width(10))
+                    .push(create_button(
+                        "Free explore",
+                        Message::ClearZone,
+                        self.selected_fleet_robot.is_some(),
+                    )),
+            )
+            .push(Text::new(zone_status).size(14))
+            .push(
+                Row::new()
+                    .push(
+                        PickList::new(
+                            &RestrictionKind::ALL[..],
+                            Some(self.selected_restriction),
+                            Message::SelectRestriction,
+                        )
+                        .width(Length::Fill),
+                    )
+                    .push(Space::with_width(10))
+                    .push(
+                        PickList::new(restriction_scopes, Some(self.selected_scope), Message::SelectScope)
+                            .width(Length::Fill),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .push(create_button("Restrict", Message::Restrict, !self.zone_points.is_empty()))
+                    .push(Space::with_width(10))
+                    .push(create_button("Lift constraints", Message::LiftRestrictions, true)),
+            )
+            .push(Text::new(restriction_status).size(14))
+            .push(
+                Row::new()
+                    .push(create_button(
+                        "Take control",
+                        Message::TakeControl,
+                        is_running && self.selected_fleet_robot.is_some(),
+                    ))
+                    .push(Space::with_width(10))
+                    .push(create_button("Release", Message::ReleaseControl, controlled.is_some())),
+            )
+            .push(
+                Row::new()
+                    .push(create_button(
+                        "Harvest",
+                        Message::Teleop(TeleopCommand::Harvest),
+                        controlled.is_some(),
+                    ))
+                    .push(Space::with_width(10))
+                    .push(create_button(
+                        "Scan",
+                        Message::Teleop(TeleopCommand::Scan),
+                        controlled.is_some(),
+                    )),
+            )
+            .push(Text::new(teleop_status).size(14))
+            .push(Text::new(inspector).size(12))
+            .push(Checkbox::new(
+                "Show sensors",
+                self.show_sensors,
+                Message::ToggleSensors,
+            ))
+            .push(Checkbox::new(
+                "Show behaviour",
+                self.show_behaviour,
+                Message::ToggleBehaviour,
+            ))
+            .push(Text::new(behaviour_status).size(12))
+            .push(Checkbox::new(
+                "Show deposits",
+                self.show_deposits,
+                Message::ToggleDeposits,
+            ))
+            .push(Text::new(deposit_list).size(12))
+            .push(Checkbox::new(
+                "Pheromones",
+                pheromone_config.is_some(),
+                Message::TogglePheromones,
+            ))
+            .push(Checkbox::new(
+                "Show pheromones",
+                self.show_pheromones,
+                Message::ShowPheromones,
             ))
+            .push(pheromone_controls)
+            .push(Space::with_height(20))
+            .push(
+                PickList::new(
+                    &StructureType::ALL[..],
+                    Some(self.selected_structure),
+                    Message::SelectStructure,
+                )
+                .width(Length::Fill),
+            )
+            .push(Text::new(format!(
+                "Cost: {} minerals, {} ticks",
+                self.selected_structure.mineral_cost(),
+                self.selected_structure.build_ticks()
+            )))
+            .push(create_button("Send Builder", Message::SendBuilder, is_running))
+            .push(Space::with_height(20))
+            .push(Text::new(factory_status).size(14))
+            .push(policy_controls)
+            .push(Space::with_height(20))
+            .push(
+                PickList::new(
+                    &UpgradeKind::ALL[..],
+                    Some(self.selected_upgrade),
+                    Message::SelectUpgrade,
+                )
+                .width(Length::Fill),
+            )
+            .push(Text::new(upgrade_status).size(14))
+            .push(
+                Row::new()
+                    .push(create_button("New robots", Message::BuyFleetUpgrade, true))
+                    .push(Space::with_width(10))
+                    .push(create_button(
+                        "Fit on dock",
+                        Message::FitUpgrade,
+                        is_running && self.selected_fleet_robot.is_some(),
+                    )),
+            )
             .push(Space::with_height(20))
             .push(create_button("Play/Pause", toggle_simulation_state(), true))
             .push(
@@ -117,15 +880,17 @@ impl Application for MapWindow {
                     .push(create_button("Speed +", Message::UpSpeed, true))
                     .push(Space::with_width(10))
                     .push(create_button("Speed -", Message::DownSpeed, true)),
-            );
+            )
+            .push(Space::with_height(20))
+            .push(Text::new(events).size(14));
 
-        let map = self.map_grid.view().map(|_| Message::Tick);
+        let map = self.map_grid.view();
 
         Container::new(
             Row::new()
                 .push(Container::new(map)
                 .width(Length::FillPortion(8)))
-                .push(controls),
+                .push(Scrollable::new(controls).width(Length::FillPortion(2))),
         )
         .width(Length::Fill)
         .height(Length::Fill)
@@ -133,3 +898,80 @@ impl Application for MapWindow {
         .into()
     }
 }
+
+// Destination d'un groupe choisie dans l'interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupTarget {
+    Base,
+    Deposit(usize, usize),
+}
+
+impl fmt::Display for GroupTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupTarget::Base => write!(f, "Base"),
+            GroupTarget::Deposit(x, y) => write!(f, "Deposit ({}, {})", x, y),
+        }
+    }
+}
+
+// Robots concernés par une zone interdite ou à éviter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestrictionScope {
+    AllRobots,
+    Only(RobotType),
+}
+
+impl fmt::Display for RestrictionScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestrictionScope::AllRobots => write!(f, "All robots"),
+            RestrictionScope::Only(robot_type) => write!(f, "{} only", robot_type),
+        }
+    }
+}
+
+// Chaque type de trace est ramené entre 0 et 1 par rapport à sa tuile la plus marquée
+fn pheromone_heatmap(map: &Map) -> HashMap<(usize, usize), (f32, f32)> {
+    let Some(field) = map.pheromones.as_ref() else {
+        return HashMap::new();
+    };
+    let normalise = |kind: PheromoneKind, x: usize, y: usize| match field.max_level(kind) {
+        max if max > 0.0 => field.level(kind, x, y) / max,
+        _ => 0.0,
+    };
+
+    let mut heatmap = HashMap::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let levels = (
+                normalise(PheromoneKind::Explored, x, y),
+                normalise(PheromoneKind::ResourcePath, x, y),
+            );
+            if levels.0 > 0.0 || levels.1 > 0.0 {
+                heatmap.insert((x, y), levels);
+            }
+        }
+    }
+    heatmap
+}
+
+// Les touches ne pilotent que le robot pris en main ; sans robot, les ordres sont ignorés
+fn teleop_key(event: Event, status: event::Status) -> Option<Message> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) = event else {
+        return None;
+    };
+    if status == event::Status::Captured {
+        return None;
+    }
+    let command = match key_code {
+        KeyCode::Up | KeyCode::W => TeleopCommand::Move(0, -1),
+        KeyCode::Down | KeyCode::S => TeleopCommand::Move(0, 1),
+        KeyCode::Left | KeyCode::A => TeleopCommand::Move(-1, 0),
+        KeyCode::Right | KeyCode::D => TeleopCommand::Move(1, 0),
+        KeyCode::H => TeleopCommand::Harvest,
+        KeyCode::C => TeleopCommand::Scan,
+        _ => return None,
+    };
+    Some(Message::Teleop(command))
+}
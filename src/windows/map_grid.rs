@@ -1,22 +1,61 @@
 use crate::environment::map::Map;
-use iced::widget::{Column, Container, Row, Text};
-use iced::{Element, Font, Length};
-use std::sync::{Arc, RwLock};
+use crate::environment::restriction::RestrictionKind;
+use crate::environment::tile::TileType;
+use crate::simulation::motion::Motions;
+use crate::simulation::zones::{Mission, Zones};
+use iced::widget::canvas::{self, event, Canvas, Frame, Geometry, Path, Stroke};
+use iced::widget::{Container, Text};
+use iced::{alignment, mouse, Color, Element, Font, Length, Point, Rectangle, Renderer, Size, Theme};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
 use super::graphic_ui::Message;
 
+// Taille d'une tuile à l'écran, espacement compris
+const CELL_SIZE: f32 = 30.0;
+const CELL_PITCH: f32 = 31.0;
+
 pub struct MapGrid {
     map: Arc<RwLock<Map>>,
+    motions: Arc<Mutex<Motions>>,
+    zones: Arc<Mutex<Zones>>,
     pub font: Font,
     map_content: String,
+    highlighted: HashSet<(usize, usize)>,
+    // Intensités normalisées (déjà exploré, chemin de ressource) par tuile
+    heatmap: HashMap<(usize, usize), (f32, f32)>,
+    // Points cliqués par l'opérateur, pas encore confiés à un explorateur
+    draft: Vec<(usize, usize)>,
 }
 
 impl MapGrid {
-    pub fn new(map: Arc<RwLock<Map>>, font: Font) -> Self {
-        Self { map, font, map_content: String::new() }
+    pub fn new(map: Arc<RwLock<Map>>, motions: Arc<Mutex<Motions>>, zones: Arc<Mutex<Zones>>, font: Font) -> Self {
+        Self {
+            map,
+            motions,
+            zones,
+            font,
+            map_content: String::new(),
+            highlighted: HashSet::new(),
+            heatmap: HashMap::new(),
+            draft: Vec::new(),
+        }
+    }
+
+    pub fn set_highlighted(&mut self, tiles: HashSet<(usize, usize)>) {
+        self.highlighted = tiles;
+    }
+
+    pub fn set_heatmap(&mut self, heatmap: HashMap<(usize, usize), (f32, f32)>) {
+        self.heatmap = heatmap;
     }
 
-    pub fn update(&mut self, map: &mut Map) {
+    pub fn set_draft(&mut self, draft: Vec<(usize, usize)>) {
+        self.draft = draft;
+    }
+
+    pub fn update(&mut self, map: &Map) {
         let mut map_content = String::new();
         for y in 0..map.height {
             for x in 0..map.width {
@@ -28,40 +67,184 @@ impl MapGrid {
         self.map_content = map_content;
     }
 
-    pub fn view(&self) -> Element<Message> {
-        if let Ok(map) = self.map.read() {
-            let mut rows = Column::new().spacing(1);
+    pub fn view(&self) -> Element<'_, Message> {
+        let (width, height) = match self.map.read() {
+            Ok(map) => (map.width, map.height),
+            Err(_) => {
+                return Container::new(Text::new("Failed to lock map").font(self.font))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x()
+                    .center_y()
+                    .into()
+            }
+        };
+
+        Container::new(
+            Canvas::new(self)
+                .width(Length::Fixed(width as f32 * CELL_PITCH))
+                .height(Length::Fixed(height as f32 * CELL_PITCH)),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+
+    fn glyph(&self, glyph: char, corner: Point) -> canvas::Text {
+        canvas::Text {
+            content: glyph.to_string(),
+            position: Point::new(corner.x + CELL_SIZE / 2.0, corner.y + CELL_SIZE / 2.0),
+            font: self.font,
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Center,
+            ..canvas::Text::default()
+        }
+    }
 
-            for y in 0..map.height {
-                let mut row = Row::new().spacing(1);
+    // Trait reliant le centre des tuiles données, refermé pour une zone
+    fn outline(&self, frame: &mut Frame, points: &[(usize, usize)], closed: bool, color: Color) {
+        let center = |(x, y): (usize, usize)| {
+            Point::new(x as f32 * CELL_PITCH + CELL_SIZE / 2.0, y as f32 * CELL_PITCH + CELL_SIZE / 2.0)
+        };
+        let Some(first) = points.first() else {
+            return;
+        };
+        let path = Path::new(|builder| {
+            builder.move_to(center(*first));
+            for point in &points[1..] {
+                builder.line_to(center(*point));
+            }
+            if closed {
+                builder.close();
+            }
+        });
+        frame.stroke(&path, Stroke::default().with_color(color).with_width(2.0));
+        for point in points {
+            frame.fill(&Path::circle(center(*point), 4.0), color);
+        }
+    }
+}
 
-                for x in 0..map.width {
-                    let emoji = map.get(x, y).tile.char().to_string();
-                    let cell = Container::new(Text::new(emoji).font(self.font))
-                        .width(Length::Fixed(30.0))
-                        .height(Length::Fixed(30.0))
-                        .center_x()
-                        .center_y();
+impl canvas::Program<Message> for MapGrid {
+    type State = ();
 
-                    row = row.push(cell);
+    // Un clic gauche désigne une tuile, pour tracer une zone ou une ronde
+    fn update(
+        &self,
+        _state: &mut (),
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        if let canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            if let Some(position) = cursor.position_in(bounds) {
+                let (x, y) = ((position.x / CELL_PITCH) as usize, (position.y / CELL_PITCH) as usize);
+                return (event::Status::Captured, Some(Message::MapClicked(x, y)));
+            }
+        }
+        (event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let Ok(map) = self.map.read() else {
+            return Vec::new();
+        };
+        let moving = self.motions.lock().unwrap().positions(Instant::now());
+        let zones = self.zones.lock().unwrap();
+        let pending: HashSet<(usize, usize)> = zones
+            .missions()
+            .values()
+            .filter_map(|mission| match mission {
+                Mission::Survey { pending, .. } => Some(pending.iter().copied()),
+                Mission::Patrol { .. } => None,
+            })
+            .flatten()
+            .collect();
+        let cell = Size::new(CELL_SIZE, CELL_SIZE);
+        let origin = |x: f32, y: f32| Point::new(x * CELL_PITCH, y * CELL_PITCH);
+        let mut robots = Vec::new();
+
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let tile = map.get(x, y).tile;
+                let corner = origin(x as f32, y as f32);
+
+                // Sol accidenté, plus lent à traverser
+                if map.is_rough(x, y) && !matches!(tile, TileType::Terrain) {
+                    frame.fill_rectangle(corner, cell, Color::from_rgba(0.55, 0.4, 0.25, 0.2));
                 }
 
-                rows = rows.push(row);
+                // Bleu pour les zones explorées, orange pour les chemins de ressource
+                if let Some(&(explored, path)) = self.heatmap.get(&(x, y)) {
+                    let strength = explored.max(path);
+                    let red = 0.2 + 0.8 * path;
+                    let green = 0.3 + 0.3 * path;
+                    let blue = 1.0 - 0.8 * path;
+                    frame.fill_rectangle(corner, cell, Color::from_rgba(red, green, blue, 0.6 * strength));
+                }
+
+                // Rouge pour les zones interdites, orange pour celles à éviter
+                for rule in map.restrictions.rules().iter().filter(|rule| rule.tiles.contains(&(x, y))) {
+                    let color = match rule.kind {
+                        RestrictionKind::NoGo => Color::from_rgba(0.9, 0.1, 0.1, 0.3),
+                        RestrictionKind::Avoid => Color::from_rgba(1.0, 0.5, 0.0, 0.2),
+                    };
+                    frame.fill_rectangle(corner, cell, color);
+                }
+
+                // Tuiles d'une zone assignée que l'explorateur n'a pas encore vues
+                if pending.contains(&(x, y)) {
+                    frame.fill_rectangle(corner, cell, Color::from_rgba(1.0, 0.8, 0.2, 0.25));
+                }
+
+                if self.highlighted.contains(&(x, y)) {
+                    frame.fill_rectangle(corner, cell, Color::from_rgba(0.2, 0.6, 1.0, 0.25));
+                }
+
+                // Un robot en mouvement est dessiné après la grille, entre ses deux tuiles
+                match (tile, moving.get(&(x, y))) {
+                    (TileType::Robot(_), Some(&(moving_x, moving_y))) => {
+                        robots.push((tile.char(), origin(moving_x, moving_y)))
+                    }
+                    _ => frame.fill_text(self.glyph(tile.char(), corner)),
+                }
+            }
+        }
+
+        // Zones en jaune, rondes (en boucle) en vert, chacune étiquetée du robot qui en a la charge
+        for ((robot_type, id), mission) in zones.missions() {
+            let (points, color) = match mission {
+                Mission::Survey { area, .. } => (area.outline(), Color::from_rgb(1.0, 0.7, 0.1)),
+                Mission::Patrol { waypoints, .. } => (waypoints.clone(), Color::from_rgb(0.2, 0.8, 0.4)),
+            };
+            self.outline(&mut frame, &points, true, color);
+            if let Some((x, y)) = points.first() {
+                frame.fill_text(canvas::Text {
+                    content: format!("{} {}", robot_type, id),
+                    position: origin(*x as f32, *y as f32),
+                    color,
+                    size: 14.0,
+                    vertical_alignment: alignment::Vertical::Bottom,
+                    ..canvas::Text::default()
+                });
             }
+        }
+        self.outline(&mut frame, &self.draft, false, Color::from_rgb(0.9, 0.2, 0.9));
 
-            Container::new(rows)
-                .width(Length::Fill) 
-                .height(Length::Fill)
-                .center_x()
-                .center_y()
-                .into()
-        } else {
-            Container::new(Text::new("Failed to lock map").font(self.font))
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .center_x()
-                .center_y()
-                .into()
+        for (glyph, corner) in robots {
+            frame.fill_text(self.glyph(glyph, corner));
         }
+
+        vec![frame.into_geometry()]
     }
-}
\ No newline at end of file
+}
@@ -2,7 +2,7 @@ use iced::{widget::{Button, Text}, Application, Length, Settings};
 use crate::simulation::simulation::Simulation;
 use super::graphic_ui::{MapWindow, Message};
 
-pub fn create_button(label: &str, message: Message, enabled: bool ) -> Button<Message> {
+pub fn create_button(label: &str, message: Message, enabled: bool ) -> Button<'_, Message> {
     let mut button = Button::new(
         Text::new(label)
             .horizontal_alignment(iced::alignment::Horizontal::Center)